@@ -1,8 +1,9 @@
 "use client";
 
-import { Trash2 } from "lucide-react";
+import { RotateCcw, Trash2 } from "lucide-react";
+import { toast } from "sonner";
 import { useHistory, formatBytes } from "@/lib/hooks/useHistory";
-import { useTranslation } from "@/lib/i18n";
+import { translateRustError, useTranslation } from "@/lib/i18n";
 
 /**
  * ISO 8601のタイムスタンプをローカル時刻の "HH:MM" 形式にフォーマットする
@@ -13,9 +14,27 @@ function formatTime(timestamp: string): string {
 }
 
 export default function HistoryPage() {
-	const { history, stats, loading, clearHistory } = useHistory();
+	const { history, stats, loading, clearHistory, retryEntry, retryAllFailed } = useHistory();
 	const { t } = useTranslation();
 
+	const handleRetry = async (id: string) => {
+		try {
+			await retryEntry(id);
+			toast.success(t("history.retryQueued", { count: 1 }));
+		} catch (e) {
+			toast.error(translateRustError(String(e), t));
+		}
+	};
+
+	const handleRetryAllFailed = async () => {
+		try {
+			const ids = await retryAllFailed();
+			toast.success(t("history.retryQueued", { count: ids.length }));
+		} catch (e) {
+			toast.error(translateRustError(String(e), t));
+		}
+	};
+
 	const statsCards = [
 		{ label: t("history.today"), value: String(stats.todayCount), desc: t("history.downloads"), size: formatBytes(stats.todaySize) },
 		{ label: t("history.thisWeek"), value: String(stats.weekCount), desc: t("history.downloads"), size: formatBytes(stats.weekSize) },
@@ -40,14 +59,24 @@ export default function HistoryPage() {
 						{t("history.description")}
 					</p>
 				</div>
-				<button
-					type="button"
-					onClick={clearHistory}
-					className="flex items-center gap-2 rounded-lg bg-[#1E293B] px-4 py-2.5 text-[13px] font-medium text-[#94A3B8] transition-colors hover:bg-[#1E293B]/80"
-				>
-					<Trash2 className="size-4" />
-					{t("history.clearHistory")}
-				</button>
+				<div className="flex items-center gap-2">
+					<button
+						type="button"
+						onClick={handleRetryAllFailed}
+						className="flex items-center gap-2 rounded-lg bg-[#1E293B] px-4 py-2.5 text-[13px] font-medium text-[#94A3B8] transition-colors hover:bg-[#1E293B]/80"
+					>
+						<RotateCcw className="size-4" />
+						{t("history.retryFailed")}
+					</button>
+					<button
+						type="button"
+						onClick={clearHistory}
+						className="flex items-center gap-2 rounded-lg bg-[#1E293B] px-4 py-2.5 text-[13px] font-medium text-[#94A3B8] transition-colors hover:bg-[#1E293B]/80"
+					>
+						<Trash2 className="size-4" />
+						{t("history.clearHistory")}
+					</button>
+				</div>
 			</div>
 
 			{/* Stats Row */}
@@ -137,6 +166,14 @@ export default function HistoryPage() {
 										>
 											{item.size != null ? formatBytes(item.size) : "— —"}
 										</span>
+										<button
+											type="button"
+											onClick={() => handleRetry(item.id)}
+											title={t("history.retry")}
+											className="shrink-0 rounded p-1 text-[#64748B] transition-colors hover:text-foreground"
+										>
+											<RotateCcw className="size-3.5" />
+										</button>
 									</div>
 								))}
 							</div>
@@ -21,7 +21,7 @@ import {
 } from "lucide-react";
 import { Switch } from "@/components/ui/switch";
 import { useAppVersion } from "@/lib/hooks/useAppVersion";
-import { useSettings } from "@/lib/hooks/useSettings";
+import { useSettings, type BinaryVersionReport } from "@/lib/hooks/useSettings";
 import { FORMAT_OPTIONS } from "@/lib/utils";
 import { useTranslation, type Locale } from "@/lib/i18n";
 
@@ -124,7 +124,9 @@ export default function SettingsPage() {
 	};
 
 	useEffect(() => {
-		invoke<string>("get_yt_dlp_version").then(setYtDlpVersion).catch(() => setYtDlpVersion("not found"));
+		invoke<BinaryVersionReport>("get_yt_dlp_version")
+			.then((report) => setYtDlpVersion(report.ytDlp.installed ?? "not found"))
+			.catch(() => setYtDlpVersion("not found"));
 	}, []);
 
 	const handleSave = async () => {
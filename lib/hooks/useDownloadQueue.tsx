@@ -19,7 +19,7 @@ import {
 	warmUpAudioContext,
 } from "../notifications";
 import { isAudioFormat } from "../utils";
-import type { AppSettings } from "./useSettings";
+import type { AppSettings, HeaderOverrides } from "./useSettings";
 import type { DownloadProgress, VideoMetadata } from "./useVideoDownloader";
 
 /** Tauriイベントから受け取る進捗ペイロード（idを含む） */
@@ -42,6 +42,34 @@ interface DownloadErrorEvent {
 	error: string;
 }
 
+/** アプリ外（ローカル API・ディープリンクなど）からのダウンロード要求 */
+interface DownloadRequestedEvent {
+	downloadId: string;
+	url: string;
+	/** 解決済みのプリセット名（オプションの各項目はプリセットの値で埋められている） */
+	preset: string | null;
+	audioOnly: boolean;
+	folderPath: string | null;
+	bestQuality: boolean;
+	downloadSubtitles: boolean;
+	preferredFormat: string | null;
+	customFilename: string | null;
+	thumbnail: string | null;
+	metadataTitle: string | null;
+	videoPassword: string | null;
+	headers: HeaderOverrides | null;
+	/** 再試行元の履歴エントリID */
+	retryOf: string | null;
+}
+
+/** クリップボードで検出した URL（メタデータ取得済み） */
+interface ClipboardUrlDetectedEvent {
+	url: string;
+	metadata: VideoMetadata;
+	/** 自動でキューに追加された場合のダウンロードID */
+	downloadId: string | null;
+}
+
 /** Rust側のダウンロード状態エントリ */
 interface RustDownloadStatus {
 	id: string;
@@ -62,6 +90,12 @@ export interface QueueItem {
 	downloadSubtitles: boolean;
 	customFilename: string;
 	folderPath: string;
+	videoPassword?: string | null;
+	headers?: HeaderOverrides | null;
+	/** 使用したプリセット名（履歴に記録する） */
+	preset?: string | null;
+	/** 再試行元の履歴エントリID */
+	retryOf?: string | null;
 	status: "queued" | "downloading" | "completed" | "error";
 	progress: DownloadProgress;
 	error?: string;
@@ -71,7 +105,7 @@ export interface QueueItem {
 interface DownloadQueueContextValue {
 	queue: QueueItem[];
 	activeCount: number;
-	addToQueue: (item: Omit<QueueItem, "id" | "status" | "progress">) => void;
+	addToQueue: (item: Omit<QueueItem, "id" | "status" | "progress">, id?: string) => void;
 	removeFromQueue: (id: string) => void;
 	clearCompleted: () => void;
 }
@@ -235,16 +269,22 @@ export function DownloadQueueProvider({
 
 		try {
 			await invoke<string>("download_video", {
-				downloadId: item.id,
-				url: item.url,
-				audioOnly: item.audioOnly,
-				folderPath: item.folderPath === "" ? null : item.folderPath,
-				bestQuality: !item.audioOnly && item.bestQuality,
-				downloadSubtitles: !item.audioOnly && item.downloadSubtitles,
-				preferredFormat: item.formatKey,
-				customFilename: item.customFilename.trim() || null,
-				thumbnail: item.metadata?.thumbnail ?? null,
-				metadataTitle: item.metadata?.title ?? null,
+				request: {
+					downloadId: item.id,
+					url: item.url,
+					audioOnly: item.audioOnly,
+					folderPath: item.folderPath === "" ? null : item.folderPath,
+					bestQuality: !item.audioOnly && item.bestQuality,
+					downloadSubtitles: !item.audioOnly && item.downloadSubtitles,
+					preferredFormat: item.formatKey,
+					customFilename: item.customFilename.trim() || null,
+					thumbnail: item.metadata?.thumbnail ?? null,
+					metadataTitle: item.metadata?.title ?? null,
+					videoPassword: item.videoPassword ?? null,
+					headers: item.headers ?? null,
+					preset: item.preset ?? null,
+					retryOf: item.retryOf ?? null,
+				},
 			});
 			// 完了ステータスの更新と通知は download-complete イベントリスナーで行う
 		} catch {
@@ -388,9 +428,14 @@ export function DownloadQueueProvider({
 		}
 	}, []);
 
-	/** キューにアイテムを追加する */
+	/**
+	 * キューにアイテムを追加する
+	 * id はアプリ外からの要求でRust側が採番済みの場合のみ指定する
+	 */
 	const addToQueue = useCallback(
-		(item: Omit<QueueItem, "id" | "status" | "progress">) => {
+		(item: Omit<QueueItem, "id" | "status" | "progress">, id?: string) => {
+			if (id && queueRef.current.some((q) => q.id === id)) return;
+
 			// ユーザージェスチャーのスタック上で AudioContext を初期化
 			if (settingsRef.current?.notifSound) {
 				warmUpAudioContext();
@@ -398,7 +443,7 @@ export function DownloadQueueProvider({
 
 			const newItem: QueueItem = {
 				...item,
-				id: crypto.randomUUID(),
+				id: id ?? crypto.randomUUID(),
 				status: "queued",
 				progress: { percent: 0, speed: null, eta: null },
 			};
@@ -419,6 +464,112 @@ export function DownloadQueueProvider({
 		setQueue((prev) => prev.filter((q) => q.id !== id));
 	}, []);
 
+	/**
+	 * アプリ外（ローカル API・ディープリンクなど）からの要求をキューに追加する
+	 * 起動直後など画面の読み込み前に届いた要求も取りこぼさないよう、
+	 * 要求はRust側に保留され、ここでまとめて受け取る
+	 */
+	const takePendingDownloads = useCallback(async () => {
+		try {
+			const requests = await invoke<DownloadRequestedEvent[]>("take_pending_downloads");
+			for (const request of requests) {
+				const defaultFormat = request.audioOnly
+					? "mp3"
+					: (settingsRef.current?.defaultFormat ?? "mp4");
+				addToQueue(
+					{
+						url: request.url,
+						metadata: request.metadataTitle
+							? {
+									title: request.metadataTitle,
+									thumbnail: request.thumbnail ?? undefined,
+								}
+							: null,
+						formatKey: request.preferredFormat ?? defaultFormat,
+						audioOnly: request.audioOnly,
+						bestQuality: request.bestQuality,
+						downloadSubtitles: request.downloadSubtitles,
+						customFilename: request.customFilename ?? "",
+						folderPath: request.folderPath ?? "",
+						videoPassword: request.videoPassword,
+						headers: request.headers,
+						preset: request.preset,
+						retryOf: request.retryOf,
+					},
+					request.downloadId,
+				);
+			}
+		} catch {
+			// コマンドが存在しない（古いバックエンド）場合は何もしない
+		}
+	}, [addToQueue]);
+
+	/**
+	 * - download-requested: 保留中の要求を受け取ってキューに追加
+	 * - download-removed: キューから削除（実行中の場合はRust側で中断済み）
+	 * - clipboard-url-detected: 検出した URL を通知（自動追加でなければ追加ボタンを表示）
+	 */
+	useEffect(() => {
+		let cancelled = false;
+		const unlisteners: UnlistenFn[] = [];
+
+		takePendingDownloads();
+
+		const promises = [
+			listen<DownloadRequestedEvent>("download-requested", () => {
+				takePendingDownloads();
+			}),
+
+			listen<string>("download-removed", (event) => {
+				removeFromQueue(event.payload);
+			}),
+
+			listen<ClipboardUrlDetectedEvent>("clipboard-url-detected", (event) => {
+				const { url, metadata, downloadId } = event.payload;
+				const t = tRef.current;
+				if (downloadId) {
+					toast.success(t("toast.clipboardUrlEnqueued"), { description: metadata.title });
+					return;
+				}
+				toast(t("toast.clipboardUrlDetected"), {
+					description: metadata.title,
+					duration: 10000,
+					action: {
+						label: t("toast.addToQueue"),
+						onClick: () => {
+							const settings = settingsRef.current;
+							addToQueue({
+								url,
+								metadata,
+								formatKey: settings?.defaultFormat ?? "mp4",
+								audioOnly: false,
+								bestQuality: true,
+								downloadSubtitles: false,
+								customFilename: "",
+								folderPath: "",
+							});
+						},
+					},
+				});
+			}),
+		];
+
+		Promise.all(promises).then((fns) => {
+			if (cancelled) {
+				for (const fn of fns) fn();
+			} else {
+				unlisteners.push(...fns);
+			}
+		});
+
+		return () => {
+			cancelled = true;
+			for (const unlisten of unlisteners) {
+				unlisten();
+			}
+		};
+	}, [takePendingDownloads, removeFromQueue, addToQueue]);
+
 	/** 完了済みアイテムをすべてクリアする */
 	const clearCompleted = useCallback(() => {
 		setQueue((prev) => prev.filter((q) => q.status !== "completed"));
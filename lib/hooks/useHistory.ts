@@ -12,6 +12,15 @@ export interface HistoryEntry {
 	status: "success" | "failed";
 	errorMessage: string | null;
 	timestamp: string;
+	/** 再試行元の履歴エントリID */
+	retryOf: string | null;
+}
+
+/** まとめて再試行する失敗履歴の条件 */
+export interface RetryFilter {
+	query?: string;
+	since?: string;
+	limit?: number;
 }
 
 export interface HistoryGroup {
@@ -68,12 +77,30 @@ export function useHistory() {
 		setStats(EMPTY_STATS);
 	}, []);
 
+	/** 元のオプションで再ダウンロードする（キューに追加され、新しいダウンロードIDを返す） */
+	const retryEntry = useCallback(async (id: string) => {
+		return invoke<string>("retry_history_entry", { id });
+	}, []);
+
+	const retryAllFailed = useCallback(async (filter?: RetryFilter) => {
+		return invoke<string[]>("retry_all_failed", { filter: filter ?? null });
+	}, []);
+
 	const successRate =
 		stats.monthTotal > 0
 			? Number(((stats.monthCount / stats.monthTotal) * 100).toFixed(1))
 			: null;
 
-	return { history, stats, successRate, loading, refresh, clearHistory };
+	return {
+		history,
+		stats,
+		successRate,
+		loading,
+		refresh,
+		clearHistory,
+		retryEntry,
+		retryAllFailed,
+	};
 }
 
 /** バイト数を人間が読みやすい形式にフォーマット */
@@ -13,6 +13,156 @@ export interface AppSettings {
 	notifComplete: boolean;
 	notifError: boolean;
 	notifSound: boolean;
+	binaries: BinarySettings;
+	proxy: ProxySettings;
+	network: NetworkSettings;
+	siteProfiles: SiteProfile[];
+	logLevel: LogLevel;
+	api: ApiSettings;
+	clipboard: ClipboardSettings;
+	watchFolder: WatchFolderSettings;
+	presets: DownloadPreset[];
+}
+
+/** ダウンロードのオプション */
+export interface DownloadOptions {
+	audioOnly: boolean;
+	folderPath: string | null;
+	bestQuality: boolean;
+	downloadSubtitles: boolean;
+	preferredFormat: string | null;
+}
+
+/** 名前付きのダウンロードオプション */
+export interface DownloadPreset {
+	name: string;
+	options: DownloadOptions;
+}
+
+/** 監視フォルダ（処理したファイルは done/・failed/ に移動する） */
+export interface WatchFolderSettings {
+	enabled: boolean;
+	path: string;
+	/** 追加するダウンロードのプリセット（null は既定のオプション） */
+	preset: string | null;
+}
+
+/** クリップボードの URL 検出 */
+export interface ClipboardSettings {
+	enabled: boolean;
+	/** 検出した URL を確認なしでキューに追加する */
+	autoEnqueue: boolean;
+	/** 自動で追加するときのプリセット */
+	preset: string | null;
+}
+
+/** 外部ツール向けのローカル HTTP API（127.0.0.1 のみ） */
+export interface ApiSettings {
+	enabled: boolean;
+	port: number;
+	/** Bearer トークン（有効化時に空なら自動生成される） */
+	token: string;
+}
+
+export interface ApiStatus {
+	running: boolean;
+	port: number | null;
+	url: string | null;
+	error: string | null;
+}
+
+export type NativeHostBrowser = "chrome" | "firefox";
+
+/** ブラウザ拡張機能用ネイティブメッセージングホストの登録状況 */
+export interface NativeHostStatus {
+	browser: NativeHostBrowser;
+	installed: boolean;
+	manifestPath: string | null;
+}
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+/** サイトごとに yt-dlp へ渡す HTTP ヘッダー */
+export interface SiteProfile {
+	name: string;
+	/** 適用するドメイン（サブドメインにも一致する） */
+	domains: string[];
+	userAgent: string | null;
+	referer: string | null;
+	headers: Record<string, string>;
+}
+
+/** ダウンロードごとに指定する HTTP ヘッダー（サイト設定より優先） */
+export interface HeaderOverrides {
+	userAgent?: string | null;
+	referer?: string | null;
+	headers?: Record<string, string>;
+}
+
+/** TLS 検証・IP バージョンなどの接続設定 */
+export interface NetworkSettings {
+	skipCertificateCheck: boolean;
+	forceIpv4: boolean;
+	/** 追加で信頼する CA 証明書（PEM）のパス */
+	caBundlePath: string | null;
+}
+
+export type ProxyScheme = "http" | "https" | "socks5";
+
+/** yt-dlp とバイナリ取得に使うプロキシ設定 */
+export interface ProxySettings {
+	enabled: boolean;
+	scheme: ProxyScheme;
+	host: string;
+	port: number;
+	username: string | null;
+	/** 入力したパスワード（保存済みの値は返らない。null なら維持、空文字なら削除） */
+	password: string | null;
+	/** プロキシを経由しないホスト（"localhost", ".example.com" など） */
+	noProxy: string[];
+}
+
+export interface ProxyTestResult {
+	ok: boolean;
+	status: number | null;
+	elapsedMs: number;
+	error: string | null;
+}
+
+export type YtDlpChannel = "stable" | "nightly" | "master";
+
+/** バイナリの取得元（同梱版 / PATH 上のシステム版 / 任意のパス） */
+export type BinarySource =
+	| { type: "bundled" }
+	| { type: "system" }
+	| { type: "custom"; path: string };
+
+/** バイナリの取得元とバージョン固定設定（null は最新版） */
+export interface BinarySettings {
+	ytDlpSource: BinarySource;
+	ffmpegSource: BinarySource;
+	denoSource: BinarySource;
+	ytDlpChannel: YtDlpChannel;
+	ytDlpVersion: string | null;
+	ffmpegVersion: string | null;
+	denoVersion: string | null;
+	/** ミラーのベースURL（https://github.com/{owner}/{repo}/releases の代替） */
+	ytDlpMirror: string | null;
+	ffmpegMirror: string | null;
+	denoMirror: string | null;
+}
+
+export interface BinaryVersionInfo {
+	installed: string | null;
+	pinned: string | null;
+	latest: string | null;
+}
+
+export interface BinaryVersionReport {
+	ytDlpChannel: YtDlpChannel;
+	ytDlp: BinaryVersionInfo;
+	ffmpeg: BinaryVersionInfo;
+	deno: BinaryVersionInfo;
 }
 
 export interface SavePathStatus {
@@ -31,6 +181,50 @@ const FALLBACK_SETTINGS: AppSettings = {
 	notifComplete: true,
 	notifError: true,
 	notifSound: false,
+	binaries: {
+		ytDlpSource: { type: "bundled" },
+		ffmpegSource: { type: "bundled" },
+		denoSource: { type: "bundled" },
+		ytDlpChannel: "stable",
+		ytDlpVersion: null,
+		ffmpegVersion: null,
+		denoVersion: null,
+		ytDlpMirror: null,
+		ffmpegMirror: null,
+		denoMirror: null,
+	},
+	proxy: {
+		enabled: false,
+		scheme: "http",
+		host: "",
+		port: 0,
+		username: null,
+		password: null,
+		noProxy: [],
+	},
+	network: {
+		skipCertificateCheck: false,
+		forceIpv4: false,
+		caBundlePath: null,
+	},
+	siteProfiles: [],
+	logLevel: "info",
+	api: {
+		enabled: false,
+		port: 17385,
+		token: "",
+	},
+	clipboard: {
+		enabled: false,
+		autoEnqueue: false,
+		preset: null,
+	},
+	watchFolder: {
+		enabled: false,
+		path: "",
+		preset: null,
+	},
+	presets: [],
 };
 
 export function useSettings() {
@@ -1,12 +1,32 @@
-import { useEffect, useState } from "react";
+import { useCallback, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 type StepStatus = "pending" | "in_progress" | "ready" | "error";
 
+type SetupStep = "yt-dlp" | "ffmpeg" | "deno";
+
 interface SetupProgressPayload {
-	step: "yt-dlp" | "ffmpeg" | "deno";
+	step: SetupStep;
 	status: StepStatus;
+	/** ダウンロード済みバイト数（ダウンロード中のみ） */
+	downloaded: number | null;
+	/** 合計バイト数（不明な場合は null） */
+	total: number | null;
+	/** ダウンロード速度（バイト/秒） */
+	speed: number | null;
+}
+
+/** get_setup_status の各ステップの状態 */
+type SetupStepState =
+	| { status: "in_progress" }
+	| { status: "ready" }
+	| { status: "error"; error: string };
+
+export interface ByteProgress {
+	downloaded: number;
+	total: number | null;
+	speed: number | null;
 }
 
 interface SetupSteps {
@@ -22,6 +42,7 @@ export function useSetupProgress() {
 		ffmpeg: "pending",
 		deno: "pending",
 	});
+	const [bytes, setBytes] = useState<Partial<Record<SetupStep, ByteProgress>>>({});
 	const [isComplete, setIsComplete] = useState<boolean | null>(null);
 
 	useEffect(() => {
@@ -33,8 +54,11 @@ export function useSetupProgress() {
 			unlisteners.push(
 				listen<SetupProgressPayload>("setup-progress", (event) => {
 					if (cancelled) return;
-					const { step, status } = event.payload;
+					const { step, status, downloaded, total, speed } = event.payload;
 					setSteps((prev) => ({ ...prev, [step]: status }));
+					if (downloaded !== null) {
+						setBytes((prev) => ({ ...prev, [step]: { downloaded, total, speed } }));
+					}
 				}),
 			);
 
@@ -46,15 +70,19 @@ export function useSetupProgress() {
 			);
 
 			// リスナー登録後に現在の状態を確認（見逃し防止）
-			const complete = await invoke<boolean>("is_setup_complete");
+			// 失敗したステップがあっても全ステップが終わっていれば完了扱いにする
+			const status = await invoke<Partial<Record<SetupStep, SetupStepState>>>("get_setup_status");
 			if (cancelled) return;
 
-			if (complete) {
-				setSteps({ "yt-dlp": "ready", ffmpeg: "ready", deno: "ready" });
-				setIsComplete(true);
-			} else {
-				setIsComplete(false);
-			}
+			const restored: SetupSteps = {
+				"yt-dlp": status["yt-dlp"]?.status ?? "pending",
+				ffmpeg: status.ffmpeg?.status ?? "pending",
+				deno: status.deno?.status ?? "pending",
+			};
+			setSteps(restored);
+			setIsComplete(
+				Object.values(restored).every((s) => s === "ready" || s === "error"),
+			);
 		}
 
 		init();
@@ -67,5 +95,8 @@ export function useSetupProgress() {
 		};
 	}, []);
 
-	return { steps, isComplete: isComplete ?? true };
+	const retryStep = useCallback((step: SetupStep) => invoke("retry_setup", { step }), []);
+	const repair = useCallback(() => invoke("repair_binaries"), []);
+
+	return { steps, bytes, isComplete: isComplete ?? true, retryStep, repair };
 }
@@ -69,6 +69,9 @@ export const en: Translations = {
 		downloads: "downloads",
 		empty: "No download history yet",
 		loading: "Loading history...",
+		retry: "Download again",
+		retryFailed: "Retry Failed",
+		retryQueued: "Added {{count}} to the queue",
 	},
 	settings: {
 		title: "Settings",
@@ -147,6 +150,9 @@ export const en: Translations = {
 		fileDeleteError: "Failed to delete file",
 		fileOpenError: "Failed to open file",
 		folderOpenError: "Failed to open folder",
+		clipboardUrlDetected: "Video link detected in clipboard",
+		clipboardUrlEnqueued: "Added video from clipboard to the queue",
+		addToQueue: "Add to queue",
 	},
 	validation: {
 		invalidUrl:
@@ -165,6 +171,28 @@ export const en: Translations = {
 		downloadComplete: "Download Complete",
 		downloadError: "Download Error",
 	},
+	doctor: {
+		hint: {
+			binaryMissing: "Binary not found. Run \"Repair binaries\" from Settings",
+			binaryNotExecutable:
+				"Binary cannot be executed. Repair it, or check file permissions and security software",
+			binaryInvalid:
+				"The configured system or custom binary cannot be used. Check the binary source in Settings",
+			updateYtDlp: "yt-dlp may be outdated. Update yt-dlp",
+			denoNotDetected:
+				"yt-dlp cannot find Deno. Repair Deno or check the Deno source setting",
+			savePathMissing: "Save location does not exist. Choose it again in Settings",
+			savePathNotWritable:
+				"Cannot write to the save location. Check folder permissions or choose another location",
+			freeDiskSpace: "Not enough free space at the save location. Remove unneeded files",
+			resetSettings: "Settings file is corrupted. Reset settings",
+			clearHistory: "History file is corrupted. Clear history",
+			cookieBrowserMissing:
+				"Cookie browser not found. Choose a browser that is installed",
+			cookieBrowserUnsupported:
+				"Cookie browser is not supported. Choose another browser or import a cookies.txt",
+		},
+	},
 	errors: {
 		invalidUrl: "Invalid URL",
 		getTitleFailed: "Failed to get title",
@@ -199,6 +227,9 @@ export const en: Translations = {
 		downloadDirNotExists: "Download directory does not exist",
 		unsafePathGenerated: "Unsafe path generated",
 		ytdlpDownloadFailed: "Failed to download yt-dlp binary",
+		binaryInvalid:
+			"The configured binary was not found or cannot be executed. Check the binary source in Settings",
+		binarySourceExternal: "This binary uses an external (system or custom) copy. Update it with the tool that installed it",
 		ytdlpDeleteFailed: "Failed to delete old yt-dlp",
 		ytdlpVersionFailed: "Failed to get version",
 		ytdlpVersionNotFound: "Could not get yt-dlp version",
@@ -216,6 +247,20 @@ export const en: Translations = {
 		denoNotFoundInZip: "Deno binary not found in ZIP",
 		cookieFailed:
 			"Failed to read cookies. Please close the browser and try again.",
+		unsupportedFormat: "Unsupported format",
+		downloadCancelled: "Download cancelled",
+		importReadFailed: "Failed to read the URL list file",
+		importFileTooLarge: "The URL list file is too large",
+		watchFolderMoveFailed: "Failed to move the file in the watch folder",
+		presetNotFound: "Preset not found",
+		presetInvalidName: "Enter a preset name (up to 64 characters)",
+		presetExists: "A preset with the same name already exists",
+		presetExportFailed: "Failed to export presets",
+		presetImportFailed: "Failed to import presets",
+		historyNotFound: "History entry not found",
+		historyDbFailed: "History database operation failed",
+		credentialsKeyMissing: "The encryption key for saved passwords is missing. Saved passwords cannot be read",
+		credentialsKeyInvalid: "The encryption key for saved passwords is corrupted",
 		unknown: "An unknown error occurred",
 	},
 } as const;
@@ -67,6 +67,9 @@ export const ja = {
 		downloads: "ダウンロード",
 		empty: "ダウンロード履歴はまだありません",
 		loading: "履歴を読み込み中...",
+		retry: "再ダウンロード",
+		retryFailed: "失敗をまとめて再試行",
+		retryQueued: "{{count}} 件をキューに追加しました",
 	},
 	settings: {
 		title: "設定",
@@ -144,6 +147,9 @@ export const ja = {
 		fileDeleteError: "ファイルの削除に失敗しました",
 		fileOpenError: "ファイルを開けませんでした",
 		folderOpenError: "フォルダを開けませんでした",
+		clipboardUrlDetected: "クリップボードの動画を検出しました",
+		clipboardUrlEnqueued: "クリップボードの動画をキューに追加しました",
+		addToQueue: "キューに追加",
 	},
 	validation: {
 		invalidUrl:
@@ -162,6 +168,29 @@ export const ja = {
 		downloadComplete: "ダウンロード完了",
 		downloadError: "ダウンロードエラー",
 	},
+	doctor: {
+		hint: {
+			binaryMissing:
+				"バイナリが見つかりません。設定画面から「バイナリを修復」を実行してください",
+			binaryNotExecutable:
+				"バイナリを実行できません。修復するか、実行権限とセキュリティソフトの設定を確認してください",
+			binaryInvalid:
+				"設定された system / custom のバイナリを使用できません。設定のバイナリの取得元を確認してください",
+			updateYtDlp: "yt-dlpが古い可能性があります。yt-dlpを更新してください",
+			denoNotDetected:
+				"yt-dlpがDenoを検出できません。Denoを修復するか、Denoの取得元設定を確認してください",
+			savePathMissing: "保存先が存在しません。設定画面で保存先を選び直してください",
+			savePathNotWritable:
+				"保存先に書き込めません。フォルダの権限を確認するか、別の保存先を選んでください",
+			freeDiskSpace: "保存先の空き容量が不足しています。不要なファイルを削除してください",
+			resetSettings: "設定ファイルが壊れています。設定をリセットしてください",
+			clearHistory: "履歴ファイルが壊れています。履歴を削除してください",
+			cookieBrowserMissing:
+				"Cookieの取得元ブラウザが見つかりません。インストール済みのブラウザを選んでください",
+			cookieBrowserUnsupported:
+				"Cookieの取得元ブラウザに対応していません。別のブラウザを選ぶか、cookies.txtを取り込んでください",
+		},
+	},
 	errors: {
 		invalidUrl: "有効なURLではありません",
 		getTitleFailed: "タイトルの取得に失敗しました",
@@ -196,6 +225,9 @@ export const ja = {
 			"ダウンロードディレクトリが存在しないか、ディレクトリではありません",
 		unsafePathGenerated: "安全でないパスが生成されました",
 		ytdlpDownloadFailed: "yt-dlpバイナリのダウンロードに失敗しました",
+		binaryInvalid:
+			"設定されたバイナリが見つからないか、実行できません。設定のバイナリの取得元を確認してください",
+		binarySourceExternal: "外部（system / custom）のバイナリを使用しているため更新できません。インストールしたツールで更新してください",
 		ytdlpDeleteFailed: "古いyt-dlpの削除に失敗しました",
 		ytdlpVersionFailed: "バージョン取得に失敗しました",
 		ytdlpVersionNotFound: "yt-dlpのバージョンを取得できませんでした",
@@ -213,6 +245,20 @@ export const ja = {
 		denoNotFoundInZip: "ZIP内にDenoバイナリが見つかりませんでした",
 		cookieFailed:
 			"Cookieの読み取りに失敗しました。ブラウザを閉じてから再試行してください。",
+		unsupportedFormat: "対応していないフォーマットです",
+		downloadCancelled: "ダウンロードを中断しました",
+		importReadFailed: "URL 一覧のファイルを読み込めませんでした",
+		importFileTooLarge: "URL 一覧のファイルが大きすぎます",
+		watchFolderMoveFailed: "監視フォルダのファイルを移動できませんでした",
+		presetNotFound: "プリセットが見つかりません",
+		presetInvalidName: "プリセット名を入力してください（64文字以内）",
+		presetExists: "同じ名前のプリセットがあります",
+		presetExportFailed: "プリセットを書き出せませんでした",
+		presetImportFailed: "プリセットを取り込めませんでした",
+		historyNotFound: "履歴が見つかりません",
+		historyDbFailed: "履歴データベースの操作に失敗しました",
+		credentialsKeyMissing: "保存済みパスワードの暗号鍵が見つからないため読み込めません",
+		credentialsKeyInvalid: "保存済みパスワードの暗号鍵が壊れています",
 		unknown: "不明なエラーが発生しました",
 	},
 } as const;
@@ -0,0 +1,295 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use tokio::sync::{broadcast, watch};
+use tower_http::cors::{Any, CorsLayer};
+
+use crate::commands;
+use crate::download::{self, DownloadRequest};
+use crate::history;
+use crate::settings::ApiSettings;
+
+/// WebSocket に流すイベントのバッファ数（遅いクライアントはこれを超えると取りこぼす）
+const EVENT_BUFFER: usize = 256;
+
+/// ダウンロードイベントの配信元（WebSocket クライアントごとに購読する）
+static EVENTS: LazyLock<broadcast::Sender<String>> =
+  LazyLock::new(|| broadcast::channel(EVENT_BUFFER).0);
+
+/// 起動中のサーバー
+static SERVER: LazyLock<Mutex<Option<RunningServer>>> = LazyLock::new(|| Mutex::new(None));
+
+/// 直近の起動エラー（ポート使用中など）
+static LAST_ERROR: LazyLock<Mutex<Option<String>>> = LazyLock::new(|| Mutex::new(None));
+
+struct RunningServer {
+  port: u16,
+  /// リクエストの検証に使うトークン（再起動せずに差し替えられるよう共有する）
+  token: Arc<RwLock<String>>,
+  shutdown: watch::Sender<bool>,
+  /// サーバーのタスク（同じポートで起動し直す前に終了を待つ）
+  task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// API サーバーの状態
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiStatus {
+  pub running: bool,
+  pub port: Option<u16>,
+  pub url: Option<String>,
+  pub error: Option<String>,
+}
+
+#[derive(Clone)]
+struct ApiState {
+  app_handle: tauri::AppHandle,
+  token: Arc<RwLock<String>>,
+  shutdown: watch::Receiver<bool>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+  error: String,
+}
+
+fn error_response(status: StatusCode, error: impl Into<String>) -> Response {
+  (status, Json(ErrorBody { error: error.into() })).into_response()
+}
+
+/// ダウンロードイベントを WebSocket クライアントに配信する
+///
+/// フロントエンドに送るイベントと同じ名前・ペイロードを `{ "event", "payload" }` で包む。
+pub fn publish<T: Serialize>(event: &str, payload: &T) {
+  if EVENTS.receiver_count() == 0 {
+    return;
+  }
+  match serde_json::to_string(&serde_json::json!({ "event": event, "payload": payload })) {
+    Ok(message) => {
+      let _ = EVENTS.send(message);
+    }
+    Err(e) => log::warn!("API イベントのシリアライズに失敗: {e}"),
+  }
+}
+
+/// ランダムな API トークンを生成する
+pub fn generate_token() -> String {
+  format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+/// 長さ以外の情報を漏らさないよう、全バイトを比較する
+fn token_matches(given: &str, expected: &str) -> bool {
+  given.len() == expected.len()
+    && given
+      .bytes()
+      .zip(expected.bytes())
+      .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+      == 0
+}
+
+/// リクエストからトークンを取り出す
+///
+/// ブラウザの WebSocket はヘッダーを付けられないため、クエリの `token` も受け付ける。
+fn request_token(headers: &HeaderMap, query: Option<&str>) -> Option<String> {
+  let bearer = headers
+    .get(header::AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(|token| token.trim().to_string());
+
+  bearer.or_else(|| {
+    url::form_urlencoded::parse(query?.as_bytes())
+      .find(|(key, _)| key == "token")
+      .map(|(_, value)| value.into_owned())
+  })
+}
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+  match request_token(request.headers(), request.uri().query()) {
+    Some(token) if token_matches(&token, &state.token.read().unwrap()) => next.run(request).await,
+    _ => error_response(StatusCode::UNAUTHORIZED, "error.api_unauthorized"),
+  }
+}
+
+// ─── ハンドラ ──────────────────────────────────────
+
+async fn create_download(
+  State(state): State<ApiState>,
+  Json(request): Json<DownloadRequest>,
+) -> Response {
+  match commands::enqueue_download(&state.app_handle, request) {
+    Ok(id) => (StatusCode::ACCEPTED, Json(serde_json::json!({ "id": id }))).into_response(),
+    Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+  }
+}
+
+async fn list_downloads() -> Response {
+  Json(download::get_download_statuses()).into_response()
+}
+
+async fn remove_download(State(state): State<ApiState>, Path(id): Path<String>) -> Response {
+  commands::remove_download(&state.app_handle, &id);
+  StatusCode::NO_CONTENT.into_response()
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+  limit: Option<usize>,
+}
+
+async fn list_history(Query(query): Query<HistoryQuery>) -> Response {
+  match history::load_entries(None, query.limit) {
+    Ok(entries) => Json(entries).into_response(),
+    Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+  }
+}
+
+async fn events(State(state): State<ApiState>, upgrade: WebSocketUpgrade) -> Response {
+  upgrade.on_upgrade(move |socket| stream_events(socket, state.shutdown))
+}
+
+/// ダウンロードイベントをクライアントが切断するかサーバーが停止するまで送り続ける
+async fn stream_events(mut socket: WebSocket, mut shutdown: watch::Receiver<bool>) {
+  let mut events = EVENTS.subscribe();
+
+  loop {
+    tokio::select! {
+      event = events.recv() => match event {
+        Ok(message) => {
+          if socket.send(Message::Text(message.into())).await.is_err() {
+            break;
+          }
+        }
+        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+          log::warn!("WebSocket クライアントがイベントを {skipped} 件取りこぼしました");
+        }
+        Err(broadcast::error::RecvError::Closed) => break,
+      },
+      incoming = socket.recv() => match incoming {
+        Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+        Some(Ok(_)) => {}
+      },
+      _ = shutdown.changed() => break,
+    }
+  }
+}
+
+fn router(state: ApiState) -> Router {
+  // ブックマークレットなどブラウザ上のページから呼べるようにする（認証はトークンで行う）
+  let cors = CorsLayer::new()
+    .allow_origin(Any)
+    .allow_methods([Method::GET, Method::POST, Method::DELETE])
+    .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE]);
+
+  Router::new()
+    .route("/downloads", get(list_downloads).post(create_download))
+    .route("/downloads/{id}", delete(remove_download))
+    .route("/history", get(list_history))
+    .route("/events", get(events))
+    .layer(middleware::from_fn_with_state(state.clone(), require_token))
+    .layer(cors)
+    .with_state(state)
+}
+
+// ─── 起動・停止 ─────────────────────────────────────
+
+/// サーバーに停止を通知し、終了を待つためのタスクを返す
+fn stop() -> Option<tauri::async_runtime::JoinHandle<()>> {
+  let server = SERVER.lock().unwrap().take()?;
+  let _ = server.shutdown.send(true);
+  log::info!("ローカル API を停止しました (port {})", server.port);
+  Some(server.task)
+}
+
+/// 設定に合わせてサーバーを起動・停止・再起動する
+///
+/// ポートが同じでトークンだけが変わった場合は、再起動せずにトークンを差し替える。
+pub fn apply(app_handle: &tauri::AppHandle, api_settings: &ApiSettings) {
+  if api_settings.enabled && !api_settings.token.is_empty() {
+    let server = SERVER.lock().unwrap();
+    if let Some(server) = server.as_ref().filter(|server| server.port == api_settings.port) {
+      let mut token = server.token.write().unwrap();
+      if *token != api_settings.token {
+        token.clone_from(&api_settings.token);
+        log::info!("ローカル API のトークンを更新しました");
+      }
+      return;
+    }
+  }
+  let previous = stop();
+  *LAST_ERROR.lock().unwrap() = None;
+
+  if !api_settings.enabled {
+    return;
+  }
+  if api_settings.token.is_empty() {
+    log::warn!("API トークンが未設定のため、ローカル API を起動しません");
+    *LAST_ERROR.lock().unwrap() = Some("error.api_token_missing".to_string());
+    return;
+  }
+
+  let (shutdown, shutdown_rx) = watch::channel(false);
+  let token = Arc::new(RwLock::new(api_settings.token.clone()));
+  let state = ApiState {
+    app_handle: app_handle.clone(),
+    token: token.clone(),
+    shutdown: shutdown_rx.clone(),
+  };
+  let port = api_settings.port;
+  // 起動に失敗したタスクが後から起動したサーバーを消さないよう、トークンの Arc で識別する
+  let server_token = token.clone();
+
+  // タスクが SERVER を参照する前に登録を終えるよう、ロックを持ったまま起動する
+  let mut server = SERVER.lock().unwrap();
+  let task = tauri::async_runtime::spawn(async move {
+    // 停止したサーバーがポートを解放するまで待つ（待たないと同じポートで EADDRINUSE になる）
+    if let Some(previous) = previous {
+      let _ = previous.await;
+    }
+
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+      Ok(listener) => listener,
+      Err(e) => {
+        log::error!("ローカル API を起動できません ({addr}): {e}");
+        *LAST_ERROR.lock().unwrap() = Some(format!("error.api_bind_failed:{e}"));
+        let mut server = SERVER.lock().unwrap();
+        if server.as_ref().is_some_and(|s| Arc::ptr_eq(&s.token, &server_token)) {
+          *server = None;
+        }
+        return;
+      }
+    };
+
+    log::info!("ローカル API を起動しました: http://{addr}");
+    let mut shutdown_rx = shutdown_rx;
+    let result = axum::serve(listener, router(state))
+      .with_graceful_shutdown(async move {
+        let _ = shutdown_rx.changed().await;
+      })
+      .await;
+    if let Err(e) = result {
+      log::error!("ローカル API がエラーで停止しました: {e}");
+    }
+  });
+  *server = Some(RunningServer { port, token, shutdown, task });
+}
+
+/// サーバーの状態を取得する
+pub fn status() -> ApiStatus {
+  let server = SERVER.lock().unwrap();
+  let port = server.as_ref().map(|s| s.port);
+  ApiStatus {
+    running: server.is_some(),
+    port,
+    url: port.map(|port| format!("http://127.0.0.1:{port}")),
+    error: LAST_ERROR.lock().unwrap().clone(),
+  }
+}
@@ -0,0 +1,471 @@
+//! Lucentia のヘッドレス CLI
+//!
+//! アプリと同じ設定・履歴・バイナリを使い、Tauri のウィンドウなしで操作する。
+
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use lucentia_lib::download::{
+  self, DownloadCompleteEvent, DownloadErrorEvent, DownloadProgress, DownloadReporter,
+  DownloadRequest,
+};
+use lucentia_lib::history::{self, HistoryEntry};
+use lucentia_lib::presets::DownloadOptionsOverride;
+use lucentia_lib::settings::{self, AppSettings};
+use lucentia_lib::{downloader, environment};
+
+#[derive(Parser)]
+#[command(name = "lucentia-cli", version, about = "Lucentia command line interface")]
+struct Cli {
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// 動画・音声をダウンロードする
+  Download(DownloadArgs),
+  /// タイトル・サムネイル・長さを取得する
+  Metadata {
+    url: String,
+    /// 出力を JSON にする
+    #[arg(long)]
+    json: bool,
+  },
+  /// ダウンロード履歴
+  #[command(subcommand)]
+  History(HistoryCommand),
+  /// アプリ設定
+  #[command(subcommand)]
+  Settings(SettingsCommand),
+  /// yt-dlp / FFmpeg / Deno
+  #[command(subcommand)]
+  Binaries(BinariesCommand),
+  /// 保存先のダウンロード済みファイル
+  #[command(subcommand)]
+  Files(FilesCommand),
+}
+
+#[derive(Args)]
+struct DownloadArgs {
+  url: String,
+  /// 使用するプリセット名（指定した他のオプションはプリセットの値を上書きする）
+  #[arg(long)]
+  preset: Option<String>,
+  /// 音声のみを抽出する
+  #[arg(long)]
+  audio: bool,
+  /// 出力フォーマット（mp4, webm, mp3, m4a など。省略時は設定の既定値）
+  #[arg(long)]
+  format: Option<String>,
+  /// 画質（省略時は best）
+  #[arg(long, value_enum)]
+  quality: Option<Quality>,
+  /// 保存先フォルダ（省略時は設定の保存先）
+  #[arg(long)]
+  out: Option<PathBuf>,
+  /// 字幕をダウンロードして埋め込む
+  #[arg(long)]
+  subtitles: bool,
+  /// 保存するファイル名（拡張子なし）
+  #[arg(long)]
+  filename: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Quality {
+  /// 最高画質の映像と音声を結合する
+  Best,
+  /// 結合済みの単一ファイルを選ぶ
+  Single,
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+  /// 新しい順に表示する
+  List {
+    /// 表示する件数
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+    /// 失敗したダウンロードのみ表示する
+    #[arg(long)]
+    failed: bool,
+  },
+  /// JSON / CSV に書き出す
+  Export {
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    format: ExportFormat,
+    /// 出力先ファイル（省略時は標準出力）
+    #[arg(long)]
+    output: Option<PathBuf>,
+  },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+  Json,
+  Csv,
+}
+
+#[derive(Subcommand)]
+enum SettingsCommand {
+  /// 設定値を表示する（キー省略時はすべて）
+  Get {
+    /// "savePath" や "proxy.host" などのドット区切りキー
+    key: Option<String>,
+  },
+  /// 設定値を変更する（値は JSON として解釈し、失敗した場合は文字列として扱う）
+  Set { key: String, value: String },
+}
+
+#[derive(Subcommand)]
+enum BinariesCommand {
+  /// バイナリを更新する
+  Update {
+    #[arg(value_enum, default_value_t = BinaryTarget::All)]
+    target: BinaryTarget,
+    /// 更新先のバージョン（省略時は設定のチャンネル・固定バージョン。all では指定できない）
+    #[arg(long)]
+    version: Option<String>,
+  },
+  /// インストール状況を表示する
+  Status,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BinaryTarget {
+  YtDlp,
+  Ffmpeg,
+  Deno,
+  All,
+}
+
+#[derive(Subcommand)]
+enum FilesCommand {
+  /// ダウンロード済みファイルを新しい順に表示する
+  List {
+    #[arg(long)]
+    json: bool,
+  },
+}
+
+// ─── 進捗表示 ─────────────────────────────────────
+
+/// ダウンロードの進捗を端末のプログレスバーに表示する
+struct ProgressBarReporter {
+  bar: ProgressBar,
+}
+
+impl ProgressBarReporter {
+  fn new() -> Self {
+    let bar = ProgressBar::new(100);
+    bar.set_style(
+      ProgressStyle::with_template("{spinner} [{bar:40.cyan/blue}] {pos:>3}% {msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("=> "),
+    );
+    bar.enable_steady_tick(std::time::Duration::from_millis(120));
+    Self { bar }
+  }
+}
+
+impl DownloadReporter for ProgressBarReporter {
+  fn progress(&self, event: DownloadProgress) {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    self.bar.set_position(event.percent.clamp(0.0, 100.0) as u64);
+    let message = match (event.speed, event.eta) {
+      (Some(speed), Some(eta)) => format!("{speed} ETA {eta}"),
+      (Some(speed), None) => speed,
+      (None, Some(eta)) => format!("ETA {eta}"),
+      (None, None) => String::new(),
+    };
+    self.bar.set_message(message);
+  }
+
+  fn complete(&self, event: DownloadCompleteEvent) {
+    self.bar.finish_with_message(event.output_path);
+  }
+
+  fn error(&self, event: DownloadErrorEvent) {
+    self.bar.abandon_with_message(event.error);
+  }
+}
+
+// ─── サブコマンド ───────────────────────────────────
+
+async fn download(args: DownloadArgs) -> Result<(), String> {
+  // アプリ起動時のセットアップに相当（FFmpeg / Deno がなくても続行し、yt-dlp 側で失敗させる）
+  if let Err(e) = downloader::ensure_ffmpeg().await {
+    eprintln!("warning: FFmpeg を準備できません: {e}");
+  }
+  if let Err(e) = downloader::ensure_deno().await {
+    eprintln!("warning: Deno を準備できません: {e}");
+  }
+
+  let app_settings = settings::load_settings().unwrap_or_default();
+  let preferred_format = args.format.or_else(|| {
+    // 設定の既定フォーマットは映像用のため、音声のみの場合やプリセットを使う場合は使わない
+    (!args.audio && args.preset.is_none()).then_some(app_settings.default_format)
+  });
+
+  let request = DownloadRequest {
+    download_id: uuid::Uuid::new_v4().to_string(),
+    url: args.url,
+    preset: args.preset,
+    options: DownloadOptionsOverride {
+      audio_only: args.audio.then_some(true),
+      folder_path: args.out.map(|p| p.to_string_lossy().into_owned()),
+      best_quality: if args.audio {
+        Some(false)
+      } else {
+        args.quality.map(|quality| matches!(quality, Quality::Best))
+      },
+      download_subtitles: args.subtitles.then_some(true),
+      preferred_format,
+    },
+    custom_filename: args.filename,
+    ..DownloadRequest::default()
+  };
+
+  let reporter = ProgressBarReporter::new();
+  let output_path = download::run_download(&reporter, request).await?;
+  println!("{output_path}");
+  Ok(())
+}
+
+async fn metadata(url: String, json: bool) -> Result<(), String> {
+  let metadata = download::fetch_metadata(&url, None, None).await?;
+  if json {
+    println!("{}", to_json(&metadata)?);
+  } else {
+    println!("title:     {}", metadata.title);
+    println!("duration:  {}", metadata.duration.as_deref().unwrap_or("-"));
+    println!("thumbnail: {}", metadata.thumbnail.as_deref().unwrap_or("-"));
+  }
+  Ok(())
+}
+
+fn history_list(limit: usize, failed: bool) -> Result<(), String> {
+  let filter = failed.then_some(history::HistoryStatus::Failed);
+  for entry in &history::load_entries(filter, Some(limit))? {
+    let status = match entry.status {
+      history::HistoryStatus::Success => "ok",
+      history::HistoryStatus::Failed => "failed",
+    };
+    println!(
+      "{}  {:<6}  {:<4}  {}  {}",
+      entry.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+      status,
+      entry.format,
+      entry.title,
+      entry.url
+    );
+  }
+  Ok(())
+}
+
+/// CSV のフィールドをエスケープする
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+fn history_csv(entries: &[HistoryEntry]) -> String {
+  let mut csv = String::from("id,timestamp,status,title,url,format,size,filePath,errorMessage\n");
+  for entry in entries {
+    let status = match entry.status {
+      history::HistoryStatus::Success => "success",
+      history::HistoryStatus::Failed => "failed",
+    };
+    let fields = [
+      entry.id.clone(),
+      entry.timestamp.to_rfc3339(),
+      status.to_string(),
+      entry.title.clone(),
+      entry.url.clone(),
+      entry.format.clone(),
+      entry.size.map(|s| s.to_string()).unwrap_or_default(),
+      entry.file_path.clone().unwrap_or_default(),
+      entry.error_message.clone().unwrap_or_default(),
+    ];
+    let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+    csv.push_str(&row.join(","));
+    csv.push('\n');
+  }
+  csv
+}
+
+fn history_export(format: ExportFormat, output: Option<PathBuf>) -> Result<(), String> {
+  let entries = history::load_all_entries()?;
+  let content = match format {
+    ExportFormat::Json => to_json(&entries)?,
+    ExportFormat::Csv => history_csv(&entries),
+  };
+
+  match output {
+    Some(path) => {
+      std::fs::write(&path, content).map_err(|e| format!("error.file_write_failed:{e}"))?;
+      eprintln!("{} 件を書き出しました: {}", entries.len(), path.display());
+    }
+    None => print!("{content}"),
+  }
+  Ok(())
+}
+
+/// ドット区切りのキーで JSON の値を辿る
+fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+  key.split('.').try_fold(value, |current, part| current.get(part))
+}
+
+fn settings_get(key: Option<String>) -> Result<(), String> {
+  let app_settings = settings::load_settings()?;
+  let value = serde_json::to_value(&app_settings).map_err(|e| format!("error.settings_parse_failed:{e}"))?;
+
+  let value = match key.as_deref() {
+    Some(key) => lookup(&value, key).ok_or_else(|| format!("error.unknown_setting:{key}"))?,
+    None => &value,
+  };
+  match value {
+    Value::String(s) => println!("{s}"),
+    other => println!("{}", to_json(other)?),
+  }
+  Ok(())
+}
+
+fn settings_set(key: &str, raw: &str) -> Result<(), String> {
+  let app_settings = settings::load_settings()?;
+  let mut value = serde_json::to_value(&app_settings).map_err(|e| format!("error.settings_parse_failed:{e}"))?;
+
+  let new_value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+
+  let (parent_key, field) = key.rsplit_once('.').map_or((None, key), |(p, f)| (Some(p), f));
+  let parent = match parent_key {
+    Some(parent_key) => {
+      key_path_mut(&mut value, parent_key).ok_or_else(|| format!("error.unknown_setting:{key}"))?
+    }
+    None => &mut value,
+  };
+  let Some(object) = parent.as_object_mut() else {
+    return Err(format!("error.unknown_setting:{key}"));
+  };
+  // Option のフィールドは null で出力されるため、既存キーのみ受け付ける
+  if !object.contains_key(field) {
+    return Err(format!("error.unknown_setting:{key}"));
+  }
+  object.insert(field.to_string(), new_value);
+
+  let updated: AppSettings =
+    serde_json::from_value(value).map_err(|e| format!("error.invalid_setting_value:{e}"))?;
+  settings::save_settings(&updated)?;
+  Ok(())
+}
+
+fn key_path_mut<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+  key.split('.').try_fold(value, |current, part| current.get_mut(part))
+}
+
+async fn binaries_update(target: BinaryTarget, version: Option<String>) -> Result<(), String> {
+  let all = target == BinaryTarget::All;
+
+  if all || target == BinaryTarget::YtDlp {
+    println!("yt-dlp: {}", downloader::update_yt_dlp(version.clone()).await?);
+  }
+  if all || target == BinaryTarget::Ffmpeg {
+    println!("ffmpeg: {}", downloader::update_ffmpeg(version.clone()).await?);
+  }
+  if all || target == BinaryTarget::Deno {
+    println!("deno: {}", downloader::update_deno(version).await?);
+  }
+  Ok(())
+}
+
+fn binaries_status() -> Result<(), String> {
+  for status in downloader::get_binaries_status()? {
+    let state = match (status.installed, status.external) {
+      _ if status.error.is_some() => "invalid",
+      (false, _) => "missing",
+      (true, true) => "external",
+      (true, false) => "bundled",
+    };
+    println!(
+      "{:<7} {:<8} {:<24} {}",
+      status.name,
+      state,
+      status.version.as_deref().unwrap_or("-"),
+      status.path
+    );
+  }
+  Ok(())
+}
+
+fn files_list(json: bool) -> Result<(), String> {
+  let files = download::list_downloaded_files()?;
+  if json {
+    println!("{}", to_json(&files)?);
+    return Ok(());
+  }
+  for file in files {
+    #[allow(clippy::cast_precision_loss)]
+    let size_mb = file.size as f64 / (1024.0 * 1024.0);
+    println!("{:>9.1} MB  {:<4}  {}", size_mb, file.format, file.path);
+  }
+  Ok(())
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<String, String> {
+  serde_json::to_string_pretty(value).map_err(|e| format!("error.serialize_failed:{e}"))
+}
+
+async fn run(command: Command) -> Result<(), String> {
+  match command {
+    Command::Download(args) => download(args).await,
+    Command::Metadata { url, json } => metadata(url, json).await,
+    Command::History(HistoryCommand::List { limit, failed }) => history_list(limit, failed),
+    Command::History(HistoryCommand::Export { format, output }) => history_export(format, output),
+    Command::Settings(SettingsCommand::Get { key }) => settings_get(key),
+    Command::Settings(SettingsCommand::Set { key, value }) => settings_set(&key, &value),
+    Command::Binaries(BinariesCommand::Update { target, version }) => {
+      binaries_update(target, version).await
+    }
+    Command::Binaries(BinariesCommand::Status) => binaries_status(),
+    Command::Files(FilesCommand::List { json }) => files_list(json),
+  }
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  // 固定バージョンは個別指定時のみ有効（バイナリごとに番号体系が異なるため）
+  if let Command::Binaries(BinariesCommand::Update { target: BinaryTarget::All, version: Some(_) }) =
+    &cli.command
+  {
+    Cli::command()
+      .error(
+        ErrorKind::ArgumentConflict,
+        "--version は更新対象（yt-dlp / ffmpeg / deno）を指定した場合のみ使えます",
+      )
+      .exit();
+  }
+
+  // 環境変数の変更はスレッドを作る前に行う（tokio のワーカースレッドを起動する前）
+  environment::prepare_environment();
+
+  let result = match tokio::runtime::Runtime::new() {
+    Ok(runtime) => runtime.block_on(run(cli.command)),
+    Err(e) => Err(format!("error.runtime_failed:{e}")),
+  };
+
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(e) => {
+      eprintln!("error: {e}");
+      ExitCode::FAILURE
+    }
+  }
+}
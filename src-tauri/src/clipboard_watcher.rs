@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::Emitter;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::sync::watch;
+
+use crate::commands;
+use crate::download::{self, clean_timestamp_param, DownloadRequest, VideoMetadata};
+use crate::history;
+use crate::redact;
+use crate::settings::{self, ClipboardSettings};
+use crate::utils::is_valid_url;
+
+/// クリップボードを確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 重複判定に使う直近の履歴件数
+const RECENT_HISTORY_LIMIT: usize = 200;
+/// 一度検出した URL を覚えておく件数（同じ URL を繰り返しコピーしても通知しない）
+const SEEN_LIMIT: usize = 100;
+
+/// 起動中の監視タスクの停止通知
+static WATCHER: LazyLock<Mutex<Option<watch::Sender<bool>>>> = LazyLock::new(|| Mutex::new(None));
+
+/// `clipboard-url-detected` イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardUrlDetected {
+  pub url: String,
+  pub metadata: VideoMetadata,
+  /// 自動でキューに追加した場合のダウンロードID
+  pub download_id: Option<String>,
+}
+
+/// 設定に合わせて監視を開始・停止する
+pub fn apply(app_handle: &tauri::AppHandle, clipboard_settings: &ClipboardSettings) {
+  let mut watcher = WATCHER.lock().unwrap();
+
+  if !clipboard_settings.enabled {
+    if let Some(stop) = watcher.take() {
+      let _ = stop.send(true);
+      log::info!("クリップボードの監視を停止しました");
+    }
+    return;
+  }
+  if watcher.is_some() {
+    // 自動追加の設定は検出のたびに読み直すため、再起動は不要
+    return;
+  }
+
+  let (stop, stop_rx) = watch::channel(false);
+  *watcher = Some(stop);
+  tauri::async_runtime::spawn(watch_clipboard(app_handle.clone(), stop_rx));
+  log::info!("クリップボードの監視を開始しました");
+}
+
+/// 直近の履歴に同じ URL があるか
+fn in_recent_history(url: &str) -> bool {
+  history::load_entries(None, Some(RECENT_HISTORY_LIMIT))
+    .map(|entries| {
+      entries
+        .iter()
+        .any(|entry| clean_timestamp_param(&entry.url) == url)
+    })
+    .unwrap_or(false)
+}
+
+/// クリップボードのテキストから候補の URL を取り出す（1行の URL のみ対象）
+fn candidate_url(text: &str) -> Option<String> {
+  let text = text.trim();
+  if text.is_empty() || text.contains(char::is_whitespace) || !is_valid_url(text) {
+    return None;
+  }
+  Some(clean_timestamp_param(text))
+}
+
+async fn watch_clipboard(app_handle: tauri::AppHandle, mut stop: watch::Receiver<bool>) {
+  // 監視開始時点の内容は検出対象にしない
+  let mut last_text = app_handle.clipboard().read_text().unwrap_or_default();
+  let mut seen: VecDeque<String> = VecDeque::new();
+
+  loop {
+    tokio::select! {
+      () = tokio::time::sleep(POLL_INTERVAL) => {}
+      _ = stop.changed() => return,
+    }
+
+    // 画像のみのコピーなどテキストがない場合はエラーになる
+    let Ok(text) = app_handle.clipboard().read_text() else {
+      continue;
+    };
+    if text == last_text {
+      continue;
+    }
+    last_text = text;
+
+    let Some(url) = candidate_url(&last_text) else {
+      continue;
+    };
+    if seen.contains(&url) || in_recent_history(&url) {
+      continue;
+    }
+    seen.push_back(url.clone());
+    if seen.len() > SEEN_LIMIT {
+      seen.pop_front();
+    }
+
+    // メタデータを取得できた URL のみ yt-dlp が扱えるものとして通知する
+    let metadata = match download::probe_metadata(&url).await {
+      Ok(metadata) => metadata,
+      Err(e) => {
+        log::debug!("クリップボードの URL は対象外です ({}): {e}", redact::redact(&url));
+        continue;
+      }
+    };
+
+    let clipboard_settings = settings::load_settings().unwrap_or_default().clipboard;
+    let download_id = if clipboard_settings.auto_enqueue {
+      let request = DownloadRequest {
+        url: url.clone(),
+        preset: clipboard_settings.preset,
+        metadata_title: Some(metadata.title.clone()),
+        thumbnail: metadata.thumbnail.clone(),
+        ..DownloadRequest::default()
+      };
+      match commands::enqueue_download(&app_handle, request) {
+        Ok(id) => Some(id),
+        Err(e) => {
+          log::warn!("クリップボードの URL をキューに追加できません: {e}");
+          None
+        }
+      }
+    } else {
+      None
+    };
+
+    log::info!("クリップボードから URL を検出しました: {}", redact::redact(&url));
+    let _ = app_handle.emit(
+      "clipboard-url-detected",
+      ClipboardUrlDetected { url, metadata, download_id },
+    );
+  }
+}
@@ -1,759 +1,195 @@
-use chrono::{DateTime, Utc};
-use regex::Regex;
 use serde::Serialize;
-
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{LazyLock, Mutex};
 use tauri::Emitter;
-use tokio::io::AsyncBufReadExt;
 use uuid::Uuid;
-use youtube_dl::YoutubeDl;
-
-use crate::downloader::{get_ffmpeg_dir, get_yt_dlp_path};
-use crate::history::{self, HistoryEntry, HistoryGroup, HistoryStatus};
-use crate::settings::{self, AppSettings};
-use crate::utils::{get_default_download_path, is_safe_path, is_valid_url, sanitize_filename};
-
-/// 対応する動画拡張子
-const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "webm", "avi", "mov", "flv"];
-/// 対応する音声拡張子
-const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "opus", "ogg", "wav", "flac", "aac"];
-/// yt-dlp でダウンロード可能な音声フォーマット
-const SUPPORTED_AUDIO_FORMATS: &[&str] = &["mp3", "m4a"];
-
-/// yt-dlp フォーマットセレクタ
-const FMT_BEST_AUDIO: &str = "bestaudio/best";
-const FMT_BEST_VIDEO_AUDIO: &str = "bestvideo+bestaudio/best";
-const FMT_BEST_SINGLE: &str = "best";
-
-static RE_AMP_TIMESTAMP: LazyLock<Regex> =
-  LazyLock::new(|| Regex::new(r"&t=\d+\.?\d*").unwrap());
-static RE_FIRST_TIMESTAMP: LazyLock<Regex> =
-  LazyLock::new(|| Regex::new(r"\?t=\d+\.?\d*&").unwrap());
-static RE_ONLY_TIMESTAMP: LazyLock<Regex> =
-  LazyLock::new(|| Regex::new(r"\?t=\d+\.?\d*$").unwrap());
-static RE_PROGRESS: LazyLock<Regex> = LazyLock::new(|| {
-  Regex::new(r"\[download\]\s+(\d+\.?\d*)%(?:\s+of\s+~?\S+\s+at\s+(\S+)(?:\s+ETA\s+(\S+))?)?")
-    .unwrap()
-});
-
-// ─── ダウンロード状態管理 ──────────────────────────────
-// フロントエンドがリロードしてもRust側で状態を保持し、復帰時に問い合わせられるようにする
-
-/// 各ダウンロードの現在の状態
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DownloadStatusEntry {
-  pub id: String,
-  pub status: &'static str, // "downloading" | "completed" | "error"
-  pub percent: f64,
-  pub output_path: Option<String>,
-  pub error: Option<String>,
-}
-
-/// グローバルなダウンロード状態マップ
-/// フロントエンドが再接続したとき、ここに問い合わせて復帰する
-static DOWNLOAD_MANAGER: LazyLock<Mutex<HashMap<String, DownloadStatusEntry>>> =
-  LazyLock::new(|| Mutex::new(HashMap::new()));
-
-fn dm_set_downloading(id: &str) {
-  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
-  map.insert(id.to_string(), DownloadStatusEntry {
-    id: id.to_string(),
-    status: "downloading",
-    percent: 0.0,
-    output_path: None,
-    error: None,
-  });
-}
 
-fn dm_update_progress(id: &str, percent: f64) {
-  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
-  if let Some(entry) = map.get_mut(id) {
-    entry.percent = percent;
+use crate::api_server::{self, ApiStatus};
+use crate::clipboard_watcher;
+use crate::cookies::{self, CookieSetInfo};
+use crate::credentials::{self, CredentialInfo, CredentialInput};
+use crate::download::{
+  self, DownloadCompleteEvent, DownloadErrorEvent, DownloadProgress, DownloadReporter,
+  DownloadRequest, DownloadStatusEntry, DownloadedFile, VideoMetadata,
+};
+use crate::downloader::{
+  BinaryStatus, BinaryVersionInfo, BinaryVersionReport, SetupStepState,
+};
+use crate::history::{self, HistoryEntry, HistoryGroup};
+use crate::logging;
+use crate::native_messaging::{self, NativeHostBrowser, NativeHostStatus};
+use crate::network::{self, HeaderOverrides};
+use crate::presets::{self, DownloadOptionsOverride, SUPPORTED_AUDIO_FORMATS, SUPPORTED_VIDEO_FORMATS};
+use crate::settings::{self, AppSettings, DownloadPreset, ProxySettings};
+use crate::url_import::{self, ImportedUrl, UrlImportPreview};
+use crate::watch_folder;
+use crate::utils::is_valid_url;
+
+/// フロントエンドがまだ受け取っていないダウンロード要求
+///
+/// 起動直後（画面の読み込み前）に届いた要求を取りこぼさないよう、
+/// フロントエンドは `take_pending_downloads` でまとめて受け取る。
+static PENDING_DOWNLOADS: LazyLock<Mutex<Vec<DownloadRequest>>> =
+  LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// アプリ外（HTTP API など）からのダウンロード要求をフロントエンドのキューに追加する
+///
+/// 同時実行数の制御はフロントエンドのキューが行うため、ここでは要求を保留リストに積んで
+/// "download-requested" イベントで知らせるだけで、実際のダウンロードはキューから
+/// `download_video` で開始される。プリセットはこの時点で解決し、要求には全項目を埋めて渡す。
+pub fn enqueue_download(
+  app_handle: &tauri::AppHandle,
+  mut request: DownloadRequest,
+) -> Result<String, String> {
+  if !is_valid_url(&request.url) {
+    return Err("error.invalid_url".to_string());
   }
-}
-
-fn dm_set_completed(id: &str, output_path: &str) {
-  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
-  if let Some(entry) = map.get_mut(id) {
-    entry.status = "completed";
-    entry.percent = 100.0;
-    entry.output_path = Some(output_path.to_string());
+  let options = presets::resolve(request.preset.as_deref(), &request.options)?;
+  request.options = options.into();
+  if request.download_id.is_empty() {
+    request.download_id = Uuid::new_v4().to_string();
+  } else if !logging::is_valid_download_id(&request.download_id) {
+    return Err("error.invalid_download_id".to_string());
   }
-}
 
-fn dm_set_error(id: &str, error: &str) {
-  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
-  if let Some(entry) = map.get_mut(id) {
-    entry.status = "error";
-    entry.error = Some(error.to_string());
-  }
+  log::info!("ダウンロード要求を受け付けました: {} ({})", request.url, request.download_id);
+  PENDING_DOWNLOADS.lock().unwrap().push(request.clone());
+  let _ = app_handle.emit("download-requested", &request);
+  Ok(request.download_id)
 }
 
-fn dm_remove(id: &str) {
-  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
-  map.remove(id);
+/// 保留中のダウンロード要求を取り出す（フロントエンドのキューに追加される）
+#[tauri::command]
+pub fn take_pending_downloads() -> Vec<DownloadRequest> {
+  std::mem::take(&mut *PENDING_DOWNLOADS.lock().unwrap())
 }
 
-#[derive(Serialize)]
-pub struct VideoMetadata {
-  pub title: String,
-  pub thumbnail: Option<String>,
-  pub duration: Option<String>,
-}
-
-/// yt-dlp メタデータ出力からサムネイルURLを抽出する
-fn extract_thumbnail(output: &youtube_dl::YoutubeDlOutput) -> Option<String> {
-  if let Some(playlist) = output.clone().into_playlist() {
-    playlist
-      .thumbnails
-      .and_then(|thumbs| thumbs.first().cloned())
-      .and_then(|thumb| thumb.url)
-  } else if let Some(video) = output.clone().into_single_video() {
-    video.thumbnail.or_else(|| {
-      video
-        .thumbnails
-        .and_then(|thumbs| thumbs.first().cloned())
-        .and_then(|thumb| thumb.url)
-    })
-  } else {
-    None
+/// ダウンロードを中断し、フロントエンドのキューからも取り除く
+pub fn remove_download(app_handle: &tauri::AppHandle, id: &str) {
+  if !download::cancel_running_download(id) {
+    download::dm_remove(id);
   }
+  let _ = app_handle.emit("download-removed", id);
 }
 
-#[derive(Serialize, Clone)]
-struct DownloadProgress {
-  id: String,
-  percent: f64,
-  speed: Option<String>,
-  eta: Option<String>,
-}
-
-#[derive(Serialize, Clone)]
-struct DownloadCompleteEvent {
-  id: String,
-  output_path: String,
-}
-
-#[derive(Serialize, Clone)]
-struct DownloadErrorEvent {
-  id: String,
-  error: String,
-}
-
-#[tauri::command]
-pub async fn download_metadata(url: String) -> Result<VideoMetadata, String> {
-  log::info!("Downloading metadata: {url}");
-
-  if !is_valid_url(&url) {
-    return Err("error.invalid_url".to_string());
+impl DownloadReporter for tauri::AppHandle {
+  fn progress(&self, event: DownloadProgress) {
+    api_server::publish("download-progress", &event);
+    let _ = self.emit("download-progress", event);
   }
 
-  let cleaned_url = clean_timestamp_param(&url);
-  let yt_dlp_path = get_yt_dlp_path().await?;
-  let app_settings = settings::load_settings().unwrap_or_default();
-
-  let mut instance = YoutubeDl::new(cleaned_url);
-  instance.youtube_dl_path(&yt_dlp_path);
-
-  instance
-    .socket_timeout("15")
-    .flat_playlist(true)
-    .extra_arg("--no-check-certificate")
-    .extra_arg("--force-ipv4");
-
-  if let Some(browser) = &app_settings.cookies_browser {
-    instance.extra_arg("--cookies-from-browser").extra_arg(browser);
+  fn complete(&self, event: DownloadCompleteEvent) {
+    api_server::publish("download-complete", &event);
+    let _ = self.emit("download-complete", event);
   }
 
-  let result = instance.run_async().await;
-
-  log::info!("Downloaded metadata");
-
-  match result {
-    Ok(metadata) => {
-      let thumbnail = extract_thumbnail(&metadata);
-
-      if let Some(playlist) = metadata.clone().into_playlist() {
-        Ok(VideoMetadata {
-          title: playlist.title.unwrap_or_else(|| "No Title".to_string()),
-          thumbnail,
-          duration: None,
-        })
-      } else if let Some(video) = metadata.into_single_video() {
-        let duration = video.duration.and_then(|d| format_duration(&d));
-
-        Ok(VideoMetadata {
-          title: video.title.unwrap_or_else(|| "No Title".to_string()),
-          thumbnail,
-          duration,
-        })
-      } else {
-        Err("error.get_title_failed".to_string())
-      }
-    }
-    Err(e) => Err(map_yt_dlp_error(&e.to_string(), "error.metadata_failed")),
+  fn error(&self, event: DownloadErrorEvent) {
+    api_server::publish("download-error", &event);
+    let _ = self.emit("download-error", event);
   }
 }
 
-/// `serde_json::Value` から duration を "MM:SS" 形式にフォーマットする
-fn format_duration(d: &serde_json::Value) -> Option<String> {
-  let seconds = if let Some(s) = d.as_u64() {
-    Some(s)
-  } else if let Some(f) = d.as_f64() {
-    if f >= 0.0 && f.is_finite() {
-      #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-      Some(f as u64)
-    } else {
-      None
-    }
-  } else if let Some(s) = d.as_str() {
-    s.parse::<u64>().ok()
-  } else {
-    serde_json::to_string(d).ok().and_then(|json_str| {
-      let cleaned = if json_str.contains('.') {
-        json_str.split('.').next().unwrap_or("0").trim_matches('"')
-      } else {
-        json_str.trim_matches('"')
-      };
-      cleaned.parse::<u64>().ok()
-    })
-  };
-
-  seconds.map(|s| format!("{:02}:{:02}", s / 60, s % 60))
+#[tauri::command]
+pub async fn download_metadata(
+  url: String,
+  video_password: Option<String>,
+  headers: Option<HeaderOverrides>,
+) -> Result<VideoMetadata, String> {
+  download::fetch_metadata(&url, video_password.as_deref(), headers.as_ref()).await
 }
 
+/// ダウンロードを開始する（request.preset のプリセットに request の各項目を上書きして使う）
 #[tauri::command]
-#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 pub async fn download_video(
   app_handle: tauri::AppHandle,
-  download_id: String,
-  url: String,
-  audio_only: bool,
-  folder_path: Option<String>,
-  best_quality: bool,
-  download_subtitles: bool,
-  preferred_format: Option<String>,
-  custom_filename: Option<String>,
-  thumbnail: Option<String>,
-  metadata_title: Option<String>,
+  request: DownloadRequest,
 ) -> Result<String, String> {
-  log::info!("Downloading video: {url}");
-
-  if !is_valid_url(&url) {
-    return Err("error.invalid_url".to_string());
-  }
-
-  let cleaned_url = clean_timestamp_param(&url);
-  let yt_dlp_path = get_yt_dlp_path().await?;
-
-  // ファイル名の生成
-  // 優先順: カスタムファイル名 > フロントエンドで取得済みのタイトル > yt-dlpから再取得
-  let (filename_base, thumbnail) = match custom_filename {
-    Some(filename) if !filename.trim().is_empty() => (sanitize_filename(&filename), thumbnail),
-    _ => match metadata_title {
-      Some(title) if !title.trim().is_empty() => (sanitize_filename(&title), thumbnail),
-      _ => {
-        let (title, fetched_thumb) = get_video_info(&cleaned_url, &yt_dlp_path.to_string_lossy()).await?;
-        (title, thumbnail.or(fetched_thumb))
-      }
-    },
-  };
-
-  // 出力ファイル名生成（不正なフォーマットはデフォルトにフォールバック）
-  let extension = if audio_only {
-    let fmt = preferred_format.as_deref().unwrap_or("mp3");
-    if SUPPORTED_AUDIO_FORMATS.contains(&fmt) { fmt } else { "mp3" }
-  } else {
-    preferred_format.as_deref().unwrap_or("mp4")
-  };
-  let output_filename = format!("{filename_base}.{extension}");
-
-  // フォルダパスの検証と安全なパスの構築
-  // 優先順位: 引数 folder_path > settings.save_path > OS デフォルト
-  let app_settings = settings::load_settings().unwrap_or_default();
-
-  let resolve_folder = |p: &str| -> Result<String, String> {
-    let path = Path::new(p);
-    if !path.is_dir() {
-      return Err("error.path_not_dir".to_string());
-    }
-    // audio/video でサブディレクトリを振り分け
-    let subdir = if audio_only { settings::SUBDIR_AUDIO } else { settings::SUBDIR_VIDEOS };
-    let target_dir = path.join(subdir);
-    let target_dir = if target_dir.is_dir() { target_dir } else { path.to_path_buf() };
-
-    let full_path = target_dir.join(&output_filename);
-    if !is_safe_path(&full_path) {
-      return Err("error.unsafe_path".to_string());
-    }
-    Ok(full_path.to_string_lossy().to_string())
-  };
-
-  let base_output_path = match folder_path {
-    Some(ref p) if !p.trim().is_empty() => resolve_folder(p)?,
-    _ if !app_settings.save_path.is_empty() => {
-      resolve_folder(&app_settings.save_path)
-        .unwrap_or_else(|_| get_default_download_path(&output_filename).unwrap_or_default())
-    }
-    _ => get_default_download_path(&output_filename)?,
-  };
-
-  // ファイル名が存在する場合はUUIDを追加して重複を回避
-  #[allow(unused_mut)]
-  let mut output_path = if Path::new(&base_output_path).exists() {
-    let dir = Path::new(&base_output_path)
-      .parent()
-      .ok_or("error.parent_dir_failed")?;
-    let stem = Path::new(&output_filename)
-      .file_stem()
-      .ok_or("error.parent_dir_failed")?;
-    let ext = Path::new(&output_filename)
-      .extension()
-      .ok_or("error.parent_dir_failed")?;
-
-    let uuid_str = Uuid::new_v4().to_string();
-    let uuid = uuid_str.split('-').next().unwrap_or("unique");
-    let new_filename = format!(
-      "{}_{}.{}",
-      stem.to_string_lossy(),
-      uuid,
-      ext.to_string_lossy()
-    );
-
-    let new_path = dir.join(new_filename);
-    log::info!("ファイル名の重複を回避: {}", new_path.to_string_lossy());
-
-    new_path.to_string_lossy().to_string()
-  } else {
-    base_output_path
-  };
-
-  // Windows環境では単純なパス処理
-  #[cfg(windows)]
-  {
-    let simple_path = if output_path.contains(" ") {
-      let dir = Path::new(&output_path).parent().unwrap_or(Path::new(""));
-      let ext = Path::new(&output_path)
-        .extension()
-        .unwrap_or_else(|| std::ffi::OsStr::new("mp4"));
-
-      let filename_base = Path::new(&output_path)
-        .file_stem()
-        .unwrap_or_else(|| std::ffi::OsStr::new("video"))
-        .to_string_lossy()
-        .to_string();
-
-      let clean_name = filename_base
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .take(100)
-        .collect::<String>();
-
-      let mut new_path = dir.join(format!("{}.{}", clean_name, ext.to_string_lossy()));
-
-      // 単純化後のファイル名が既に存在する場合はUUIDで衝突回避
-      if new_path.exists() {
-        let uuid_str = Uuid::new_v4().to_string();
-        let uuid = uuid_str.split('-').next().unwrap_or("unique");
-        new_path = dir.join(format!("{}_{}.{}", clean_name, uuid, ext.to_string_lossy()));
-      }
-
-      log::info!(
-        "パス名を単純化: {} -> {}",
-        output_path,
-        new_path.to_string_lossy()
-      );
-      new_path.to_string_lossy().to_string()
-    } else {
-      output_path.clone()
-    };
-
-    output_path = simple_path;
-  }
-
-  log::info!("Output file: {output_path}");
-
-  let ffmpeg_location = get_ffmpeg_dir().ok().and_then(|dir| {
-    let ffmpeg = dir.join(if cfg!(windows) { "ffmpeg.exe" } else { "ffmpeg" });
-    ffmpeg.exists().then(|| ffmpeg.to_string_lossy().to_string())
-  });
-
-  let args = build_yt_dlp_args(
-    &cleaned_url,
-    &output_path,
-    audio_only,
-    best_quality,
-    download_subtitles,
-    preferred_format.as_deref(),
-    app_settings.cookies_browser.as_deref(),
-    ffmpeg_location.as_deref(),
-  );
-
-  log::info!("Starting download...");
-  log::debug!(
-    "実行コマンド: {} {}",
-    yt_dlp_path.to_string_lossy(),
-    args.join(" ")
-  );
-
-  // ダウンロード状態をManagerに登録
-  dm_set_downloading(&download_id);
-
-  if let Err(e) = run_yt_dlp_with_progress(&app_handle, &yt_dlp_path, &download_id, &args, best_quality && !audio_only).await {
-    let _ = history::add_entry(build_history_entry(
-      &url, &filename_base, extension, best_quality,
-      HistoryStatus::Failed, None, Some(e.clone()), thumbnail.clone(), None,
-    ));
-    dm_set_error(&download_id, &e);
-    let _ = app_handle.emit("download-error", DownloadErrorEvent { id: download_id.clone(), error: e.clone() });
-    schedule_dm_cleanup(download_id);
-    return Err(e);
-  }
-
-  if Path::new(&output_path).exists() {
-    let file_size = std::fs::metadata(&output_path).ok().map(|m| m.len());
-    let size_str = file_size.map_or("不明".to_string(), |s| format!("{s} bytes"));
-    log::info!("出力ファイル: {output_path} (サイズ: {size_str})");
-    let _ = app_handle.emit("download-progress", DownloadProgress { id: download_id.clone(), percent: 100.0, speed: None, eta: None });
-
-    let _ = history::add_entry(build_history_entry(
-      &url, &filename_base, extension, best_quality,
-      HistoryStatus::Success, file_size, None, thumbnail, Some(output_path.clone()),
-    ));
-
-    dm_set_completed(&download_id, &output_path);
-    let _ = app_handle.emit("download-complete", DownloadCompleteEvent { id: download_id.clone(), output_path: output_path.clone() });
-    schedule_dm_cleanup(download_id);
-    Ok(output_path)
-  } else {
-    log::warn!("出力ファイルが存在しません: {output_path}");
-
-    let _ = history::add_entry(build_history_entry(
-      &url, &filename_base, extension, best_quality,
-      HistoryStatus::Failed, None, Some("error.file_not_found".to_string()), thumbnail, None,
-    ));
-
-    dm_set_error(&download_id, "error.file_not_found");
-    let _ = app_handle.emit("download-error", DownloadErrorEvent { id: download_id.clone(), error: "error.file_not_found".to_string() });
-    schedule_dm_cleanup(download_id);
-    Err("error.file_not_found".to_string())
-  }
+  download::run_download(&app_handle, request).await
 }
 
-/// yt-dlp のコマンド引数を構築する
-#[allow(clippy::too_many_arguments)]
-fn build_yt_dlp_args(
-  url: &str,
-  output_path: &str,
-  audio_only: bool,
-  best_quality: bool,
-  download_subtitles: bool,
-  preferred_format: Option<&str>,
-  cookies_browser: Option<&str>,
-  ffmpeg_location: Option<&str>,
-) -> Vec<String> {
-  let format_value = preferred_format.unwrap_or("mp4");
-  let mut args: Vec<String> = vec![
-    "--newline".into(),
-    "--socket-timeout".into(),
-    "15".into(),
-    "--no-check-certificate".into(),
-    "--force-ipv4".into(),
-    "--no-playlist".into(),
-  ];
-
-  if let Some(location) = ffmpeg_location {
-    args.extend(["--ffmpeg-location".into(), location.into()]);
-  }
-
-  if let Some(browser) = cookies_browser {
-    args.extend(["--cookies-from-browser".into(), browser.to_string()]);
-  }
-
-  #[cfg(windows)]
-  {
-    args.push("--windows-filenames".into());
-    // CREATE_NO_WINDOW でコンソールがないため、上書き確認プロンプトでハングを防止
-    args.push("--force-overwrites".into());
-  }
-
-  if audio_only {
-    let audio_format = if SUPPORTED_AUDIO_FORMATS.contains(&format_value) {
-      format_value
-    } else {
-      "mp3"
-    };
-    args.extend(
-      [
-        "--extract-audio",
-        "--audio-format",
-        audio_format,
-        "--audio-quality",
-        "0",
-        "--format",
-        FMT_BEST_AUDIO,
-        "--no-mtime",
-      ]
-      .map(String::from),
-    );
-  } else {
-    let format_selector = if best_quality { FMT_BEST_VIDEO_AUDIO } else { FMT_BEST_SINGLE };
-    args.extend(
-      ["--format", format_selector, "--merge-output-format", format_value]
-        .map(String::from),
-    );
-
-    // MP4コンテナの場合、Opus音声をAACに変換してWindows互換性を確保
-    if format_value == "mp4" {
-      args.extend([
-        "--postprocessor-args".into(),
-        "Merger+ffmpeg_o:-c:v copy -c:a aac".into(),
-      ]);
-    }
-  }
+/// フロントエンドが復帰時に現在のダウンロード状態を問い合わせるコマンド
+#[tauri::command]
+pub fn get_download_statuses() -> Vec<DownloadStatusEntry> {
+  download::get_download_statuses()
+}
 
-  if download_subtitles {
-    args.extend(
-      [
-        "--write-sub",
-        "--write-auto-sub",
-        "--sub-format",
-        "srt",
-        "--embed-subs",
-        "--sub-lang",
-        "ja,en",
-      ]
-      .map(String::from),
-    );
-  }
+// ─── yt-dlp コマンド ──────────────────────────────
 
-  args.extend(["-o".to_string(), output_path.to_string(), url.to_string()]);
-  args
+/// yt-dlpを更新する（バージョン省略時は固定バージョンまたはチャンネルの最新版）
+#[tauri::command]
+pub async fn update_yt_dlp(version: Option<String>) -> Result<String, String> {
+  crate::downloader::update_yt_dlp(version).await
 }
 
-/// yt-dlp プロセスを起動し、進捗をフロントエンドにリアルタイム通知する
-async fn run_yt_dlp_with_progress(
-  app_handle: &tauri::AppHandle,
-  yt_dlp_path: &Path,
-  download_id: &str,
-  args: &[String],
-  uses_separate_streams: bool,
-) -> Result<(), String> {
-  let mut cmd = tokio::process::Command::new(yt_dlp_path);
-  cmd.args(args)
-    .stdout(std::process::Stdio::piped())
-    .stderr(std::process::Stdio::piped());
-  #[cfg(windows)]
-  {
-    cmd.creation_flags(crate::downloader::CREATE_NO_WINDOW);
-  }
-  let mut child = cmd.spawn()
-    .map_err(|e| format!("error.ytdlp_spawn:{e}"))?;
-
-  let stdout = child
-    .stdout
-    .take()
-    .ok_or("error.stdout_failed")?;
-  let stderr = child
-    .stderr
-    .take()
-    .ok_or("error.stderr_failed")?;
-
-  // stderr をバックグラウンドで収集（エラー報告用）
-  let stderr_handle = tokio::spawn(async move {
-    let reader = tokio::io::BufReader::new(stderr);
-    let mut lines = reader.lines();
-    let mut output = String::new();
-    while let Ok(Some(line)) = lines.next_line().await {
-      log::debug!("yt-dlp stderr: {line}");
-      output.push_str(&line);
-      output.push('\n');
-    }
-    output
-  });
-
-  // stdout をパースしてダウンロード進捗を取得
-  let reader = tokio::io::BufReader::new(stdout);
-  let mut lines = reader.lines();
-  let mut pass: u32 = 0;
-  let mut last_raw_percent: f64 = 0.0;
-  let mut last_emitted: f64 = 0.0;
-  let mut stdout_error: Option<String> = None;
-
-  while let Ok(Some(line)) = lines.next_line().await {
-    log::debug!("yt-dlp: {line}");
-
-    // stdoutに出力されるERROR行を捕捉
-    if line.starts_with("ERROR:") {
-      stdout_error = Some(line);
-    } else if line.starts_with("[download]") {
-      if let Some(caps) = RE_PROGRESS.captures(&line) {
-        if let Ok(raw_percent) = caps[1].parse::<f64>() {
-          // パーセンテージが大幅に下がった場合、新しいダウンロードパスと判定
-          if raw_percent < last_raw_percent - 10.0 {
-            pass += 1;
-          }
-          last_raw_percent = raw_percent;
-
-          let percent = if uses_separate_streams {
-            match pass {
-              0 => raw_percent * 0.5,          // 映像ストリーム: 0-50%
-              1 => 50.0 + raw_percent * 0.45,  // 音声ストリーム: 50-95%
-              _ => 95.0,
-            }
-          } else {
-            raw_percent * 0.95 // 単一ストリーム: 0-95%
-          };
-
-          let percent = percent.min(95.0);
-
-          let speed = caps.get(2).map(|m| m.as_str().to_string());
-          let eta = caps.get(3).map(|m| m.as_str().to_string());
-
-          if percent > last_emitted {
-            last_emitted = percent;
-            dm_update_progress(download_id, percent);
-            let _ = app_handle.emit("download-progress", DownloadProgress { id: download_id.to_string(), percent, speed, eta });
-          }
-        }
-      }
-    } else if (line.contains("[Merger]")
-      || line.contains("[ExtractAudio]")
-      || line.contains("[FixupM3u8]"))
-      && last_emitted < 95.0
-    {
-      last_emitted = 95.0;
-      let _ = app_handle.emit(
-        "download-progress",
-        DownloadProgress { id: download_id.to_string(), percent: 95.0, speed: None, eta: None },
-      );
-    }
-  }
+/// yt-dlp / FFmpeg / Deno のインストール済み・固定・最新バージョンを返す
+#[tauri::command]
+pub async fn get_yt_dlp_version() -> Result<BinaryVersionReport, String> {
+  crate::downloader::get_binary_versions().await
+}
 
-  let status = child
-    .wait()
-    .await
-    .map_err(|e| format!("error.process_failed:{e}"))?;
-
-  let stderr_output = stderr_handle.await.unwrap_or_default();
-
-  if !status.success() {
-    log::error!("yt-dlpがエラーで終了しました: {stderr_output}");
-    // stdoutのERROR行 → stderrの非WARNING行の優先順位でエラーを特定
-    // WARNING行はyt-dlpの警告であり、エラーメッセージとして表示しない
-    let error_line = stdout_error.as_deref()
-      .or_else(|| {
-        stderr_output
-          .lines()
-          .filter(|line| !line.trim_start().starts_with("WARNING:"))
-          .last()
-      })
-      .unwrap_or("unknown error");
-    return Err(map_yt_dlp_error(error_line, "error.download_failed"));
-  }
+// ─── FFmpeg / Deno コマンド ────────────────────────
 
-  Ok(())
+/// FFmpegとFFprobeを更新する（バージョン省略時は固定バージョンまたは最新版）
+#[tauri::command]
+pub async fn update_ffmpeg(version: Option<String>) -> Result<String, String> {
+  crate::downloader::update_ffmpeg(version).await
 }
 
-/// 完了/エラー状態のエントリを一定時間後にManagerから削除する
-/// フロントエンドが復帰するまでの猶予を持たせるため60秒待つ
-fn schedule_dm_cleanup(id: String) {
-  tokio::spawn(async move {
-    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
-    dm_remove(&id);
-  });
+#[tauri::command]
+pub async fn get_ffmpeg_version() -> Result<BinaryVersionInfo, String> {
+  crate::downloader::get_ffmpeg_version().await
 }
 
-/// フロントエンドが復帰時に現在のダウンロード状態を問い合わせるコマンド
+/// Denoを更新する（バージョン省略時は固定バージョンまたは最新版）
 #[tauri::command]
-pub fn get_download_statuses() -> Vec<DownloadStatusEntry> {
-  DOWNLOAD_MANAGER.lock().unwrap().values().cloned().collect()
+pub async fn update_deno(version: Option<String>) -> Result<String, String> {
+  crate::downloader::update_deno(version).await
 }
 
-/// Cookie関連のエラーかどうかを判定する
-fn is_cookie_error(error: &str) -> bool {
-  error.to_lowercase().contains("cookie")
+#[tauri::command]
+pub async fn get_deno_version() -> Result<BinaryVersionInfo, String> {
+  crate::downloader::get_deno_version().await
 }
 
-/// エラー文字列をCookieエラー優先で適切なエラーキーに変換する
-fn map_yt_dlp_error(error: &str, default_key: &str) -> String {
-  if is_cookie_error(error) {
-    "error.cookie_failed".to_string()
-  } else {
-    format!("{default_key}:{error}")
-  }
+/// 全バイナリのバージョン・パス・サイズ・インストール日時を返す
+#[tauri::command]
+pub async fn get_binaries_status() -> Result<Vec<BinaryStatus>, String> {
+  crate::downloader::get_binaries_status()
 }
 
-/// URLからタイムスタンプパラメータ(t=XX)を安全に削除する関数
-fn clean_timestamp_param(url: &str) -> String {
-  if !url.contains("t=") {
-    return url.to_string();
-  }
-
-  let cleaned = RE_AMP_TIMESTAMP.replace_all(url, "").to_string();
-  let cleaned = RE_FIRST_TIMESTAMP.replace_all(&cleaned, "?").to_string();
-  let cleaned = RE_ONLY_TIMESTAMP.replace_all(&cleaned, "").to_string();
-
-  if cleaned != url {
-    log::info!("タイムスタンプを削除したURL: {cleaned}");
-  }
-
-  cleaned
-}
-
-/// ダウンロード履歴エントリを構築するヘルパー
-#[allow(clippy::too_many_arguments)]
-fn build_history_entry(
-  url: &str,
-  title: &str,
-  extension: &str,
-  best_quality: bool,
-  status: HistoryStatus,
-  file_size: Option<u64>,
-  error_message: Option<String>,
-  thumbnail: Option<String>,
-  file_path: Option<String>,
-) -> HistoryEntry {
-  let format_label = match status {
-    HistoryStatus::Success if best_quality => {
-      format!("{} best", extension.to_uppercase())
-    }
-    _ => extension.to_uppercase(),
-  };
-
-  HistoryEntry {
-    id: Uuid::new_v4().to_string(),
-    url: url.to_string(),
-    title: title.to_string(),
-    thumbnail,
-    file_path,
-    format: format_label,
-    size: file_size,
-    status,
-    error_message,
-    timestamp: Utc::now(),
-  }
+/// ローカルのアーカイブからバイナリをインストールする（github.com に届かない環境向け）
+#[tauri::command]
+pub async fn install_binaries_from_archive(path: String) -> Result<Vec<String>, String> {
+  tokio::task::spawn_blocking(move || {
+    crate::downloader::install_binaries_from_archive(Path::new(&path))
+  })
+  .await
+  .map_err(|e| format!("error.archive_extract_failed:{e}"))?
 }
 
-// ─── yt-dlp コマンド ──────────────────────────────
+/// 全バイナリのセットアップが成功しているかを返す
+#[tauri::command]
+pub fn is_setup_complete() -> bool {
+  crate::downloader::is_setup_done()
+}
 
+/// セットアップの各ステップ（yt-dlp / ffmpeg / deno）の状態を返す
 #[tauri::command]
-pub async fn update_yt_dlp() -> Result<String, String> {
-  crate::downloader::update_yt_dlp().await
+pub fn get_setup_status() -> HashMap<String, SetupStepState> {
+  crate::downloader::get_setup_status()
 }
 
+/// 失敗したセットアップステップを再実行する
 #[tauri::command]
-pub async fn get_yt_dlp_version() -> Result<String, String> {
-  crate::downloader::get_yt_dlp_version().await
+pub async fn retry_setup(app_handle: tauri::AppHandle, step: String) -> Result<(), String> {
+  crate::downloader::retry_setup(app_handle, &step).await
 }
 
-/// バイナリのセットアップが完了しているかを返す
+/// 全バイナリを再ダウンロードして修復する
 #[tauri::command]
-pub fn is_setup_complete() -> bool {
-  crate::downloader::is_setup_done()
+pub async fn repair_binaries(app_handle: tauri::AppHandle) -> Result<(), String> {
+  crate::downloader::repair_binaries(app_handle).await
 }
 
 // ─── 初期化コマンド ────────────────────────────────
@@ -787,8 +223,57 @@ pub fn get_settings() -> Result<AppSettings, String> {
 }
 
 #[tauri::command]
-pub fn save_settings(new_settings: AppSettings) -> Result<(), String> {
-  settings::save_settings(&new_settings)
+pub fn save_settings(app_handle: tauri::AppHandle, mut new_settings: AppSettings) -> Result<(), String> {
+  if new_settings.api.enabled && new_settings.api.token.is_empty() {
+    new_settings.api.token = api_server::generate_token();
+  }
+  settings::save_settings(&new_settings)?;
+  crate::downloader::clear_path_cache();
+  logging::apply_level(new_settings.log_level);
+  api_server::apply(&app_handle, &new_settings.api);
+  clipboard_watcher::apply(&app_handle, &new_settings.clipboard);
+  watch_folder::apply(&app_handle, &new_settings.watch_folder);
+  Ok(())
+}
+
+/// ローカル API の起動状態を取得する
+#[tauri::command]
+pub fn get_api_status() -> ApiStatus {
+  api_server::status()
+}
+
+/// ローカル API のトークンを再生成する（起動中のサーバーは新しいトークンで再起動する）
+#[tauri::command]
+pub fn regenerate_api_token(app_handle: tauri::AppHandle) -> Result<String, String> {
+  let mut app_settings = settings::load_settings()?;
+  app_settings.api.token = api_server::generate_token();
+  settings::save_settings(&app_settings)?;
+  api_server::apply(&app_handle, &app_settings.api);
+  Ok(app_settings.api.token)
+}
+
+/// ブラウザ拡張機能用のネイティブメッセージングホストを登録する
+#[tauri::command]
+pub fn install_native_host(browser: NativeHostBrowser, extension_id: String) -> Result<String, String> {
+  native_messaging::install_host(browser, &extension_id).map(|path| path.to_string_lossy().into_owned())
+}
+
+/// ネイティブメッセージングホストの登録を解除する
+#[tauri::command]
+pub fn uninstall_native_host(browser: NativeHostBrowser) -> Result<(), String> {
+  native_messaging::uninstall_host(browser)
+}
+
+/// ネイティブメッセージングホストの登録状況を取得する
+#[tauri::command]
+pub fn get_native_host_status() -> Vec<NativeHostStatus> {
+  native_messaging::host_status()
+}
+
+/// 実行中のダウンロードを中断する
+#[tauri::command]
+pub fn cancel_download(download_id: String) -> bool {
+  download::cancel_running_download(&download_id)
 }
 
 #[derive(Serialize)]
@@ -799,9 +284,14 @@ pub struct ResetSettingsResult {
 }
 
 #[tauri::command]
-pub fn reset_settings() -> Result<ResetSettingsResult, String> {
+pub fn reset_settings(app_handle: tauri::AppHandle) -> Result<ResetSettingsResult, String> {
   let defaults = AppSettings { initialized: true, ..AppSettings::default() };
   settings::save_settings(&defaults)?;
+  crate::downloader::clear_path_cache();
+  logging::apply_level(defaults.log_level);
+  api_server::apply(&app_handle, &defaults.api);
+  clipboard_watcher::apply(&app_handle, &defaults.clipboard);
+  watch_folder::apply(&app_handle, &defaults.watch_folder);
   let path_status = settings::validate_save_path(&defaults.save_path);
   Ok(ResetSettingsResult {
     settings: defaults,
@@ -828,150 +318,228 @@ pub fn clear_cache() -> Result<(), String> {
 }
 
 
-// ─── 履歴コマンド ─────────────────────────────────
+// ─── ネットワークコマンド ──────────────────────────
 
+/// プロキシ経由で接続できるかを確認する（保存前の設定も渡せる）
 #[tauri::command]
-pub fn get_history() -> Result<Vec<HistoryGroup>, String> {
-  history::get_grouped_history()
+pub async fn test_proxy(
+  proxy: Option<ProxySettings>,
+  url: Option<String>,
+) -> Result<network::ProxyTestResult, String> {
+  let proxy = match proxy {
+    Some(proxy) => proxy,
+    None => settings::load_settings().unwrap_or_default().proxy,
+  };
+  network::test_proxy(&proxy, url.as_deref()).await
 }
 
+// ─── Cookie コマンド ───────────────────────────────
+
+/// cookies.txt を取り込む（domain 省略時は Cookie のドメインごとに分割）
 #[tauri::command]
-pub fn get_download_stats() -> Result<history::DownloadStats, String> {
-  history::get_stats()
+pub fn import_cookies(path: String, domain: Option<String>) -> Result<Vec<CookieSetInfo>, String> {
+  cookies::import_cookies(Path::new(&path), domain.as_deref())
 }
 
 #[tauri::command]
-pub fn clear_history() -> Result<(), String> {
-  history::clear_all()
+pub fn list_cookie_sets() -> Result<Vec<CookieSetInfo>, String> {
+  cookies::list_cookie_sets()
 }
 
-// ─── ファイル管理コマンド ──────────────────────────
+#[tauri::command]
+pub fn replace_cookie_set(domain: String, path: String) -> Result<CookieSetInfo, String> {
+  cookies::replace_cookie_set(&domain, Path::new(&path))
+}
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DownloadedFile {
-  pub id: String,
-  pub title: String,
-  pub thumbnail: Option<String>,
-  pub filename: String,
-  pub path: String,
-  pub format: String,
-  pub size: u64,
-  pub category: String,
-  pub modified_at: String,
+#[tauri::command]
+pub fn delete_cookie_set(domain: String) -> Result<(), String> {
+  cookies::delete_cookie_set(&domain)
 }
 
-/// 保存ディレクトリ内のダウンロード済みファイル一覧を取得する
+// ─── プリセットコマンド ───────────────────────────────
+
 #[tauri::command]
-pub fn list_downloaded_files() -> Result<Vec<DownloadedFile>, String> {
-  let app_settings = settings::load_settings().unwrap_or_default();
-  let save_path = &app_settings.save_path;
+pub fn list_presets() -> Result<Vec<DownloadPreset>, String> {
+  presets::list_presets()
+}
 
-  if save_path.is_empty() {
-    return Ok(Vec::new());
-  }
+/// プリセットを保存し、保存後の一覧を返す（previous_name を指定すると名前を変更する）
+#[tauri::command]
+pub fn save_preset(
+  preset: DownloadPreset,
+  previous_name: Option<String>,
+) -> Result<Vec<DownloadPreset>, String> {
+  presets::save_preset(preset, previous_name.as_deref())
+}
 
-  let base = Path::new(save_path);
-  if !base.is_dir() {
-    return Ok(Vec::new());
-  }
+#[tauri::command]
+pub fn delete_preset(name: String) -> Result<Vec<DownloadPreset>, String> {
+  presets::delete_preset(&name)
+}
 
-  // 履歴からファイルパス→サムネイルURLのマップを構築
-  let thumbnail_map: HashMap<String, String> = history::load_all_entries()
-    .unwrap_or_default()
-    .into_iter()
-    .filter_map(|e| match (e.file_path, e.thumbnail) {
-      (Some(path), Some(thumb)) => Some((path, thumb)),
-      _ => None,
-    })
-    .collect();
-
-  let mut files: Vec<DownloadedFile> = Vec::new();
-
-  // videos/ と audio/ の両方をスキャン
-  let scan_targets = [
-    (base.join(settings::SUBDIR_VIDEOS), "video", VIDEO_EXTENSIONS),
-    (base.join(settings::SUBDIR_AUDIO), "audio", AUDIO_EXTENSIONS),
-  ];
-
-  for (dir, category, extensions) in &scan_targets {
-    if !dir.is_dir() {
-      continue;
-    }
+/// プリセットを JSON に書き出し、書き出した件数を返す（names 省略時はすべて）
+#[tauri::command]
+pub fn export_presets(path: String, names: Option<Vec<String>>) -> Result<usize, String> {
+  presets::export_presets(Path::new(&path), names.as_deref())
+}
 
-    let entries = std::fs::read_dir(dir)
-      .map_err(|e| format!("error.dir_read_failed:{e}"))?;
+/// JSON のプリセットを取り込み、取り込んだプリセットを返す
+#[tauri::command]
+pub fn import_presets(path: String) -> Result<Vec<DownloadPreset>, String> {
+  presets::import_presets(Path::new(&path))
+}
 
-    for entry in entries.flatten() {
-      let path = entry.path();
+// ─── URL 一覧の取り込みコマンド ─────────────────────────
 
-      // メタデータ取得（entry.metadata() で1回のシステムコールに統一）
-      let metadata = match entry.metadata() {
-        Ok(m) => m,
-        Err(_) => continue,
-      };
+/// テキスト・ブックマーク HTML・CSV から URL を読み込み、プレビューを返す
+#[tauri::command]
+pub fn import_urls(path: String) -> Result<UrlImportPreview, String> {
+  url_import::import_urls(Path::new(&path))
+}
 
-      // ディレクトリはスキップ
-      if !metadata.is_file() {
-        continue;
-      }
+/// プレビューの URL を共通のオプションでキューに追加する
+#[tauri::command]
+pub fn enqueue_imported_urls(
+  app_handle: tauri::AppHandle,
+  entries: Vec<ImportedUrl>,
+  options: DownloadRequest,
+) -> Result<Vec<String>, String> {
+  url_import::enqueue_urls(&app_handle, entries, options)
+}
 
-      // 隠しファイルはスキップ
-      let filename = match path.file_name().and_then(|n| n.to_str()) {
-        Some(name) if !name.starts_with('.') => name.to_string(),
-        _ => continue,
-      };
-
-      // 拡張子でフィルタ
-      let ext_lower = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .unwrap_or_default();
-
-      if !extensions.contains(&ext_lower.as_str()) {
-        continue;
-      }
+// ─── 認証情報コマンド ───────────────────────────────
+
+#[tauri::command]
+pub fn list_credentials() -> Result<Vec<CredentialInfo>, String> {
+  credentials::list_credentials()
+}
+
+/// 認証情報を保存する（パスワードは OS キーリング、使えない場合は暗号化ファイルに保存）
+#[tauri::command]
+pub fn save_credential(credential: CredentialInput) -> Result<CredentialInfo, String> {
+  credentials::save_credential(credential)
+}
 
-      let size = metadata.len();
-      let modified_at = metadata
-        .modified()
-        .ok()
-        .map(|t| {
-          let datetime: DateTime<Utc> = t.into();
-          datetime.to_rfc3339()
-        })
-        .unwrap_or_default();
-
-      let title = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or(&filename)
-        .to_string();
-
-      let format = ext_lower.to_uppercase();
-      let full_path = path.to_string_lossy().to_string();
-
-      let thumbnail = thumbnail_map.get(&full_path).cloned();
-
-      files.push(DownloadedFile {
-        id: full_path.clone(),
-        title,
-        thumbnail,
-        filename,
-        path: full_path,
-        format,
-        size,
-        category: (*category).to_string(),
-        modified_at,
-      });
+#[tauri::command]
+pub fn delete_credential(extractor: String) -> Result<(), String> {
+  credentials::delete_credential(&extractor)
+}
+
+// ─── ログコマンド ─────────────────────────────────
+
+/// アプリログの末尾を取得する（filter は大文字小文字を区別しない部分一致）
+#[tauri::command]
+pub fn get_logs(filter: Option<String>, limit: Option<usize>) -> Result<Vec<String>, String> {
+  logging::read_logs(filter.as_deref(), limit)
+}
+
+/// ダウンロードIDに対応する yt-dlp の出力ログを取得する
+#[tauri::command]
+pub fn get_download_log(download_id: String) -> Result<String, String> {
+  logging::read_download_log(&download_id)
+}
+
+/// ログフォルダをファイルマネージャで開く
+#[tauri::command]
+pub fn open_log_folder() -> Result<(), String> {
+  let dir = logging::log_dir()?;
+  open_file(dir.to_string_lossy().into_owned())
+}
+
+/// 不具合報告用の診断情報を zip に書き出し、作成したファイルのパスを返す
+///
+/// dest にディレクトリを指定した場合はその中にファイル名を決めて作成する。
+#[tauri::command]
+pub async fn export_diagnostics(dest: String) -> Result<String, String> {
+  tokio::task::spawn_blocking(move || crate::diagnostics::export_diagnostics(Path::new(&dest)))
+    .await
+    .map_err(|e| format!("error.diagnostics_failed:{e}"))?
+    .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// バイナリ・保存先・設定などの環境をチェックし、項目ごとの結果を返す
+#[tauri::command]
+pub async fn run_doctor() -> Result<crate::doctor::DoctorReport, String> {
+  tokio::task::spawn_blocking(crate::doctor::run_doctor)
+    .await
+    .map_err(|e| format!("error.doctor_failed:{e}"))
+}
+
+// ─── 履歴コマンド ─────────────────────────────────
+
+#[tauri::command]
+pub fn get_history() -> Result<Vec<HistoryGroup>, String> {
+  history::get_grouped_history()
+}
+
+#[tauri::command]
+pub fn get_download_stats() -> Result<history::DownloadStats, String> {
+  history::get_stats()
+}
+
+#[tauri::command]
+pub fn clear_history() -> Result<(), String> {
+  history::clear_all()
+}
+
+/// 履歴エントリから再ダウンロードの要求を作る
+///
+/// 保存済みの要求がない古い履歴は、URL とフォーマット表記（"MP4 best" など）から復元する。
+fn retry_request(entry: &HistoryEntry) -> DownloadRequest {
+  let mut request = entry.request.clone().unwrap_or_else(|| {
+    let mut parts = entry.format.split_whitespace();
+    let extension = parts.next().unwrap_or_default().to_lowercase();
+    let audio_only = SUPPORTED_AUDIO_FORMATS.contains(&extension.as_str());
+    let preferred_format = (audio_only || SUPPORTED_VIDEO_FORMATS.contains(&extension.as_str()))
+      .then_some(extension);
+    DownloadRequest {
+      url: entry.url.clone(),
+      options: DownloadOptionsOverride {
+        audio_only: Some(audio_only),
+        preferred_format,
+        ..DownloadOptionsOverride::default()
+      },
+      metadata_title: Some(entry.title.clone()),
+      thumbnail: entry.thumbnail.clone(),
+      ..DownloadRequest::default()
+    }
+  });
+  request.download_id = String::new();
+  request.retry_of = Some(entry.id.clone());
+  request
+}
+
+/// 履歴エントリと同じオプションでダウンロードをキューに追加し、新しいダウンロードIDを返す
+#[tauri::command]
+pub fn retry_history_entry(app_handle: tauri::AppHandle, id: String) -> Result<String, String> {
+  let entry = history::find_entry(&id)?;
+  enqueue_download(&app_handle, retry_request(&entry))
+}
+
+/// 条件に合う失敗履歴をまとめて再試行し、追加したダウンロードIDを返す
+#[tauri::command]
+pub fn retry_all_failed(
+  app_handle: tauri::AppHandle,
+  filter: Option<history::RetryFilter>,
+) -> Result<Vec<String>, String> {
+  let entries = history::failed_entries(&filter.unwrap_or_default())?;
+  let mut ids = Vec::with_capacity(entries.len());
+  for entry in &entries {
+    match enqueue_download(&app_handle, retry_request(entry)) {
+      Ok(id) => ids.push(id),
+      Err(e) => log::warn!("履歴を再試行できません ({}): {e}", entry.id),
     }
   }
+  log::info!("失敗した履歴を {}/{} 件再試行しました", ids.len(), entries.len());
+  Ok(ids)
+}
 
-  // 更新日時の降順でソート（新しいものが先頭）
-  files.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+// ─── ファイル管理コマンド ──────────────────────────
 
-  Ok(files)
+/// 保存ディレクトリ内のダウンロード済みファイル一覧を取得する
+#[tauri::command]
+pub fn list_downloaded_files() -> Result<Vec<DownloadedFile>, String> {
+  download::list_downloaded_files()
 }
 
 /// 指定されたファイルを削除する
@@ -1077,47 +645,3 @@ pub fn open_file(path: String) -> Result<(), String> {
 
   Ok(())
 }
-
-/// タイトルとサムネイルを取得する補助関数
-async fn get_video_info(url: &str, yt_dlp_path: &str) -> Result<(String, Option<String>), String> {
-  let mut meta_instance = YoutubeDl::new(url.to_string());
-  meta_instance.youtube_dl_path(yt_dlp_path);
-
-  meta_instance
-    .socket_timeout("15")
-    .flat_playlist(true)
-    .extra_arg("--no-check-certificate")
-    .extra_arg("--force-ipv4");
-
-  if let Ok(s) = settings::load_settings() {
-    if let Some(browser) = &s.cookies_browser {
-      meta_instance.extra_arg("--cookies-from-browser").extra_arg(browser);
-    }
-  }
-
-  let metadata_result = meta_instance.run_async().await;
-
-  match metadata_result {
-    Ok(metadata) => {
-      let thumbnail = extract_thumbnail(&metadata);
-
-      if let Some(playlist) = metadata.clone().into_playlist() {
-        Ok((
-          sanitize_filename(&playlist.title.unwrap_or_else(|| "No Title".to_string())),
-          thumbnail,
-        ))
-      } else if let Some(video) = metadata.into_single_video() {
-        Ok((
-          sanitize_filename(&video.title.unwrap_or_else(|| "No Title".to_string())),
-          thumbnail,
-        ))
-      } else {
-        Err("error.get_title_failed".to_string())
-      }
-    }
-    Err(e) => {
-      log::error!("メタデータ取得エラー: {e:?}");
-      Err(map_yt_dlp_error(&e.to_string(), "error.video_info_failed"))
-    }
-  }
-}
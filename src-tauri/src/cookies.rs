@@ -0,0 +1,338 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use crate::utils::{ensure_app_data_dir, restrict_permissions};
+
+/// 保存する cookies.txt の先頭行（yt-dlp はこのヘッダーで Netscape 形式と判定する）
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+/// HttpOnly の Cookie はこの接頭辞付きで書き出される
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// 保存済み Cookie セットの情報
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookieSetInfo {
+  pub domain: String,
+  pub cookie_count: usize,
+  /// 最も早く期限切れになる Cookie の有効期限（セッション Cookie のみの場合は None）
+  pub expires_at: Option<DateTime<Utc>>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// ブラウザ拡張機能から受け取る Cookie（chrome.cookies / browser.cookies の形式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserCookie {
+  pub name: String,
+  pub value: String,
+  pub domain: String,
+  #[serde(default = "default_cookie_path")]
+  pub path: String,
+  #[serde(default)]
+  pub secure: bool,
+  #[serde(default)]
+  pub http_only: bool,
+  /// ホスト限定の Cookie（サブドメインには送られない）
+  #[serde(default)]
+  pub host_only: bool,
+  /// 有効期限（UNIX 秒。セッション Cookie は None）
+  pub expiration_date: Option<f64>,
+}
+
+fn default_cookie_path() -> String {
+  "/".to_string()
+}
+
+/// Netscape 形式の Cookie 1行
+struct CookieLine<'a> {
+  /// 先頭の "." を除いた小文字のドメイン
+  domain: String,
+  expires: i64,
+  raw: &'a str,
+}
+
+// ─── 保存先 ───────────────────────────────────────
+
+/// Cookie ストアのディレクトリ（所有者のみアクセス可能）
+fn cookie_store_dir() -> Result<PathBuf, String> {
+  let dir = ensure_app_data_dir()?.join("cookies");
+  fs::create_dir_all(&dir).map_err(|e| format!("error.dir_create_failed:{e}"))?;
+  restrict_permissions(&dir, 0o700)?;
+  Ok(dir)
+}
+
+/// ドメインを正規化する（小文字化・前後の "." を除去）
+///
+/// "www." などのサブドメインは残し、指定されたホストのセットとして保存する。
+/// 社内サイトの "wiki" や "localhost" のような1ラベルのホストも受け付ける。
+fn normalize_domain(domain: &str) -> Result<String, String> {
+  let domain = domain.trim().trim_matches('.').to_lowercase();
+
+  let valid = !domain.is_empty()
+    && domain
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+  if !valid {
+    return Err(format!("error.cookies_invalid_domain:{domain}"));
+  }
+  Ok(domain)
+}
+
+fn cookie_file_path(dir: &Path, domain: &str) -> PathBuf {
+  dir.join(format!("{domain}.txt"))
+}
+
+// ─── 解析 ─────────────────────────────────────────
+
+/// Netscape 形式の cookies.txt を解析する
+///
+/// 7 列のタブ区切りでない行は無視し、有効な行が1つもなければエラーとする。
+fn parse_cookies(content: &str) -> Result<Vec<CookieLine<'_>>, String> {
+  let cookies: Vec<CookieLine> = content
+    .lines()
+    .filter_map(|raw| {
+      let line = raw.strip_prefix(HTTP_ONLY_PREFIX).unwrap_or(raw);
+      if line.starts_with('#') || line.trim().is_empty() {
+        return None;
+      }
+
+      let fields: Vec<&str> = line.split('\t').collect();
+      if fields.len() != 7 {
+        return None;
+      }
+
+      Some(CookieLine {
+        domain: fields[0].trim_start_matches('.').to_lowercase(),
+        expires: fields[4].trim().parse().unwrap_or(0),
+        raw,
+      })
+    })
+    .collect();
+
+  if cookies.is_empty() {
+    return Err("error.cookies_invalid_file".to_string());
+  }
+  Ok(cookies)
+}
+
+fn render_cookies(cookies: &[&CookieLine]) -> String {
+  let mut content = format!("{NETSCAPE_HEADER}\n");
+  for cookie in cookies {
+    content.push_str(cookie.raw);
+    content.push('\n');
+  }
+  content
+}
+
+/// Cookie セットを書き込む（一時ファイル経由で置き換える）
+fn write_cookie_set(dir: &Path, domain: &str, content: &str) -> Result<CookieSetInfo, String> {
+  let path = cookie_file_path(dir, domain);
+  let tmp_path = path.with_extension("txt.tmp");
+
+  fs::write(&tmp_path, content).map_err(|e| format!("error.cookies_write_failed:{e}"))?;
+  restrict_permissions(&tmp_path, 0o600)?;
+  fs::rename(&tmp_path, &path).map_err(|e| format!("error.cookies_write_failed:{e}"))?;
+
+  log::info!("Cookie を保存しました: {domain}");
+  read_cookie_set_info(&path, domain)
+}
+
+fn read_cookie_set_info(path: &Path, domain: &str) -> Result<CookieSetInfo, String> {
+  let content =
+    fs::read_to_string(path).map_err(|e| format!("error.cookies_read_failed:{e}"))?;
+  let cookies = parse_cookies(&content).unwrap_or_default();
+
+  let expires_at = cookies
+    .iter()
+    .map(|c| c.expires)
+    .filter(|&expires| expires > 0)
+    .min()
+    .and_then(|expires| DateTime::from_timestamp(expires, 0));
+  let updated_at = fs::metadata(path)
+    .and_then(|m| m.modified())
+    .ok()
+    .map(DateTime::<Utc>::from);
+
+  Ok(CookieSetInfo {
+    domain: domain.to_string(),
+    cookie_count: cookies.len(),
+    expires_at,
+    updated_at,
+  })
+}
+
+// ─── 公開 API ─────────────────────────────────────
+
+/// cookies.txt をストアに取り込む
+///
+/// `domain` を指定した場合はファイル全体をそのドメインの Cookie セットとして保存する。
+/// 省略した場合は Cookie のドメインごとに分割して保存する。
+/// いずれも同じドメインの既存セットは置き換えられる。
+pub fn import_cookies(source: &Path, domain: Option<&str>) -> Result<Vec<CookieSetInfo>, String> {
+  let content =
+    fs::read_to_string(source).map_err(|e| format!("error.cookies_read_failed:{e}"))?;
+  let cookies = parse_cookies(&content)?;
+  let dir = cookie_store_dir()?;
+
+  if let Some(domain) = domain {
+    let domain = normalize_domain(domain)?;
+    let all: Vec<&CookieLine> = cookies.iter().collect();
+    return Ok(vec![write_cookie_set(&dir, &domain, &render_cookies(&all))?]);
+  }
+
+  let mut by_domain: BTreeMap<String, Vec<&CookieLine>> = BTreeMap::new();
+  for cookie in &cookies {
+    match normalize_domain(&cookie.domain) {
+      Ok(domain) => by_domain.entry(domain).or_default().push(cookie),
+      Err(_) => log::warn!("ドメインが不正な Cookie を無視しました: {}", cookie.domain),
+    }
+  }
+
+  by_domain
+    .iter()
+    .map(|(domain, cookies)| write_cookie_set(&dir, domain, &render_cookies(cookies)))
+    .collect()
+}
+
+/// 保存済みの Cookie セットを一覧する
+pub fn list_cookie_sets() -> Result<Vec<CookieSetInfo>, String> {
+  let dir = cookie_store_dir()?;
+  let entries = fs::read_dir(&dir).map_err(|e| format!("error.cookies_read_failed:{e}"))?;
+
+  let mut sets: Vec<CookieSetInfo> = entries
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+    .filter_map(|path| {
+      let domain = path.file_stem()?.to_string_lossy().into_owned();
+      read_cookie_set_info(&path, &domain).ok()
+    })
+    .collect();
+
+  sets.sort_by(|a, b| a.domain.cmp(&b.domain));
+  Ok(sets)
+}
+
+/// 指定ドメインの Cookie セットを cookies.txt の内容で置き換える
+pub fn replace_cookie_set(domain: &str, source: &Path) -> Result<CookieSetInfo, String> {
+  import_cookies(source, Some(domain))?
+    .into_iter()
+    .next()
+    .ok_or_else(|| "error.cookies_invalid_file".to_string())
+}
+
+/// ブラウザ拡張機能から受け取った Cookie を URL のホストの Cookie セットとして保存する
+///
+/// 同じホストの既存セットは置き換えられる。
+pub fn store_browser_cookies(url: &str, cookies: &[BrowserCookie]) -> Result<CookieSetInfo, String> {
+  let host = Url::parse(url)
+    .ok()
+    .and_then(|u| u.host_str().map(str::to_string))
+    .ok_or_else(|| "error.invalid_url".to_string())?;
+  let domain = normalize_domain(&host)?;
+
+  let mut content = format!("{NETSCAPE_HEADER}\n");
+  for cookie in cookies {
+    let fields = [&cookie.name, &cookie.value, &cookie.domain, &cookie.path];
+    if cookie.name.is_empty()
+      || fields.iter().any(|f| f.contains(['\t', '\r', '\n']))
+    {
+      log::warn!("保存できない Cookie を無視しました: {}", cookie.domain);
+      continue;
+    }
+
+    let include_subdomains = !cookie.host_only && cookie.domain.starts_with('.');
+    #[allow(clippy::cast_possible_truncation)]
+    let expires = cookie.expiration_date.map_or(0, |e| e as i64);
+    let bool_field = |value: bool| if value { "TRUE" } else { "FALSE" };
+
+    content.push_str(&format!(
+      "{}{}\t{}\t{}\t{}\t{expires}\t{}\t{}\n",
+      if cookie.http_only { HTTP_ONLY_PREFIX } else { "" },
+      cookie.domain,
+      bool_field(include_subdomains),
+      cookie.path,
+      bool_field(cookie.secure),
+      cookie.name,
+      cookie.value,
+    ));
+  }
+
+  parse_cookies(&content)?;
+  write_cookie_set(&cookie_store_dir()?, &domain, &content)
+}
+
+/// 指定ドメインの Cookie セットを削除する
+pub fn delete_cookie_set(domain: &str) -> Result<(), String> {
+  let domain = normalize_domain(domain)?;
+  let path = cookie_file_path(&cookie_store_dir()?, &domain);
+
+  if !path.exists() {
+    return Err(format!("error.cookies_not_found:{domain}"));
+  }
+  fs::remove_file(&path).map_err(|e| format!("error.cookies_delete_failed:{e}"))?;
+
+  log::info!("Cookie を削除しました: {domain}");
+  Ok(())
+}
+
+/// ホスト自身から親ドメインへ順に並べた検索候補（トップレベルドメインは含めない）
+fn domain_candidates(host: &str) -> Vec<&str> {
+  let mut candidates = vec![host];
+  let mut current = host;
+  while let Some((_, parent)) = current.split_once('.') {
+    if !parent.contains('.') {
+      break;
+    }
+    candidates.push(parent);
+    current = parent;
+  }
+  candidates
+}
+
+/// URL のホストに対応する Cookie ファイルを探す
+///
+/// ホスト自身から親ドメインへ順に辿り、最も具体的に一致したセットを返す。
+pub fn cookie_file_for_url(url: &str) -> Option<PathBuf> {
+  let host = normalize_domain(Url::parse(url).ok()?.host_str()?).ok()?;
+  let dir = cookie_store_dir().ok()?;
+
+  domain_candidates(&host)
+    .into_iter()
+    .map(|candidate| cookie_file_path(&dir, candidate))
+    .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn normalize_domain_keeps_host_as_given() {
+    assert_eq!(normalize_domain(" .WWW.Example.com ").unwrap(), "www.example.com");
+    assert_eq!(normalize_domain("example.com.").unwrap(), "example.com");
+  }
+
+  #[test]
+  fn normalize_domain_accepts_single_label_hosts() {
+    assert_eq!(normalize_domain("wiki").unwrap(), "wiki");
+    assert_eq!(normalize_domain("localhost").unwrap(), "localhost");
+    assert!(normalize_domain("").is_err());
+    assert!(normalize_domain("..").is_err());
+    assert!(normalize_domain("../etc").is_err());
+  }
+
+  #[test]
+  fn domain_candidates_walk_parents_without_tld() {
+    assert_eq!(
+      domain_candidates("a.www.example.com"),
+      ["a.www.example.com", "www.example.com", "example.com"]
+    );
+    assert_eq!(domain_candidates("example.com"), ["example.com"]);
+    assert_eq!(domain_candidates("wiki"), ["wiki"]);
+  }
+}
@@ -0,0 +1,576 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use url::Url;
+use uuid::Uuid;
+
+use crate::utils::{ensure_app_data_dir, restrict_permissions};
+
+/// OS キーリングに登録するサービス名
+const KEYRING_SERVICE: &str = "lucentia";
+/// AES-GCM のナンス長
+const NONCE_LEN: usize = 12;
+/// プロキシのパスワードを保存するキー（extractor 名には `:` を使えないため衝突しない）
+const PROXY_PASSWORD_KEY: &str = "proxy:password";
+/// yt-dlp の実行ごとに作る一時ファイルの接頭辞
+const JOB_FILE_PREFIX: &str = "job-";
+/// 異常終了などで残った一時ファイルを削除するまでの時間
+const STALE_JOB_FILE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 保存済みの認証情報（パスワードは含めない）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialEntry {
+  /// yt-dlp の extractor 名（.netrc の machine 名。"vimeo", "niconico" など）
+  pub extractor: String,
+  /// この認証情報を使うドメイン（サブドメインにも一致する）
+  ///
+  /// 空の場合はどの URL にも一致せず、.netrc の `machine <extractor>` として渡して
+  /// yt-dlp の extractor に選ばせる。
+  pub domains: Vec<String>,
+  pub username: Option<String>,
+}
+
+/// パスワードの保存先
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CredentialStorage {
+  /// OS のキーリング（Keychain / Credential Manager / Secret Service）
+  Keyring,
+  /// キーリングが使えない場合の暗号化ファイル
+  ///
+  /// 鍵を同じディレクトリに置くため、ファイルを読める相手からは保護されない（難読化のみ）。
+  LocalFile,
+}
+
+/// フロントエンドに返す認証情報の概要
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialInfo {
+  pub extractor: String,
+  pub domains: Vec<String>,
+  pub username: Option<String>,
+  pub has_password: bool,
+  pub has_video_password: bool,
+  pub storage: CredentialStorage,
+}
+
+/// 認証情報の保存リクエスト
+///
+/// パスワードは `None` なら既存の値を維持し、空文字なら削除する。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialInput {
+  pub extractor: String,
+  pub domains: Vec<String>,
+  pub username: Option<String>,
+  pub password: Option<String>,
+  pub video_password: Option<String>,
+}
+
+/// 秘密情報の種類（キーリングのアカウント名に使う）
+#[derive(Clone, Copy)]
+enum SecretKind {
+  Password,
+  VideoPassword,
+}
+
+fn secret_key(extractor: &str, kind: SecretKind) -> String {
+  match kind {
+    SecretKind::Password => format!("{extractor}:password"),
+    SecretKind::VideoPassword => format!("{extractor}:video_password"),
+  }
+}
+
+/// 認証情報ファイル・暗号化ファイルへの同時書き込みを防ぐ
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+// ─── 保存先 ───────────────────────────────────────
+
+/// 認証情報のディレクトリ（所有者のみアクセス可能）
+fn credentials_dir() -> Result<PathBuf, String> {
+  let dir = ensure_app_data_dir()?.join("credentials");
+  fs::create_dir_all(&dir).map_err(|e| format!("error.dir_create_failed:{e}"))?;
+  restrict_permissions(&dir, 0o700)?;
+  Ok(dir)
+}
+
+/// 権限を絞ったファイルを一時ファイル経由で書き込む
+fn write_private_file(path: &Path, content: &[u8]) -> Result<(), String> {
+  let tmp_path = path.with_extension("tmp");
+  fs::write(&tmp_path, content).map_err(|e| format!("error.credentials_write_failed:{e}"))?;
+  restrict_permissions(&tmp_path, 0o600)?;
+  fs::rename(&tmp_path, path).map_err(|e| format!("error.credentials_write_failed:{e}"))
+}
+
+fn load_entries() -> Result<Vec<CredentialEntry>, String> {
+  let path = credentials_dir()?.join("credentials.json");
+  if !path.exists() {
+    return Ok(Vec::new());
+  }
+
+  let content =
+    fs::read_to_string(&path).map_err(|e| format!("error.credentials_read_failed:{e}"))?;
+  serde_json::from_str(&content).map_err(|e| format!("error.credentials_read_failed:{e}"))
+}
+
+fn save_entries(entries: &[CredentialEntry]) -> Result<(), String> {
+  let content = serde_json::to_string_pretty(entries)
+    .map_err(|e| format!("error.credentials_write_failed:{e}"))?;
+  write_private_file(&credentials_dir()?.join("credentials.json"), content.as_bytes())
+}
+
+// ─── 秘密情報ストア ─────────────────────────────────
+
+/// このビルドに OS キーリングのバックエンドが含まれているか
+///
+/// `secret-service` フィーチャーを無効にした Linux などのビルドでは、keyring クレートが
+/// メモリ上のモックを使うため、保存した秘密情報が終了時に失われる。
+const KEYRING_COMPILED: bool = cfg!(any(
+  target_os = "macos",
+  target_os = "ios",
+  target_os = "windows",
+  feature = "secret-service"
+));
+
+/// OS キーリングが使えるか（ヘッドレスな Linux などでは Secret Service がない）
+static KEYRING_AVAILABLE: LazyLock<bool> = LazyLock::new(|| {
+  let available = KEYRING_COMPILED
+    && keyring::Entry::new(KEYRING_SERVICE, "__probe__")
+      .and_then(|entry| entry.get_password())
+      .map_or_else(|e| matches!(e, keyring::Error::NoEntry), |_| true);
+
+  if !available {
+    log::warn!("OS キーリングが利用できないため、暗号化ファイルに認証情報を保存します");
+  }
+  available
+});
+
+fn read_secret(key: &str) -> Result<Option<String>, String> {
+  if !*KEYRING_AVAILABLE {
+    return Ok(load_encrypted_secrets()?.remove(key));
+  }
+
+  let entry = keyring::Entry::new(KEYRING_SERVICE, key)
+    .map_err(|e| format!("error.keyring_failed:{e}"))?;
+  match entry.get_password() {
+    Ok(secret) => Ok(Some(secret)),
+    Err(keyring::Error::NoEntry) => Ok(None),
+    Err(e) => Err(format!("error.keyring_failed:{e}")),
+  }
+}
+
+fn write_secret(key: &str, secret: &str) -> Result<(), String> {
+  if !*KEYRING_AVAILABLE {
+    let mut secrets = load_encrypted_secrets()?;
+    secrets.insert(key.to_string(), secret.to_string());
+    return save_encrypted_secrets(&secrets);
+  }
+
+  keyring::Entry::new(KEYRING_SERVICE, key)
+    .and_then(|entry| entry.set_password(secret))
+    .map_err(|e| format!("error.keyring_failed:{e}"))
+}
+
+fn delete_secret(key: &str) -> Result<(), String> {
+  if !*KEYRING_AVAILABLE {
+    let mut secrets = load_encrypted_secrets()?;
+    if secrets.remove(key).is_some() {
+      save_encrypted_secrets(&secrets)?;
+    }
+    return Ok(());
+  }
+
+  let result = keyring::Entry::new(KEYRING_SERVICE, key).and_then(|entry| entry.delete_credential());
+  match result {
+    Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+    Err(e) => Err(format!("error.keyring_failed:{e}")),
+  }
+}
+
+// ─── 暗号化ファイル（キーリングの代替） ─────────────────
+
+/// 暗号鍵を読み込む（暗号化ファイルがまだない場合のみ生成する）
+///
+/// 鍵は暗号化ファイルと同じディレクトリに置くため、平文で保存しないための難読化に
+/// とどまる。鍵を作り直すと既存の秘密情報を復号できなくなるため、暗号化ファイルが
+/// あるのに鍵がない・壊れている場合はエラーにする。
+fn encryption_key() -> Result<Key<Aes256Gcm>, String> {
+  let dir = credentials_dir()?;
+  let path = dir.join("secrets.key");
+
+  match fs::read(&path) {
+    Ok(bytes) if bytes.len() == 32 => Ok(*Key::<Aes256Gcm>::from_slice(&bytes)),
+    Ok(_) => Err("error.credentials_key_invalid".to_string()),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+      if dir.join("secrets.enc").exists() {
+        return Err("error.credentials_key_missing".to_string());
+      }
+      let key = Aes256Gcm::generate_key(OsRng);
+      write_private_file(&path, key.as_slice())?;
+      Ok(key)
+    }
+    Err(e) => Err(format!("error.credentials_read_failed:{e}")),
+  }
+}
+
+/// 暗号化ファイルを読み込む（先頭 12 バイトがナンス、残りが暗号文）
+fn load_encrypted_secrets() -> Result<BTreeMap<String, String>, String> {
+  let path = credentials_dir()?.join("secrets.enc");
+  let Ok(data) = fs::read(&path) else {
+    return Ok(BTreeMap::new());
+  };
+  if data.len() < NONCE_LEN {
+    return Err("error.credentials_read_failed:corrupted".to_string());
+  }
+
+  let cipher = Aes256Gcm::new(&encryption_key()?);
+  let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+  let plaintext = cipher
+    .decrypt(Nonce::from_slice(nonce), ciphertext)
+    .map_err(|_| "error.credentials_read_failed:decrypt".to_string())?;
+
+  serde_json::from_slice(&plaintext).map_err(|e| format!("error.credentials_read_failed:{e}"))
+}
+
+fn save_encrypted_secrets(secrets: &BTreeMap<String, String>) -> Result<(), String> {
+  let plaintext =
+    serde_json::to_vec(secrets).map_err(|e| format!("error.credentials_write_failed:{e}"))?;
+
+  let cipher = Aes256Gcm::new(&encryption_key()?);
+  let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+  let ciphertext = cipher
+    .encrypt(&nonce, plaintext.as_slice())
+    .map_err(|_| "error.credentials_write_failed:encrypt".to_string())?;
+
+  let mut data = nonce.to_vec();
+  data.extend_from_slice(&ciphertext);
+  write_private_file(&credentials_dir()?.join("secrets.enc"), &data)
+}
+
+// ─── 公開 API ─────────────────────────────────────
+
+fn normalize_extractor(extractor: &str) -> Result<String, String> {
+  let extractor = extractor.trim().to_lowercase();
+  let valid = !extractor.is_empty()
+    && extractor
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+  if !valid {
+    return Err(format!("error.credentials_invalid_extractor:{extractor}"));
+  }
+  Ok(extractor)
+}
+
+fn to_info(entry: &CredentialEntry) -> CredentialInfo {
+  let has_secret = |kind| {
+    read_secret(&secret_key(&entry.extractor, kind))
+      .ok()
+      .flatten()
+      .is_some()
+  };
+
+  CredentialInfo {
+    extractor: entry.extractor.clone(),
+    domains: entry.domains.clone(),
+    username: entry.username.clone(),
+    has_password: has_secret(SecretKind::Password),
+    has_video_password: has_secret(SecretKind::VideoPassword),
+    storage: if *KEYRING_AVAILABLE { CredentialStorage::Keyring } else { CredentialStorage::LocalFile },
+  }
+}
+
+/// 保存済みの認証情報を一覧する
+pub fn list_credentials() -> Result<Vec<CredentialInfo>, String> {
+  let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  Ok(load_entries()?.iter().map(to_info).collect())
+}
+
+/// 認証情報を保存する（同じ extractor の既存エントリは上書き）
+pub fn save_credential(input: CredentialInput) -> Result<CredentialInfo, String> {
+  let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let extractor = normalize_extractor(&input.extractor)?;
+
+  for (secret, kind) in [
+    (&input.password, SecretKind::Password),
+    (&input.video_password, SecretKind::VideoPassword),
+  ] {
+    match secret.as_deref() {
+      Some("") => delete_secret(&secret_key(&extractor, kind))?,
+      Some(value) => write_secret(&secret_key(&extractor, kind), value)?,
+      None => {}
+    }
+  }
+
+  let entry = CredentialEntry {
+    extractor: extractor.clone(),
+    domains: input
+      .domains
+      .iter()
+      .map(|d| d.trim().trim_start_matches('.').to_lowercase())
+      .filter(|d| !d.is_empty())
+      .collect(),
+    username: input.username.filter(|u| !u.trim().is_empty()),
+  };
+
+  let mut entries = load_entries()?;
+  entries.retain(|e| e.extractor != extractor);
+  entries.push(entry.clone());
+  entries.sort_by(|a, b| a.extractor.cmp(&b.extractor));
+  save_entries(&entries)?;
+
+  log::info!("認証情報を保存しました: {extractor}");
+  Ok(to_info(&entry))
+}
+
+/// 認証情報を削除する（キーリング上のパスワードも削除）
+pub fn delete_credential(extractor: &str) -> Result<(), String> {
+  let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let extractor = normalize_extractor(extractor)?;
+
+  let mut entries = load_entries()?;
+  let before = entries.len();
+  entries.retain(|e| e.extractor != extractor);
+  if entries.len() == before {
+    return Err(format!("error.credentials_not_found:{extractor}"));
+  }
+
+  delete_secret(&secret_key(&extractor, SecretKind::Password))?;
+  delete_secret(&secret_key(&extractor, SecretKind::VideoPassword))?;
+  save_entries(&entries)?;
+
+  log::info!("認証情報を削除しました: {extractor}");
+  Ok(())
+}
+
+/// 保存済みのプロキシのパスワード
+pub fn proxy_password() -> Result<Option<String>, String> {
+  let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  read_secret(PROXY_PASSWORD_KEY)
+}
+
+/// プロキシのパスワードを保存する（空文字なら削除する）
+pub fn set_proxy_password(password: &str) -> Result<(), String> {
+  let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  if password.is_empty() {
+    delete_secret(PROXY_PASSWORD_KEY)
+  } else {
+    write_secret(PROXY_PASSWORD_KEY, password)
+  }
+}
+
+// ─── yt-dlp 連携 ──────────────────────────────────
+
+/// .netrc のトークンをクォートする（空白や引用符を含む場合のみ）
+fn netrc_token(value: &str) -> String {
+  if value.chars().any(|c| c.is_whitespace() || c == '"' || c == '\\') {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+  } else {
+    value.to_string()
+  }
+}
+
+/// yt-dlp の設定ファイル用に値をクォートする（シェルと同じ単一引用符）
+fn config_token(value: &str) -> String {
+  format!("'{}'", value.replace('\'', r#"'"'"'"#))
+}
+
+/// 1回の yt-dlp の実行に渡す認証情報のファイル
+///
+/// 所有者のみ読み書きできる一時ファイルとして作り、ドロップ時（プロセスの終了後）に削除する。
+#[derive(Default)]
+pub struct CredentialFiles {
+  paths: Vec<PathBuf>,
+}
+
+impl CredentialFiles {
+  /// 一時ファイルを作成して内容を書き込み、パスを返す
+  fn create(&mut self, extension: &str, content: &str) -> Result<PathBuf, String> {
+    let path = credentials_dir()?.join(format!("{JOB_FILE_PREFIX}{}.{extension}", Uuid::new_v4()));
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::OpenOptionsExt;
+      options.mode(0o600);
+    }
+    let mut file = options
+      .open(&path)
+      .map_err(|e| format!("error.credentials_write_failed:{e}"))?;
+    self.paths.push(path.clone());
+    file
+      .write_all(content.as_bytes())
+      .map_err(|e| format!("error.credentials_write_failed:{e}"))?;
+    Ok(path)
+  }
+}
+
+impl Drop for CredentialFiles {
+  fn drop(&mut self) {
+    for path in &self.paths {
+      if let Err(e) = fs::remove_file(path) {
+        log::warn!("認証情報の一時ファイルを削除できません ({}): {e}", path.display());
+      }
+    }
+  }
+}
+
+/// 以前のバージョンが残した .netrc と、異常終了で残った一時ファイルを削除する
+fn remove_stale_files() -> Result<(), String> {
+  let dir = credentials_dir()?;
+  let _ = fs::remove_file(dir.join("netrc"));
+
+  let Ok(entries) = fs::read_dir(&dir) else {
+    return Ok(());
+  };
+  for entry in entries.flatten() {
+    let is_job_file = entry.file_name().to_string_lossy().starts_with(JOB_FILE_PREFIX);
+    let is_stale = entry
+      .metadata()
+      .and_then(|meta| meta.modified())
+      .ok()
+      .and_then(|modified| modified.elapsed().ok())
+      .is_some_and(|age| age > STALE_JOB_FILE_AGE);
+    if is_job_file && is_stale {
+      let _ = fs::remove_file(entry.path());
+    }
+  }
+  Ok(())
+}
+
+/// URL のホストに一致する認証情報を探す
+fn entry_for_url<'a>(entries: &'a [CredentialEntry], url: &str) -> Option<&'a CredentialEntry> {
+  let host = Url::parse(url).ok()?.host_str()?.to_lowercase();
+  entries.iter().find(|entry| {
+    entry
+      .domains
+      .iter()
+      .any(|domain| host == *domain || host.ends_with(&format!(".{domain}")))
+  })
+}
+
+/// .netrc に書くログイン情報を選ぶ
+///
+/// ドメインが一致するものがあればそれだけを使う。なければドメインを指定していない
+/// エントリをすべて `machine <extractor>` として書き、yt-dlp の extractor に選ばせる。
+fn netrc_entries<'a>(entries: &'a [CredentialEntry], url: &str) -> Vec<&'a CredentialEntry> {
+  match entry_for_url(entries, url) {
+    Some(entry) => vec![entry],
+    None => entries.iter().filter(|entry| entry.domains.is_empty()).collect(),
+  }
+}
+
+/// yt-dlp に渡す認証関連の引数と、その実行中だけ残す一時ファイル
+///
+/// ログイン情報は URL に一致したもの（なければドメイン未指定のもの）だけを .netrc に、
+/// 動画パスワードとプロキシ（認証情報を含む URL）は設定ファイル（`--config-locations`）に
+/// 書き、コマンドラインにパスワードを載せない。
+/// `video_password` を指定した場合は保存済みの動画パスワードより優先する。
+/// 返した `CredentialFiles` は yt-dlp のプロセスが終了するまで保持すること。
+pub fn yt_dlp_credential_args(
+  url: &str,
+  video_password: Option<&str>,
+  proxy: Option<&str>,
+) -> (Vec<String>, CredentialFiles) {
+  let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let mut args = Vec::new();
+  let mut files = CredentialFiles::default();
+  if let Err(e) = remove_stale_files() {
+    log::warn!("古い認証情報ファイルの削除に失敗: {e}");
+  }
+
+  let entries = load_entries().unwrap_or_else(|e| {
+    log::warn!("認証情報の読み込みに失敗: {e}");
+    Vec::new()
+  });
+  let entry = entry_for_url(&entries, url);
+
+  let mut netrc = String::new();
+  for entry in netrc_entries(&entries, url) {
+    let Some(username) = &entry.username else {
+      continue;
+    };
+    match read_secret(&secret_key(&entry.extractor, SecretKind::Password)) {
+      Ok(Some(password)) => netrc.push_str(&format!(
+        "machine {} login {} password {}\n",
+        entry.extractor,
+        netrc_token(username),
+        netrc_token(&password)
+      )),
+      Ok(None) => {}
+      Err(e) => log::warn!("パスワードの読み込みに失敗 ({}): {e}", entry.extractor),
+    }
+  }
+  if !netrc.is_empty() {
+    match files.create("netrc", &netrc) {
+      Ok(path) => args.extend([
+        "--netrc".to_string(),
+        "--netrc-location".to_string(),
+        path.to_string_lossy().into_owned(),
+      ]),
+      Err(e) => log::warn!(".netrc の生成に失敗: {e}"),
+    }
+  }
+
+  let video_password = match video_password.filter(|p| !p.is_empty()) {
+    Some(password) => Some(password.to_string()),
+    None => entry.and_then(|e| {
+      read_secret(&secret_key(&e.extractor, SecretKind::VideoPassword))
+        .unwrap_or_else(|err| {
+          log::warn!("動画パスワードの読み込みに失敗: {err}");
+          None
+        })
+    }),
+  };
+  let mut config = String::new();
+  if let Some(password) = video_password {
+    config.push_str(&format!("--video-password {}\n", config_token(&password)));
+  }
+  if let Some(proxy) = proxy {
+    config.push_str(&format!("--proxy {}\n", config_token(proxy)));
+  }
+  if !config.is_empty() {
+    match files.create("conf", &config) {
+      Ok(path) => args.extend(["--config-locations".to_string(), path.to_string_lossy().into_owned()]),
+      Err(e) => log::warn!("yt-dlp の設定ファイルの生成に失敗: {e}"),
+    }
+  }
+
+  (args, files)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(extractor: &str, domains: &[&str]) -> CredentialEntry {
+    CredentialEntry {
+      extractor: extractor.to_string(),
+      domains: domains.iter().map(|d| d.to_string()).collect(),
+      username: Some("user".to_string()),
+    }
+  }
+
+  #[test]
+  fn netrc_entries_prefers_domain_match() {
+    let entries = [entry("generic", &[]), entry("vimeo", &["vimeo.com"])];
+    let selected = netrc_entries(&entries, "https://player.vimeo.com/video/1");
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].extractor, "vimeo");
+  }
+
+  #[test]
+  fn netrc_entries_falls_back_to_entries_without_domains() {
+    let entries = [entry("niconico", &[]), entry("vimeo", &["vimeo.com"])];
+    let selected = netrc_entries(&entries, "https://www.nicovideo.jp/watch/sm9");
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].extractor, "niconico");
+  }
+}
@@ -0,0 +1,84 @@
+use tauri::Manager;
+use url::Url;
+
+use crate::commands;
+use crate::download::DownloadRequest;
+use crate::utils::is_valid_url;
+
+/// 登録する URL スキーム（tauri.conf.json の plugins.deep-link と揃える）
+pub const SCHEME: &str = "lucentia";
+
+/// 真偽値のクエリパラメータ（"1" / "true" / "yes" を真とする）
+fn parse_flag(value: &str) -> bool {
+  matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+/// `lucentia://download?url=...&audio=1&format=m4a` をダウンロード要求に変換する
+///
+/// 対応するパラメータ: url（必須）, preset, audio, format, quality（best / single）,
+/// subtitles, filename, title, referer。preset 以外のオプションはプリセットの値を上書きする。
+pub fn parse_deep_link(link: &str) -> Result<DownloadRequest, String> {
+  let parsed = Url::parse(link).map_err(|e| format!("error.deep_link_invalid:{e}"))?;
+  if parsed.scheme() != SCHEME {
+    return Err(format!("error.deep_link_invalid:{}", parsed.scheme()));
+  }
+
+  // lucentia://download?... と lucentia:download?... のどちらも受け付ける
+  let action = parsed.host_str().unwrap_or_else(|| parsed.path().trim_matches('/'));
+  if action != "download" {
+    return Err(format!("error.deep_link_unknown_action:{action}"));
+  }
+
+  let mut request = DownloadRequest::default();
+  for (key, value) in parsed.query_pairs() {
+    match key.as_ref() {
+      "url" => request.url = value.trim().to_string(),
+      "preset" => request.preset = Some(value.into_owned()),
+      "audio" => request.options.audio_only = Some(parse_flag(&value)),
+      "format" => request.options.preferred_format = Some(value.to_lowercase()),
+      "quality" => request.options.best_quality = Some(value != "single"),
+      "subtitles" => request.options.download_subtitles = Some(parse_flag(&value)),
+      "filename" => request.custom_filename = Some(value.into_owned()),
+      "title" => request.metadata_title = Some(value.into_owned()),
+      "referer" if is_valid_url(&value) => {
+        request.headers.get_or_insert_with(Default::default).referer = Some(value.into_owned());
+      }
+      _ => log::debug!("未対応のディープリンクパラメータを無視しました: {key}"),
+    }
+  }
+
+  if !is_valid_url(&request.url) {
+    return Err("error.invalid_url".to_string());
+  }
+  Ok(request)
+}
+
+/// メインウィンドウを前面に出す
+pub fn focus_main_window(app_handle: &tauri::AppHandle) {
+  if let Some(window) = app_handle.get_webview_window("main") {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
+/// コマンドライン引数（ディープリンクまたは URL）をダウンロード要求として処理する
+///
+/// 2つ目のインスタンスから転送された引数と、起動時の引数の両方に使う。
+/// ディープリンク・URL 以外の引数は無視する。
+pub fn handle_args(app_handle: &tauri::AppHandle, args: &[String]) {
+  for arg in args {
+    let request = if arg.starts_with(&format!("{SCHEME}:")) {
+      parse_deep_link(arg)
+    } else if is_valid_url(arg) {
+      Ok(DownloadRequest { url: arg.clone(), ..DownloadRequest::default() })
+    } else {
+      continue;
+    };
+
+    match request.and_then(|request| commands::enqueue_download(app_handle, request)) {
+      Ok(id) => log::info!("起動引数からダウンロードを追加しました: {id}"),
+      Err(e) => log::warn!("起動引数を処理できません ({}): {e}", crate::redact::redact(arg)),
+    }
+  }
+}
@@ -0,0 +1,201 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::downloader::get_binaries_status;
+use crate::history::{self, HistoryStatus};
+use crate::redact::redact;
+use crate::settings;
+use crate::utils::ensure_app_data_dir;
+use crate::{cookies, logging};
+
+/// 同梱する失敗履歴の件数
+const FAILED_HISTORY_LIMIT: usize = 20;
+/// 同梱するアプリログの行数
+const LOG_LINE_LIMIT: usize = 5000;
+
+/// OS・アプリの情報
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemInfo {
+  app_version: &'static str,
+  os: &'static str,
+  os_version: Option<String>,
+  arch: &'static str,
+  family: &'static str,
+}
+
+/// ディスクの空き容量
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiskSpace {
+  label: &'static str,
+  path: String,
+  available_bytes: Option<u64>,
+  total_bytes: Option<u64>,
+}
+
+/// OS のバージョン文字列を取得する（失敗時は None）
+fn os_version() -> Option<String> {
+  #[cfg(target_os = "macos")]
+  let output = std::process::Command::new("sw_vers").arg("-productVersion").output();
+  #[cfg(target_os = "windows")]
+  let output = crate::downloader::silent_command(Path::new("cmd")).args(["/C", "ver"]).output();
+  #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+  let output = std::process::Command::new("uname").arg("-sr").output();
+
+  let output = output.ok().filter(|o| o.status.success())?;
+  let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+  (!version.is_empty()).then_some(version)
+}
+
+fn system_info() -> SystemInfo {
+  SystemInfo {
+    app_version: env!("CARGO_PKG_VERSION"),
+    os: std::env::consts::OS,
+    os_version: os_version(),
+    arch: std::env::consts::ARCH,
+    family: std::env::consts::FAMILY,
+  }
+}
+
+fn disk_space(label: &'static str, path: &Path) -> DiskSpace {
+  DiskSpace {
+    label,
+    path: path.to_string_lossy().into_owned(),
+    available_bytes: fs4::available_space(path).ok(),
+    total_bytes: fs4::total_space(path).ok(),
+  }
+}
+
+/// 設定を JSON にし、プロキシのユーザー名・API トークン・サイト設定の追加ヘッダーの値を伏せる
+fn redacted_settings() -> Result<serde_json::Value, String> {
+  let mut app_settings = settings::load_settings()?;
+  let mask = |value: &mut Option<String>| {
+    if value.is_some() {
+      *value = Some("***".to_string());
+    }
+  };
+  mask(&mut app_settings.proxy.username);
+  if !app_settings.api.token.is_empty() {
+    app_settings.api.token = "***".to_string();
+  }
+  // 追加ヘッダーは Authorization や X-Api-Key など名前を問わず秘密情報を含みうる
+  for profile in &mut app_settings.site_profiles {
+    for value in profile.headers.values_mut() {
+      *value = "***".to_string();
+    }
+  }
+
+  serde_json::to_value(&app_settings).map_err(|e| format!("error.diagnostics_failed:{e}"))
+}
+
+/// 起動時に調整した PATH（FFmpeg / Deno などのディレクトリを含む）
+fn path_entries() -> Vec<String> {
+  std::env::var_os("PATH")
+    .map(|path| {
+      std::env::split_paths(&path)
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+  serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("{{\"error\": \"{e}\"}}"))
+}
+
+/// 出力先がディレクトリの場合はその中にファイル名を決めて作成する
+fn resolve_dest(dest: &Path) -> PathBuf {
+  if dest.is_dir() {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    dest.join(format!("lucentia-diagnostics-{timestamp}.zip"))
+  } else {
+    dest.to_path_buf()
+  }
+}
+
+/// 不具合報告用の診断情報を zip にまとめて書き出す
+///
+/// すべてのファイルは書き込み前に [`redact`] を通し、Cookie の値や
+/// パスワードは含めない（Cookie はドメインと件数のみ）。
+pub fn export_diagnostics(dest: &Path) -> Result<PathBuf, String> {
+  let dest = resolve_dest(dest);
+  let app_settings = settings::load_settings().unwrap_or_default();
+
+  let mut files: Vec<(&str, String)> = vec![("system.json", to_json(&system_info()))];
+
+  files.push((
+    "binaries.json",
+    match get_binaries_status() {
+      Ok(status) => to_json(&status),
+      Err(e) => to_json(&serde_json::json!({ "error": e })),
+    },
+  ));
+
+  files.push((
+    "settings.json",
+    match redacted_settings() {
+      Ok(value) => to_json(&value),
+      Err(e) => to_json(&serde_json::json!({ "error": e })),
+    },
+  ));
+
+  let failed: Vec<_> = history::load_entries(Some(HistoryStatus::Failed), Some(FAILED_HISTORY_LIMIT))
+    .unwrap_or_default()
+    .into_iter()
+    .map(|mut entry| {
+      // 保存済みの要求のヘッダーには認証情報が含まれることがある
+      if let Some(headers) = entry.request.as_mut().and_then(|request| request.headers.as_mut()) {
+        headers.headers.values_mut().for_each(|value| *value = "***".to_string());
+      }
+      entry
+    })
+    .collect();
+  files.push(("history-failed.json", to_json(&failed)));
+
+  files.push((
+    "cookies.json",
+    match cookies::list_cookie_sets() {
+      Ok(sets) => to_json(&sets),
+      Err(e) => to_json(&serde_json::json!({ "error": e })),
+    },
+  ));
+
+  files.push(("path.json", to_json(&path_entries())));
+
+  let mut disks = vec![disk_space("savePath", Path::new(&app_settings.save_path))];
+  if let Ok(app_data) = ensure_app_data_dir() {
+    disks.push(disk_space("appData", &app_data));
+  }
+  files.push(("disk.json", to_json(&disks)));
+
+  files.push((
+    "logs/app.log",
+    logging::read_logs(None, Some(LOG_LINE_LIMIT))
+      .map(|lines| lines.join("\n"))
+      .unwrap_or_else(|e| format!("ログを読み込めません: {e}")),
+  ));
+
+  let file = File::create(&dest).map_err(|e| format!("error.diagnostics_failed:{e}"))?;
+  let mut zip = ZipWriter::new(file);
+  let options =
+    SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+  for (name, content) in files {
+    zip
+      .start_file(name, options)
+      .map_err(|e| format!("error.diagnostics_failed:{e}"))?;
+    zip
+      .write_all(redact(&content).as_bytes())
+      .map_err(|e| format!("error.diagnostics_failed:{e}"))?;
+  }
+
+  zip.finish().map_err(|e| format!("error.diagnostics_failed:{e}"))?;
+
+  log::info!("診断情報を書き出しました: {}", dest.display());
+  Ok(dest)
+}
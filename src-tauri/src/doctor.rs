@@ -0,0 +1,297 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::downloader::{deno_location, get_binaries_status, silent_command, BinaryStatus};
+use crate::history;
+use crate::settings::{self, AppSettings};
+
+/// 空き容量がこれを下回ると警告
+const WARN_FREE_SPACE: u64 = 2 * 1024 * 1024 * 1024;
+/// 空き容量がこれを下回ると失敗
+const FAIL_FREE_SPACE: u64 = 200 * 1024 * 1024;
+
+/// チェック結果の状態（並び順は深刻度順）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+  Pass,
+  Warn,
+  Fail,
+}
+
+/// 1項目のチェック結果
+///
+/// `hint` はフロントエンドの翻訳キー（doctor.hint.*）。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorCheck {
+  pub id: String,
+  pub status: CheckStatus,
+  /// バージョンやパスなどの補足情報
+  pub detail: Option<String>,
+  pub hint: Option<String>,
+}
+
+/// 環境チェックの結果一覧
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+  /// 全チェックのうち最も深刻な状態
+  pub status: CheckStatus,
+  pub checks: Vec<DoctorCheck>,
+  pub checked_at: DateTime<Utc>,
+}
+
+impl DoctorCheck {
+  fn pass(id: &str, detail: impl Into<String>) -> Self {
+    Self { id: id.to_string(), status: CheckStatus::Pass, detail: Some(detail.into()), hint: None }
+  }
+
+  fn warn(id: &str, detail: impl Into<String>, hint: &str) -> Self {
+    Self {
+      id: id.to_string(),
+      status: CheckStatus::Warn,
+      detail: Some(detail.into()),
+      hint: Some(format!("doctor.hint.{hint}")),
+    }
+  }
+
+  fn fail(id: &str, detail: impl Into<String>, hint: &str) -> Self {
+    Self {
+      id: id.to_string(),
+      status: CheckStatus::Fail,
+      detail: Some(detail.into()),
+      hint: Some(format!("doctor.hint.{hint}")),
+    }
+  }
+}
+
+// ─── バイナリ ─────────────────────────────────────
+
+/// バイナリが存在し、実行してバージョンを取得できるか
+fn check_binary(status: &BinaryStatus) -> DoctorCheck {
+  let id = format!("binary.{}", status.name);
+  if status.error.is_some() {
+    return DoctorCheck::fail(&id, &status.path, "binaryInvalid");
+  }
+  match (&status.version, status.installed) {
+    (Some(version), _) => DoctorCheck::pass(&id, format!("{version} ({})", status.path)),
+    (None, true) => DoctorCheck::fail(&id, &status.path, "binaryNotExecutable"),
+    (None, false) => DoctorCheck::fail(&id, &status.path, "binaryMissing"),
+  }
+}
+
+/// yt-dlp の JS ランタイム検出で Deno が見つかるか
+///
+/// `yt-dlp -v` の "[debug] JS runtimes:" 行で判定する（URL なしで実行するため終了コードは見ない）。
+fn check_js_runtime(yt_dlp: Option<&BinaryStatus>) -> DoctorCheck {
+  const ID: &str = "ytDlp.jsRuntime";
+
+  let Some(yt_dlp) = yt_dlp.filter(|s| s.version.is_some()) else {
+    return DoctorCheck::fail(ID, "yt-dlp unavailable", "binaryMissing");
+  };
+
+  let mut command = silent_command(Path::new(&yt_dlp.path));
+  command.args(["-v", "--ignore-config"]);
+  if let Some(deno) = deno_location() {
+    command.arg("--js-runtimes").arg(format!("deno:{}", deno.to_string_lossy()));
+  }
+  let output = match command.output() {
+    Ok(output) => output,
+    Err(e) => return DoctorCheck::fail(ID, e.to_string(), "binaryNotExecutable"),
+  };
+  let stderr = String::from_utf8_lossy(&output.stderr);
+
+  let Some(line) = stderr.lines().find(|line| line.contains("JS runtimes:")) else {
+    return DoctorCheck::warn(ID, "JS runtime detection not reported", "updateYtDlp");
+  };
+  let runtimes = line.split_once("JS runtimes:").map_or("", |(_, r)| r).trim();
+
+  if runtimes.to_lowercase().contains("deno") {
+    DoctorCheck::pass(ID, runtimes)
+  } else {
+    DoctorCheck::fail(ID, runtimes, "denoNotDetected")
+  }
+}
+
+// ─── 保存先 ───────────────────────────────────────
+
+/// 保存先が存在し、書き込み可能で、十分な空き容量があるか
+fn check_save_path(save_path: &str) -> Vec<DoctorCheck> {
+  let path = Path::new(save_path);
+  if !path.is_dir() {
+    return vec![DoctorCheck::fail("savePath.exists", save_path, "savePathMissing")];
+  }
+
+  let mut checks = vec![DoctorCheck::pass("savePath.exists", save_path)];
+
+  let probe = path.join(format!(".lucentia-write-test-{}", std::process::id()));
+  checks.push(match fs::write(&probe, b"") {
+    Ok(()) => {
+      let _ = fs::remove_file(&probe);
+      DoctorCheck::pass("savePath.writable", save_path)
+    }
+    Err(e) => DoctorCheck::fail("savePath.writable", e.to_string(), "savePathNotWritable"),
+  });
+
+  checks.push(match fs4::available_space(path) {
+    Ok(free) if free < FAIL_FREE_SPACE => {
+      DoctorCheck::fail("savePath.space", format_bytes(free), "freeDiskSpace")
+    }
+    Ok(free) if free < WARN_FREE_SPACE => {
+      DoctorCheck::warn("savePath.space", format_bytes(free), "freeDiskSpace")
+    }
+    Ok(free) => DoctorCheck::pass("savePath.space", format_bytes(free)),
+    Err(e) => DoctorCheck::warn("savePath.space", e.to_string(), "freeDiskSpace"),
+  });
+
+  checks
+}
+
+fn format_bytes(bytes: u64) -> String {
+  #[allow(clippy::cast_precision_loss)]
+  let gib = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+  format!("{gib:.1} GiB free")
+}
+
+// ─── 設定・履歴 ────────────────────────────────────
+
+fn check_settings_file() -> (DoctorCheck, AppSettings) {
+  match settings::load_settings() {
+    Ok(app_settings) => (DoctorCheck::pass("settings.parse", "settings.json"), app_settings),
+    Err(e) => (
+      DoctorCheck::fail("settings.parse", e, "resetSettings"),
+      AppSettings::default(),
+    ),
+  }
+}
+
+fn check_history_file() -> DoctorCheck {
+  match history::count_entries() {
+    Ok(count) => DoctorCheck::pass("history.parse", format!("{count} entries")),
+    Err(e) => DoctorCheck::fail("history.parse", e, "clearHistory"),
+  }
+}
+
+// ─── Cookie のブラウザ ──────────────────────────────
+
+/// ブラウザのプロファイルディレクトリの候補
+///
+/// yt-dlp の --cookies-from-browser が参照する既定の場所に合わせている。
+fn browser_profile_dirs(browser: &str) -> Vec<PathBuf> {
+  let config = dirs::config_dir();
+  let local = dirs::data_local_dir();
+  let home = dirs::home_dir();
+
+  let join = |base: &Option<PathBuf>, rel: &str| base.as_ref().map(|b| b.join(rel));
+
+  let candidates = if cfg!(target_os = "windows") {
+    match browser {
+      "chrome" => vec![join(&local, "Google/Chrome/User Data")],
+      "chromium" => vec![join(&local, "Chromium/User Data")],
+      "edge" => vec![join(&local, "Microsoft/Edge/User Data")],
+      "brave" => vec![join(&local, "BraveSoftware/Brave-Browser/User Data")],
+      "vivaldi" => vec![join(&local, "Vivaldi/User Data")],
+      "whale" => vec![join(&local, "Naver/Naver Whale/User Data")],
+      "opera" => vec![join(&config, "Opera Software/Opera Stable")],
+      "firefox" => vec![join(&config, "Mozilla/Firefox")],
+      _ => vec![],
+    }
+  } else if cfg!(target_os = "macos") {
+    match browser {
+      "chrome" => vec![join(&config, "Google/Chrome")],
+      "chromium" => vec![join(&config, "Chromium")],
+      "edge" => vec![join(&config, "Microsoft Edge")],
+      "brave" => vec![join(&config, "BraveSoftware/Brave-Browser")],
+      "vivaldi" => vec![join(&config, "Vivaldi")],
+      "whale" => vec![join(&config, "Naver/Whale")],
+      "opera" => vec![join(&config, "com.operasoftware.Opera")],
+      "firefox" => vec![join(&config, "Firefox")],
+      "safari" => vec![
+        join(&home, "Library/Containers/com.apple.Safari/Data/Library/Cookies"),
+        join(&home, "Library/Cookies"),
+      ],
+      _ => vec![],
+    }
+  } else {
+    match browser {
+      "chrome" => vec![join(&config, "google-chrome")],
+      "chromium" => vec![join(&config, "chromium"), join(&home, "snap/chromium/common/chromium")],
+      "edge" => vec![join(&config, "microsoft-edge")],
+      "brave" => vec![join(&config, "BraveSoftware/Brave-Browser")],
+      "vivaldi" => vec![join(&config, "vivaldi")],
+      "whale" => vec![join(&config, "naver-whale")],
+      "opera" => vec![join(&config, "opera")],
+      "firefox" => vec![
+        join(&home, ".mozilla/firefox"),
+        join(&home, "snap/firefox/common/.mozilla/firefox"),
+        join(&home, ".var/app/org.mozilla.firefox/.mozilla/firefox"),
+      ],
+      _ => vec![],
+    }
+  };
+
+  candidates.into_iter().flatten().collect()
+}
+
+/// Cookie 取得元のブラウザがインストールされているか
+///
+/// 設定値は yt-dlp と同じ "BROWSER[+KEYRING][:PROFILE]" 形式を受け付ける。
+fn check_cookie_browser(cookies_browser: Option<&str>) -> DoctorCheck {
+  const ID: &str = "cookies.browser";
+
+  let Some(value) = cookies_browser.filter(|v| !v.trim().is_empty()) else {
+    return DoctorCheck::pass(ID, "not configured");
+  };
+
+  let (spec, profile) = value.split_once(':').map_or((value, None), |(b, p)| (b, Some(p)));
+  let browser = spec.split('+').next().unwrap_or(spec).trim().to_lowercase();
+
+  // プロファイルをパスで指定している場合はそのパスを確認する
+  if let Some(profile) = profile.filter(|p| p.contains('/') || p.contains('\\')) {
+    return if Path::new(profile).exists() {
+      DoctorCheck::pass(ID, value)
+    } else {
+      DoctorCheck::fail(ID, value, "cookieBrowserMissing")
+    };
+  }
+
+  let dirs = browser_profile_dirs(&browser);
+  if dirs.is_empty() {
+    return DoctorCheck::fail(ID, value, "cookieBrowserUnsupported");
+  }
+
+  match dirs.iter().find(|dir| dir.is_dir()) {
+    Some(dir) => DoctorCheck::pass(ID, dir.to_string_lossy()),
+    None => DoctorCheck::fail(ID, value, "cookieBrowserMissing"),
+  }
+}
+
+// ─── 実行 ─────────────────────────────────────────
+
+/// 環境全体をチェックする（バイナリを実行するため同期的に時間がかかる）
+pub fn run_doctor() -> DoctorReport {
+  let mut checks = Vec::new();
+
+  let (settings_check, app_settings) = check_settings_file();
+
+  match get_binaries_status() {
+    Ok(binaries) => {
+      checks.extend(binaries.iter().map(check_binary));
+      checks.push(check_js_runtime(binaries.iter().find(|b| b.name == "yt-dlp")));
+    }
+    Err(e) => checks.push(DoctorCheck::fail("binaries", e, "binaryMissing")),
+  }
+
+  checks.extend(check_save_path(&app_settings.save_path));
+  checks.push(settings_check);
+  checks.push(check_history_file());
+  checks.push(check_cookie_browser(app_settings.cookies_browser.as_deref()));
+
+  let status = checks.iter().map(|c| c.status).max().unwrap_or(CheckStatus::Pass);
+  log::info!("環境チェックが完了しました: {status:?}");
+
+  DoctorReport { status, checks, checked_at: Utc::now() }
+}
@@ -0,0 +1,1116 @@
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::io::AsyncBufReadExt;
+use uuid::Uuid;
+use youtube_dl::YoutubeDlOutput;
+
+use crate::cookies;
+use crate::credentials;
+use crate::downloader::{deno_location, ffmpeg_location, get_yt_dlp_path};
+use crate::history::{self, HistoryEntry, HistoryStatus};
+use crate::logging::DownloadLog;
+use crate::network::{self, HeaderOverrides};
+use crate::presets::{self, DownloadOptionsOverride, SUPPORTED_AUDIO_FORMATS};
+use crate::redact;
+use crate::settings::{self, AppSettings, DownloadOptions};
+use crate::utils::{get_default_download_path, is_safe_path, is_valid_url, sanitize_filename};
+
+/// 対応する動画拡張子
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "webm", "avi", "mov", "flv"];
+/// 対応する音声拡張子
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "opus", "ogg", "wav", "flac", "aac"];
+
+/// yt-dlp フォーマットセレクタ
+const FMT_BEST_AUDIO: &str = "bestaudio/best";
+const FMT_BEST_VIDEO_AUDIO: &str = "bestvideo+bestaudio/best";
+const FMT_BEST_SINGLE: &str = "best";
+
+static RE_AMP_TIMESTAMP: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"&t=\d+\.?\d*").unwrap());
+static RE_FIRST_TIMESTAMP: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"\?t=\d+\.?\d*&").unwrap());
+static RE_ONLY_TIMESTAMP: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"\?t=\d+\.?\d*$").unwrap());
+static RE_PROGRESS: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r"\[download\]\s+(\d+\.?\d*)%(?:\s+of\s+~?\S+\s+at\s+(\S+)(?:\s+ETA\s+(\S+))?)?")
+    .unwrap()
+});
+
+// ─── ダウンロード状態管理 ──────────────────────────────
+// フロントエンドがリロードしてもRust側で状態を保持し、復帰時に問い合わせられるようにする
+
+/// 各ダウンロードの現在の状態
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadStatusEntry {
+  pub id: String,
+  pub status: &'static str, // "downloading" | "completed" | "error"
+  pub percent: f64,
+  pub output_path: Option<String>,
+  pub error: Option<String>,
+}
+
+/// グローバルなダウンロード状態マップ
+/// フロントエンドが再接続したとき、ここに問い合わせて復帰する
+static DOWNLOAD_MANAGER: LazyLock<Mutex<HashMap<String, DownloadStatusEntry>>> =
+  LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn dm_set_downloading(id: &str) {
+  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
+  map.insert(id.to_string(), DownloadStatusEntry {
+    id: id.to_string(),
+    status: "downloading",
+    percent: 0.0,
+    output_path: None,
+    error: None,
+  });
+}
+
+fn dm_update_progress(id: &str, percent: f64) {
+  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
+  if let Some(entry) = map.get_mut(id) {
+    entry.percent = percent;
+  }
+}
+
+fn dm_set_completed(id: &str, output_path: &str) {
+  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
+  if let Some(entry) = map.get_mut(id) {
+    entry.status = "completed";
+    entry.percent = 100.0;
+    entry.output_path = Some(output_path.to_string());
+  }
+}
+
+fn dm_set_error(id: &str, error: &str) {
+  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
+  if let Some(entry) = map.get_mut(id) {
+    entry.status = "error";
+    entry.error = Some(error.to_string());
+  }
+}
+
+pub(crate) fn dm_remove(id: &str) {
+  let mut map = DOWNLOAD_MANAGER.lock().unwrap();
+  map.remove(id);
+}
+
+/// 実行中の yt-dlp プロセスの中断通知（ダウンロードID → 通知）
+static RUNNING_DOWNLOADS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Notify>>>> =
+  LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 実行中のダウンロードを中断する（実行中でなければ false）
+pub fn cancel_running_download(id: &str) -> bool {
+  match RUNNING_DOWNLOADS.lock().unwrap().get(id) {
+    Some(notify) => {
+      notify.notify_one();
+      true
+    }
+    None => false,
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoMetadata {
+  pub title: String,
+  pub thumbnail: Option<String>,
+  pub duration: Option<String>,
+}
+
+/// yt-dlp メタデータ出力からサムネイルURLを抽出する
+fn extract_thumbnail(output: &YoutubeDlOutput) -> Option<String> {
+  if let Some(playlist) = output.clone().into_playlist() {
+    playlist
+      .thumbnails
+      .and_then(|thumbs| thumbs.first().cloned())
+      .and_then(|thumb| thumb.url)
+  } else if let Some(video) = output.clone().into_single_video() {
+    video.thumbnail.or_else(|| {
+      video
+        .thumbnails
+        .and_then(|thumbs| thumbs.first().cloned())
+        .and_then(|thumb| thumb.url)
+    })
+  } else {
+    None
+  }
+}
+
+#[derive(Serialize, Clone)]
+pub struct DownloadProgress {
+  pub id: String,
+  pub percent: f64,
+  pub speed: Option<String>,
+  pub eta: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DownloadCompleteEvent {
+  pub id: String,
+  pub output_path: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DownloadErrorEvent {
+  pub id: String,
+  pub error: String,
+}
+
+/// ダウンロードの進捗・完了・失敗の通知先
+///
+/// アプリでは Tauri イベント、CLI では端末のプログレスバーに出力する。
+pub trait DownloadReporter: Send + Sync {
+  fn progress(&self, event: DownloadProgress);
+  fn complete(&self, event: DownloadCompleteEvent);
+  fn error(&self, event: DownloadErrorEvent);
+}
+
+/// 1件のダウンロード要求
+///
+/// オプションはプリセット（`preset`）の値を基に、指定された項目だけを上書きする。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DownloadRequest {
+  pub download_id: String,
+  pub url: String,
+  /// 使用するプリセット名（None は既定のオプション）
+  pub preset: Option<String>,
+  #[serde(flatten)]
+  pub options: DownloadOptionsOverride,
+  pub custom_filename: Option<String>,
+  pub thumbnail: Option<String>,
+  pub metadata_title: Option<String>,
+  pub video_password: Option<String>,
+  pub headers: Option<HeaderOverrides>,
+  /// 再試行元の履歴エントリID
+  pub retry_of: Option<String>,
+}
+
+impl DownloadRequest {
+  /// 履歴に保存する形にする
+  ///
+  /// オプションは解決済みの値で埋めるため、プリセット名は残さない（後でプリセットが
+  /// 変わっても再試行の結果が変わらないようにする）。ID・パスワード・任意ヘッダー
+  /// （Cookie や Authorization を含み得る）は保存しない。
+  fn for_history(&self, options: &DownloadOptions) -> Self {
+    Self {
+      download_id: String::new(),
+      preset: None,
+      options: options.clone().into(),
+      video_password: None,
+      headers: self.headers.as_ref().map(|headers| HeaderOverrides {
+        headers: BTreeMap::new(),
+        ..headers.clone()
+      }),
+      retry_of: None,
+      ..self.clone()
+    }
+  }
+}
+
+/// yt-dlp でタイトル・サムネイル・長さを取得する
+pub async fn fetch_metadata(
+  url: &str,
+  video_password: Option<&str>,
+  headers: Option<&HeaderOverrides>,
+) -> Result<VideoMetadata, String> {
+  load_metadata(url, video_password, headers, &[]).await
+}
+
+/// 汎用（generic）抽出器を除いてメタデータを取得する
+///
+/// クリップボード監視など、任意の URL が yt-dlp の対応サイトかを判定する用途に使う。
+/// generic 抽出器を含めると、ほぼすべての Web ページが「取得成功」になってしまうため。
+pub async fn probe_metadata(url: &str) -> Result<VideoMetadata, String> {
+  load_metadata(url, None, None, &["--ies", "default,-generic"]).await
+}
+
+async fn load_metadata(
+  url: &str,
+  video_password: Option<&str>,
+  headers: Option<&HeaderOverrides>,
+  extra_args: &[&str],
+) -> Result<VideoMetadata, String> {
+  log::info!("Downloading metadata: {}", redact::redact(url));
+
+  if !is_valid_url(url) {
+    return Err("error.invalid_url".to_string());
+  }
+
+  let cleaned_url = clean_timestamp_param(url);
+  let yt_dlp_path = get_yt_dlp_path().await?;
+  let app_settings = settings::load_settings().unwrap_or_default();
+
+  let context = yt_dlp_context(&app_settings, &cleaned_url, video_password, headers);
+
+  let result = fetch_yt_dlp_metadata(&yt_dlp_path, &context, &cleaned_url, extra_args).await;
+
+  log::info!("Downloaded metadata");
+
+  match result {
+    Ok(metadata) => {
+      let thumbnail = extract_thumbnail(&metadata);
+
+      if let Some(playlist) = metadata.clone().into_playlist() {
+        Ok(VideoMetadata {
+          title: playlist.title.unwrap_or_else(|| "No Title".to_string()),
+          thumbnail,
+          duration: None,
+        })
+      } else if let Some(video) = metadata.into_single_video() {
+        let duration = video.duration.and_then(|d| format_duration(&d));
+
+        Ok(VideoMetadata {
+          title: video.title.unwrap_or_else(|| "No Title".to_string()),
+          thumbnail,
+          duration,
+        })
+      } else {
+        Err("error.get_title_failed".to_string())
+      }
+    }
+    Err(e) => Err(map_yt_dlp_error(&e, "error.metadata_failed")),
+  }
+}
+
+/// `serde_json::Value` から duration を "MM:SS" 形式にフォーマットする
+fn format_duration(d: &serde_json::Value) -> Option<String> {
+  let seconds = if let Some(s) = d.as_u64() {
+    Some(s)
+  } else if let Some(f) = d.as_f64() {
+    if f >= 0.0 && f.is_finite() {
+      #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+      Some(f as u64)
+    } else {
+      None
+    }
+  } else if let Some(s) = d.as_str() {
+    s.parse::<u64>().ok()
+  } else {
+    serde_json::to_string(d).ok().and_then(|json_str| {
+      let cleaned = if json_str.contains('.') {
+        json_str.split('.').next().unwrap_or("0").trim_matches('"')
+      } else {
+        json_str.trim_matches('"')
+      };
+      cleaned.parse::<u64>().ok()
+    })
+  };
+
+  seconds.map(|s| format!("{:02}:{:02}", s / 60, s % 60))
+}
+
+/// ダウンロードを実行し、保存したファイルのパスを返す
+///
+/// 進捗は `reporter` に通知し、結果は履歴とダウンロード状態に記録する。
+#[allow(clippy::too_many_lines)]
+pub async fn run_download(
+  reporter: &dyn DownloadReporter,
+  request: DownloadRequest,
+) -> Result<String, String> {
+  let options = presets::resolve(request.preset.as_deref(), &request.options)?;
+  let history_request = request.for_history(&options);
+  let retry_of = request.retry_of.clone();
+  let DownloadOptions { audio_only, folder_path, best_quality, download_subtitles, preferred_format } =
+    options;
+  let DownloadRequest {
+    download_id,
+    url,
+    custom_filename,
+    thumbnail,
+    metadata_title,
+    video_password,
+    headers,
+    ..
+  } = request;
+
+  log::info!("Downloading video: {url}");
+
+  if !is_valid_url(&url) {
+    return Err("error.invalid_url".to_string());
+  }
+
+  let cleaned_url = clean_timestamp_param(&url);
+  let yt_dlp_path = get_yt_dlp_path().await?;
+
+  // ファイル名の生成
+  // 優先順: カスタムファイル名 > フロントエンドで取得済みのタイトル > yt-dlpから再取得
+  let (filename_base, thumbnail) = match custom_filename {
+    Some(filename) if !filename.trim().is_empty() => (sanitize_filename(&filename), thumbnail),
+    _ => match metadata_title {
+      Some(title) if !title.trim().is_empty() => (sanitize_filename(&title), thumbnail),
+      _ => {
+        let (title, fetched_thumb) = get_video_info(
+          &cleaned_url,
+          &yt_dlp_path.to_string_lossy(),
+          video_password.as_deref(),
+          headers.as_ref(),
+        )
+        .await?;
+        (title, thumbnail.or(fetched_thumb))
+      }
+    },
+  };
+
+  // 出力ファイル名生成（不正なフォーマットはデフォルトにフォールバック）
+  let extension = if audio_only {
+    let fmt = preferred_format.as_deref().unwrap_or("mp3");
+    if SUPPORTED_AUDIO_FORMATS.contains(&fmt) { fmt } else { "mp3" }
+  } else {
+    preferred_format.as_deref().unwrap_or("mp4")
+  };
+  let output_filename = format!("{filename_base}.{extension}");
+
+  // フォルダパスの検証と安全なパスの構築
+  // 優先順位: 引数 folder_path > settings.save_path > OS デフォルト
+  let app_settings = settings::load_settings().unwrap_or_default();
+
+  let resolve_folder = |p: &str| -> Result<String, String> {
+    let path = Path::new(p);
+    if !path.is_dir() {
+      return Err("error.path_not_dir".to_string());
+    }
+    // audio/video でサブディレクトリを振り分け
+    let subdir = if audio_only { settings::SUBDIR_AUDIO } else { settings::SUBDIR_VIDEOS };
+    let target_dir = path.join(subdir);
+    let target_dir = if target_dir.is_dir() { target_dir } else { path.to_path_buf() };
+
+    let full_path = target_dir.join(&output_filename);
+    if !is_safe_path(&full_path) {
+      return Err("error.unsafe_path".to_string());
+    }
+    Ok(full_path.to_string_lossy().to_string())
+  };
+
+  let base_output_path = match folder_path {
+    Some(ref p) if !p.trim().is_empty() => resolve_folder(p)?,
+    _ if !app_settings.save_path.is_empty() => {
+      resolve_folder(&app_settings.save_path)
+        .unwrap_or_else(|_| get_default_download_path(&output_filename).unwrap_or_default())
+    }
+    _ => get_default_download_path(&output_filename)?,
+  };
+
+  // ファイル名が存在する場合はUUIDを追加して重複を回避
+  #[allow(unused_mut)]
+  let mut output_path = if Path::new(&base_output_path).exists() {
+    let dir = Path::new(&base_output_path)
+      .parent()
+      .ok_or("error.parent_dir_failed")?;
+    let stem = Path::new(&output_filename)
+      .file_stem()
+      .ok_or("error.parent_dir_failed")?;
+    let ext = Path::new(&output_filename)
+      .extension()
+      .ok_or("error.parent_dir_failed")?;
+
+    let uuid_str = Uuid::new_v4().to_string();
+    let uuid = uuid_str.split('-').next().unwrap_or("unique");
+    let new_filename = format!(
+      "{}_{}.{}",
+      stem.to_string_lossy(),
+      uuid,
+      ext.to_string_lossy()
+    );
+
+    let new_path = dir.join(new_filename);
+    log::info!("ファイル名の重複を回避: {}", new_path.to_string_lossy());
+
+    new_path.to_string_lossy().to_string()
+  } else {
+    base_output_path
+  };
+
+  // Windows環境では単純なパス処理
+  #[cfg(windows)]
+  {
+    let simple_path = if output_path.contains(" ") {
+      let dir = Path::new(&output_path).parent().unwrap_or(Path::new(""));
+      let ext = Path::new(&output_path)
+        .extension()
+        .unwrap_or_else(|| std::ffi::OsStr::new("mp4"));
+
+      let filename_base = Path::new(&output_path)
+        .file_stem()
+        .unwrap_or_else(|| std::ffi::OsStr::new("video"))
+        .to_string_lossy()
+        .to_string();
+
+      let clean_name = filename_base
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .take(100)
+        .collect::<String>();
+
+      let mut new_path = dir.join(format!("{}.{}", clean_name, ext.to_string_lossy()));
+
+      // 単純化後のファイル名が既に存在する場合はUUIDで衝突回避
+      if new_path.exists() {
+        let uuid_str = Uuid::new_v4().to_string();
+        let uuid = uuid_str.split('-').next().unwrap_or("unique");
+        new_path = dir.join(format!("{}_{}.{}", clean_name, uuid, ext.to_string_lossy()));
+      }
+
+      log::info!(
+        "パス名を単純化: {} -> {}",
+        output_path,
+        new_path.to_string_lossy()
+      );
+      new_path.to_string_lossy().to_string()
+    } else {
+      output_path.clone()
+    };
+
+    output_path = simple_path;
+  }
+
+  log::info!("Output file: {output_path}");
+
+  let ffmpeg_location = ffmpeg_location().map(|p| p.to_string_lossy().to_string());
+
+  let context = yt_dlp_context(
+    &app_settings,
+    &cleaned_url,
+    video_password.as_deref(),
+    headers.as_ref(),
+  );
+  let args = build_yt_dlp_args(
+    &cleaned_url,
+    &output_path,
+    audio_only,
+    best_quality,
+    download_subtitles,
+    preferred_format.as_deref(),
+    &context.args,
+    ffmpeg_location.as_deref(),
+  );
+
+  log::info!("Starting download...");
+  log::debug!(
+    "実行コマンド: {} {}",
+    yt_dlp_path.to_string_lossy(),
+    redact::redact_args(&args)
+  );
+
+  // ダウンロード状態をManagerに登録
+  dm_set_downloading(&download_id);
+
+  if let Err(e) = run_yt_dlp_with_progress(reporter, &yt_dlp_path, &download_id, &args, &context.envs, best_quality && !audio_only).await {
+    let _ = history::add_entry(build_history_entry(
+      &url, &filename_base, extension, best_quality,
+      HistoryStatus::Failed, None, Some(e.clone()), thumbnail.clone(), None,
+      &history_request, retry_of,
+    ));
+    dm_set_error(&download_id, &e);
+    reporter.error(DownloadErrorEvent { id: download_id.clone(), error: e.clone() });
+    schedule_dm_cleanup(download_id);
+    return Err(e);
+  }
+
+  if Path::new(&output_path).exists() {
+    let file_size = std::fs::metadata(&output_path).ok().map(|m| m.len());
+    let size_str = file_size.map_or("不明".to_string(), |s| format!("{s} bytes"));
+    log::info!("出力ファイル: {output_path} (サイズ: {size_str})");
+    reporter.progress(DownloadProgress { id: download_id.clone(), percent: 100.0, speed: None, eta: None });
+
+    let _ = history::add_entry(build_history_entry(
+      &url, &filename_base, extension, best_quality,
+      HistoryStatus::Success, file_size, None, thumbnail, Some(output_path.clone()),
+      &history_request, retry_of,
+    ));
+
+    dm_set_completed(&download_id, &output_path);
+    reporter.complete(DownloadCompleteEvent { id: download_id.clone(), output_path: output_path.clone() });
+    schedule_dm_cleanup(download_id);
+    Ok(output_path)
+  } else {
+    log::warn!("出力ファイルが存在しません: {output_path}");
+
+    let _ = history::add_entry(build_history_entry(
+      &url, &filename_base, extension, best_quality,
+      HistoryStatus::Failed, None, Some("error.file_not_found".to_string()), thumbnail, None,
+      &history_request, retry_of,
+    ));
+
+    dm_set_error(&download_id, "error.file_not_found");
+    reporter.error(DownloadErrorEvent { id: download_id.clone(), error: "error.file_not_found".to_string() });
+    schedule_dm_cleanup(download_id);
+    Err("error.file_not_found".to_string())
+  }
+}
+
+/// メタデータ取得とダウンロードで共通の yt-dlp の引数と環境変数
+struct YtDlpContext {
+  /// プロキシ・ヘッダー・Cookie・認証情報の引数
+  args: Vec<String>,
+  /// 子プロセスにだけ渡す環境変数（CA 証明書など）
+  envs: Vec<(&'static str, OsString)>,
+  /// 認証情報の一時ファイル（yt-dlp の終了後、このコンテキストのドロップ時に削除される）
+  _credential_files: credentials::CredentialFiles,
+}
+
+/// メタデータ取得とダウンロードで共通の yt-dlp 引数（プロキシ・ヘッダー・JS ランタイム・Cookie・認証情報）
+///
+/// URL のドメインに対応する取り込み済み Cookie があれば、ブラウザの Cookie より優先する。
+fn yt_dlp_context(
+  app_settings: &AppSettings,
+  url: &str,
+  video_password: Option<&str>,
+  headers: Option<&HeaderOverrides>,
+) -> YtDlpContext {
+  let mut args = network::yt_dlp_network_args(app_settings);
+  args.extend(network::yt_dlp_header_args(app_settings, url, headers));
+  if let Some(deno) = deno_location() {
+    args.extend(["--js-runtimes".to_string(), format!("deno:{}", deno.to_string_lossy())]);
+  }
+  let proxy = network::yt_dlp_proxy(app_settings, url);
+  let (credential_args, credential_files) =
+    credentials::yt_dlp_credential_args(url, video_password, proxy.as_deref());
+  args.extend(credential_args);
+
+  if let Some(cookie_file) = cookies::cookie_file_for_url(url) {
+    args.extend(["--cookies".into(), cookie_file.to_string_lossy().into_owned()]);
+  } else if let Some(browser) = &app_settings.cookies_browser {
+    args.extend(["--cookies-from-browser".into(), browser.clone()]);
+  }
+
+  YtDlpContext {
+    args,
+    envs: network::yt_dlp_env(&app_settings.network),
+    _credential_files: credential_files,
+  }
+}
+
+/// yt-dlp のコマンド引数を構築する
+#[allow(clippy::too_many_arguments)]
+fn build_yt_dlp_args(
+  url: &str,
+  output_path: &str,
+  audio_only: bool,
+  best_quality: bool,
+  download_subtitles: bool,
+  preferred_format: Option<&str>,
+  common_args: &[String],
+  ffmpeg_location: Option<&str>,
+) -> Vec<String> {
+  let format_value = preferred_format.unwrap_or("mp4");
+  let mut args: Vec<String> = vec![
+    "--newline".into(),
+    "--socket-timeout".into(),
+    "15".into(),
+    "--no-playlist".into(),
+  ];
+
+  if let Some(location) = ffmpeg_location {
+    args.extend(["--ffmpeg-location".into(), location.into()]);
+  }
+
+  args.extend_from_slice(common_args);
+
+  #[cfg(windows)]
+  {
+    args.push("--windows-filenames".into());
+    // CREATE_NO_WINDOW でコンソールがないため、上書き確認プロンプトでハングを防止
+    args.push("--force-overwrites".into());
+  }
+
+  if audio_only {
+    let audio_format = if SUPPORTED_AUDIO_FORMATS.contains(&format_value) {
+      format_value
+    } else {
+      "mp3"
+    };
+    args.extend(
+      [
+        "--extract-audio",
+        "--audio-format",
+        audio_format,
+        "--audio-quality",
+        "0",
+        "--format",
+        FMT_BEST_AUDIO,
+        "--no-mtime",
+      ]
+      .map(String::from),
+    );
+  } else {
+    let format_selector = if best_quality { FMT_BEST_VIDEO_AUDIO } else { FMT_BEST_SINGLE };
+    args.extend(
+      ["--format", format_selector, "--merge-output-format", format_value]
+        .map(String::from),
+    );
+
+    // MP4コンテナの場合、Opus音声をAACに変換してWindows互換性を確保
+    if format_value == "mp4" {
+      args.extend([
+        "--postprocessor-args".into(),
+        "Merger+ffmpeg_o:-c:v copy -c:a aac".into(),
+      ]);
+    }
+  }
+
+  if download_subtitles {
+    args.extend(
+      [
+        "--write-sub",
+        "--write-auto-sub",
+        "--sub-format",
+        "srt",
+        "--embed-subs",
+        "--sub-lang",
+        "ja,en",
+      ]
+      .map(String::from),
+    );
+  }
+
+  args.extend(["-o".to_string(), output_path.to_string(), url.to_string()]);
+  args
+}
+
+/// yt-dlp プロセスを起動し、進捗をフロントエンドにリアルタイム通知する
+async fn run_yt_dlp_with_progress(
+  reporter: &dyn DownloadReporter,
+  yt_dlp_path: &Path,
+  download_id: &str,
+  args: &[String],
+  envs: &[(&'static str, OsString)],
+  uses_separate_streams: bool,
+) -> Result<(), String> {
+  let mut cmd = tokio::process::Command::new(yt_dlp_path);
+  cmd.args(args)
+    .envs(envs.iter().map(|(key, value)| (key, value)))
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped());
+  #[cfg(windows)]
+  {
+    cmd.creation_flags(crate::downloader::CREATE_NO_WINDOW);
+  }
+  let download_log = DownloadLog::create(download_id);
+  download_log.write(
+    "command",
+    &format!("{} {}", yt_dlp_path.to_string_lossy(), redact::redact_args(args)),
+  );
+
+  let mut child = cmd.spawn()
+    .map_err(|e| format!("error.ytdlp_spawn:{e}"))?;
+
+  let cancel = Arc::new(tokio::sync::Notify::new());
+  RUNNING_DOWNLOADS
+    .lock()
+    .unwrap()
+    .insert(download_id.to_string(), Arc::clone(&cancel));
+
+  let stdout = child
+    .stdout
+    .take()
+    .ok_or("error.stdout_failed")?;
+  let stderr = child
+    .stderr
+    .take()
+    .ok_or("error.stderr_failed")?;
+
+  // stderr をバックグラウンドで収集（エラー報告用）
+  let stderr_log = download_log.clone();
+  let stderr_handle = tokio::spawn(async move {
+    let reader = tokio::io::BufReader::new(stderr);
+    let mut lines = reader.lines();
+    let mut output = String::new();
+    while let Ok(Some(line)) = lines.next_line().await {
+      log::debug!("yt-dlp stderr: {line}");
+      stderr_log.write("stderr", &line);
+      output.push_str(&line);
+      output.push('\n');
+    }
+    output
+  });
+
+  // stdout をパースしてダウンロード進捗を取得
+  let reader = tokio::io::BufReader::new(stdout);
+  let mut lines = reader.lines();
+  let mut pass: u32 = 0;
+  let mut last_raw_percent: f64 = 0.0;
+  let mut last_emitted: f64 = 0.0;
+  let mut stdout_error: Option<String> = None;
+  let mut cancelled = false;
+
+  loop {
+    let line = tokio::select! {
+      line = lines.next_line() => match line {
+        Ok(Some(line)) => line,
+        _ => break,
+      },
+      () = cancel.notified() => {
+        cancelled = true;
+        let _ = child.start_kill();
+        break;
+      }
+    };
+
+    log::debug!("yt-dlp: {line}");
+    download_log.write("stdout", &line);
+
+    // stdoutに出力されるERROR行を捕捉
+    if line.starts_with("ERROR:") {
+      stdout_error = Some(line);
+    } else if line.starts_with("[download]") {
+      if let Some(caps) = RE_PROGRESS.captures(&line) {
+        if let Ok(raw_percent) = caps[1].parse::<f64>() {
+          // パーセンテージが大幅に下がった場合、新しいダウンロードパスと判定
+          if raw_percent < last_raw_percent - 10.0 {
+            pass += 1;
+          }
+          last_raw_percent = raw_percent;
+
+          let percent = if uses_separate_streams {
+            match pass {
+              0 => raw_percent * 0.5,          // 映像ストリーム: 0-50%
+              1 => 50.0 + raw_percent * 0.45,  // 音声ストリーム: 50-95%
+              _ => 95.0,
+            }
+          } else {
+            raw_percent * 0.95 // 単一ストリーム: 0-95%
+          };
+
+          let percent = percent.min(95.0);
+
+          let speed = caps.get(2).map(|m| m.as_str().to_string());
+          let eta = caps.get(3).map(|m| m.as_str().to_string());
+
+          if percent > last_emitted {
+            last_emitted = percent;
+            dm_update_progress(download_id, percent);
+            reporter.progress(DownloadProgress { id: download_id.to_string(), percent, speed, eta });
+          }
+        }
+      }
+    } else if (line.contains("[Merger]")
+      || line.contains("[ExtractAudio]")
+      || line.contains("[FixupM3u8]"))
+      && last_emitted < 95.0
+    {
+      last_emitted = 95.0;
+      reporter.progress(DownloadProgress {
+        id: download_id.to_string(),
+        percent: 95.0,
+        speed: None,
+        eta: None,
+      });
+    }
+  }
+
+  let status = child.wait().await;
+  RUNNING_DOWNLOADS.lock().unwrap().remove(download_id);
+  let status = status.map_err(|e| format!("error.process_failed:{e}"))?;
+
+  let stderr_output = stderr_handle.await.unwrap_or_default();
+  download_log.write("exit", &status.to_string());
+
+  if cancelled {
+    log::info!("ダウンロードを中断しました: {download_id}");
+    return Err("error.download_cancelled".to_string());
+  }
+
+  if !status.success() {
+    log::error!("yt-dlpがエラーで終了しました: {stderr_output}");
+    // stdoutのERROR行 → stderrの非WARNING行の優先順位でエラーを特定
+    // WARNING行はyt-dlpの警告であり、エラーメッセージとして表示しない
+    let error_line = stdout_error.as_deref()
+      .or_else(|| {
+        stderr_output
+          .lines()
+          .rfind(|line| !line.trim_start().starts_with("WARNING:"))
+      })
+      .unwrap_or("unknown error");
+    return Err(map_yt_dlp_error(error_line, "error.download_failed"));
+  }
+
+  Ok(())
+}
+
+/// 完了/エラー状態のエントリを一定時間後にManagerから削除する
+/// フロントエンドが復帰するまでの猶予を持たせるため60秒待つ
+fn schedule_dm_cleanup(id: String) {
+  tokio::spawn(async move {
+    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    dm_remove(&id);
+  });
+}
+
+/// 現在のダウンロード状態（フロントエンドが復帰時に問い合わせる）
+pub fn get_download_statuses() -> Vec<DownloadStatusEntry> {
+  DOWNLOAD_MANAGER.lock().unwrap().values().cloned().collect()
+}
+
+/// Cookie関連のエラーかどうかを判定する
+fn is_cookie_error(error: &str) -> bool {
+  error.to_lowercase().contains("cookie")
+}
+
+/// エラー文字列をCookieエラー優先で適切なエラーキーに変換する
+///
+/// yt-dlp の出力にはホームディレクトリのパスや署名付き URL のトークンが含まれうるため、
+/// フロントエンド・API・CLI に返す前に伏せる。
+fn map_yt_dlp_error(error: &str, default_key: &str) -> String {
+  if is_cookie_error(error) {
+    "error.cookie_failed".to_string()
+  } else {
+    format!("{default_key}:{}", redact::redact(error))
+  }
+}
+
+/// URLからタイムスタンプパラメータ(t=XX)を安全に削除する関数
+pub(crate) fn clean_timestamp_param(url: &str) -> String {
+  if !url.contains("t=") {
+    return url.to_string();
+  }
+
+  let cleaned = RE_AMP_TIMESTAMP.replace_all(url, "").to_string();
+  let cleaned = RE_FIRST_TIMESTAMP.replace_all(&cleaned, "?").to_string();
+  let cleaned = RE_ONLY_TIMESTAMP.replace_all(&cleaned, "").to_string();
+
+  if cleaned != url {
+    log::info!("タイムスタンプを削除したURL: {cleaned}");
+  }
+
+  cleaned
+}
+
+/// ダウンロード履歴エントリを構築するヘルパー
+#[allow(clippy::too_many_arguments)]
+fn build_history_entry(
+  url: &str,
+  title: &str,
+  extension: &str,
+  best_quality: bool,
+  status: HistoryStatus,
+  file_size: Option<u64>,
+  error_message: Option<String>,
+  thumbnail: Option<String>,
+  file_path: Option<String>,
+  request: &DownloadRequest,
+  retry_of: Option<String>,
+) -> HistoryEntry {
+  let format_label = match status {
+    HistoryStatus::Success if best_quality => {
+      format!("{} best", extension.to_uppercase())
+    }
+    _ => extension.to_uppercase(),
+  };
+
+  HistoryEntry {
+    id: Uuid::new_v4().to_string(),
+    url: url.to_string(),
+    title: title.to_string(),
+    thumbnail,
+    file_path,
+    format: format_label,
+    size: file_size,
+    status,
+    error_message,
+    timestamp: Utc::now(),
+    request: Some(request.clone()),
+    retry_of,
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadedFile {
+  pub id: String,
+  pub title: String,
+  pub thumbnail: Option<String>,
+  pub filename: String,
+  pub path: String,
+  pub format: String,
+  pub size: u64,
+  pub category: String,
+  pub modified_at: String,
+}
+
+/// 保存ディレクトリ内のダウンロード済みファイル一覧を取得する
+pub fn list_downloaded_files() -> Result<Vec<DownloadedFile>, String> {
+  let app_settings = settings::load_settings().unwrap_or_default();
+  let save_path = &app_settings.save_path;
+
+  if save_path.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  let base = Path::new(save_path);
+  if !base.is_dir() {
+    return Ok(Vec::new());
+  }
+
+  // 履歴からファイルパス→サムネイルURLのマップを構築
+  let thumbnail_map: HashMap<String, String> = history::thumbnails_by_file_path().unwrap_or_default();
+
+  let mut files: Vec<DownloadedFile> = Vec::new();
+
+  // videos/ と audio/ の両方をスキャン
+  let scan_targets = [
+    (base.join(settings::SUBDIR_VIDEOS), "video", VIDEO_EXTENSIONS),
+    (base.join(settings::SUBDIR_AUDIO), "audio", AUDIO_EXTENSIONS),
+  ];
+
+  for (dir, category, extensions) in &scan_targets {
+    if !dir.is_dir() {
+      continue;
+    }
+
+    let entries = std::fs::read_dir(dir)
+      .map_err(|e| format!("error.dir_read_failed:{e}"))?;
+
+    for entry in entries.flatten() {
+      let path = entry.path();
+
+      // メタデータ取得（entry.metadata() で1回のシステムコールに統一）
+      let metadata = match entry.metadata() {
+        Ok(m) => m,
+        Err(_) => continue,
+      };
+
+      // ディレクトリはスキップ
+      if !metadata.is_file() {
+        continue;
+      }
+
+      // 隠しファイルはスキップ
+      let filename = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) if !name.starts_with('.') => name.to_string(),
+        _ => continue,
+      };
+
+      // 拡張子でフィルタ
+      let ext_lower = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+      if !extensions.contains(&ext_lower.as_str()) {
+        continue;
+      }
+
+      let size = metadata.len();
+      let modified_at = metadata
+        .modified()
+        .ok()
+        .map(|t| {
+          let datetime: DateTime<Utc> = t.into();
+          datetime.to_rfc3339()
+        })
+        .unwrap_or_default();
+
+      let title = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&filename)
+        .to_string();
+
+      let format = ext_lower.to_uppercase();
+      let full_path = path.to_string_lossy().to_string();
+
+      let thumbnail = thumbnail_map.get(&full_path).cloned();
+
+      files.push(DownloadedFile {
+        id: full_path.clone(),
+        title,
+        thumbnail,
+        filename,
+        path: full_path,
+        format,
+        size,
+        category: (*category).to_string(),
+        modified_at,
+      });
+    }
+  }
+
+  // 更新日時の降順でソート（新しいものが先頭）
+  files.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+
+  Ok(files)
+}
+
+/// yt-dlp の `-J` でメタデータを取得する
+///
+/// 失敗した場合は終了コードと stderr を含むメッセージを返す。
+async fn fetch_yt_dlp_metadata(
+  yt_dlp_path: &Path,
+  context: &YtDlpContext,
+  url: &str,
+  extra_args: &[&str],
+) -> Result<YoutubeDlOutput, String> {
+  let mut cmd = tokio::process::Command::new(yt_dlp_path);
+  cmd.args(["--socket-timeout", "15", "--flat-playlist"])
+    .args(&context.args)
+    .args(extra_args)
+    .args(["-J", url])
+    .envs(context.envs.iter().map(|(key, value)| (key, value)))
+    .stdin(std::process::Stdio::null())
+    .kill_on_drop(true);
+  #[cfg(windows)]
+  {
+    cmd.creation_flags(crate::downloader::CREATE_NO_WINDOW);
+  }
+
+  let output = cmd.output().await.map_err(|e| format!("io error: {e}"))?;
+  if !output.status.success() {
+    return Err(format!(
+      "non-zero exit code: {}, stderr: {}",
+      output.status.code().unwrap_or(1),
+      String::from_utf8_lossy(&output.stderr)
+    ));
+  }
+
+  let value: serde_json::Value =
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("json error: {e}"))?;
+  let parsed = if value["_type"] == "playlist" {
+    serde_json::from_value(value).map(|playlist| YoutubeDlOutput::Playlist(Box::new(playlist)))
+  } else {
+    serde_json::from_value(value).map(|video| YoutubeDlOutput::SingleVideo(Box::new(video)))
+  };
+  parsed.map_err(|e| format!("json error: {e}"))
+}
+
+/// タイトルとサムネイルを取得する補助関数
+async fn get_video_info(
+  url: &str,
+  yt_dlp_path: &str,
+  video_password: Option<&str>,
+  headers: Option<&HeaderOverrides>,
+) -> Result<(String, Option<String>), String> {
+  let app_settings = settings::load_settings().unwrap_or_default();
+  let context = yt_dlp_context(&app_settings, url, video_password, headers);
+  let metadata_result = fetch_yt_dlp_metadata(Path::new(yt_dlp_path), &context, url, &[]).await;
+
+  match metadata_result {
+    Ok(metadata) => {
+      let thumbnail = extract_thumbnail(&metadata);
+
+      if let Some(playlist) = metadata.clone().into_playlist() {
+        Ok((
+          sanitize_filename(&playlist.title.unwrap_or_else(|| "No Title".to_string())),
+          thumbnail,
+        ))
+      } else if let Some(video) = metadata.into_single_video() {
+        Ok((
+          sanitize_filename(&video.title.unwrap_or_else(|| "No Title".to_string())),
+          thumbnail,
+        ))
+      } else {
+        Err("error.get_title_failed".to_string())
+      }
+    }
+    Err(e) => {
+      log::error!("メタデータ取得エラー: {e}");
+      Err(map_yt_dlp_error(&e, "error.video_info_failed"))
+    }
+  }
+}
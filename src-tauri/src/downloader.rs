@@ -1,77 +1,290 @@
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::OnceLock;
-
-use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "desktop")]
 use tauri::Emitter;
-use tokio::runtime::Runtime;
-use youtube_dl::downloader::download_yt_dlp;
 
-use crate::utils::ensure_app_data_dir;
+use crate::network::http_client;
+use crate::settings::{self, BinarySettings, BinarySource, YtDlpChannel};
+use crate::utils::{ensure_app_data_dir, find_in_path};
+
+/// バイナリ識別子（セットアップステップ名・バージョン記録のキーと共通）
+const BINARY_YT_DLP: &str = "yt-dlp";
+const BINARY_FFMPEG: &str = "ffmpeg";
+const BINARY_DENO: &str = "deno";
 
 // ─── パスキャッシュ ──────────────────────────────────
 
-static YT_DLP_PATH_CACHE: OnceLock<PathBuf> = OnceLock::new();
-static FFMPEG_DIR_CACHE: OnceLock<PathBuf> = OnceLock::new();
-static DENO_PATH_CACHE: OnceLock<PathBuf> = OnceLock::new();
-static SETUP_COMPLETE: AtomicBool = AtomicBool::new(false);
+// 設定で取得元（同梱・system・custom）を変えたときに解決し直せるよう、リセット可能にする
+static YT_DLP_PATH_CACHE: Mutex<Option<PathBuf>> = Mutex::new(None);
+static FFMPEG_DIR_CACHE: Mutex<Option<PathBuf>> = Mutex::new(None);
+static DENO_PATH_CACHE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// バイナリのパスキャッシュを破棄し、次回の使用時に設定から解決し直す
+pub fn clear_path_cache() {
+  for cache in [&YT_DLP_PATH_CACHE, &FFMPEG_DIR_CACHE, &DENO_PATH_CACHE] {
+    *cache.lock().unwrap() = None;
+  }
+}
+
+/// 外部バイナリ（system / custom）を使う設定の場合は同梱版の更新を拒否する
+///
+/// 同梱版を更新しても実際に使われるのは外部バイナリのため、更新できたように見えてしまう。
+fn ensure_bundled_source(source: &BinarySource, name: &str) -> Result<(), String> {
+  match source {
+    BinarySource::Bundled => Ok(()),
+    BinarySource::System | BinarySource::Custom { .. } => {
+      Err(format!("error.binary_source_external:{name}"))
+    }
+  }
+}
 
 // ─── セットアップ進捗 ────────────────────────────────
 
+/// セットアップの進捗イベントを送るためのアプリハンドル
+#[cfg(feature = "desktop")]
+static APP_HANDLE: std::sync::OnceLock<tauri::AppHandle> = std::sync::OnceLock::new();
+
+/// 各セットアップステップの状態（ステップ名 → 状態）
+static SETUP_STATE: LazyLock<Mutex<HashMap<String, SetupStepState>>> =
+  LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// セットアップステップの状態
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "error")]
+pub enum SetupStepState {
+  InProgress,
+  Ready,
+  Error(String),
+}
+
+#[cfg(feature = "desktop")]
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SetupProgress {
   step: String,
   status: String,
+  /// ダウンロード済みバイト数（ダウンロード中のみ）
+  downloaded: Option<u64>,
+  /// 合計バイト数（サーバーが返した場合のみ）
+  total: Option<u64>,
+  /// ダウンロード速度（バイト/秒）
+  speed: Option<f64>,
 }
 
-/// セットアップ完了状態を返す
+#[cfg(feature = "desktop")]
+impl SetupProgress {
+  fn status(step: &str, status: &str) -> Self {
+    Self { step: step.into(), status: status.into(), downloaded: None, total: None, speed: None }
+  }
+}
+
+/// 全ステップが成功しているかを返す
 pub fn is_setup_done() -> bool {
-  SETUP_COMPLETE.load(Ordering::Relaxed)
+  let state = SETUP_STATE.lock().unwrap();
+  [BINARY_YT_DLP, BINARY_FFMPEG, BINARY_DENO]
+    .iter()
+    .all(|step| state.get(*step) == Some(&SetupStepState::Ready))
+}
+
+/// 各ステップの状態を返す（未実行のステップは含まない）
+pub fn get_setup_status() -> HashMap<String, SetupStepState> {
+  SETUP_STATE.lock().unwrap().clone()
 }
 
 /// セットアップステップを実行し、進捗をフロントに通知するヘルパー
-async fn run_setup_step<F, T, Fut>(handle: &tauri::AppHandle, step: &str, label: &str, f: F)
+///
+/// 同じステップが実行中の場合は二重にダウンロードしないようエラーを返す。
+#[cfg(feature = "desktop")]
+async fn run_setup_step<F, T, Fut>(handle: &tauri::AppHandle, step: &str, label: &str, f: F) -> Result<(), String>
 where
   F: FnOnce() -> Fut,
   Fut: std::future::Future<Output = Result<T, String>>,
 {
-  let _ = handle.emit(
-    "setup-progress",
-    SetupProgress { step: step.into(), status: "in_progress".into() },
-  );
-  let result = f().await;
-  let status = if result.is_ok() { "ready" } else { "error" };
-  let _ = handle.emit(
-    "setup-progress",
-    SetupProgress { step: step.into(), status: status.into() },
-  );
-  match result {
-    Ok(_) => log::info!("{label}の準備が完了しました"),
-    Err(e) => log::error!("{label}の準備に失敗しました: {e}"),
+  {
+    let mut state = SETUP_STATE.lock().unwrap();
+    if state.get(step) == Some(&SetupStepState::InProgress) {
+      return Err(format!("error.setup_step_in_progress:{step}"));
+    }
+    state.insert(step.to_string(), SetupStepState::InProgress);
   }
+
+  let _ = handle.emit("setup-progress", SetupProgress::status(step, "in_progress"));
+  let result = f().await.map(|_| ());
+  let status = if result.is_ok() { "ready" } else { "error" };
+  let _ = handle.emit("setup-progress", SetupProgress::status(step, status));
+
+  let new_state = match &result {
+    Ok(()) => {
+      log::info!("{label}の準備が完了しました");
+      SetupStepState::Ready
+    }
+    Err(e) => {
+      log::error!("{label}の準備に失敗しました: {e}");
+      SetupStepState::Error(e.clone())
+    }
+  };
+  SETUP_STATE.lock().unwrap().insert(step.to_string(), new_state);
+  result
 }
 
 /// バックグラウンドで全バイナリを準備し、進捗をフロントに通知する
+#[cfg(feature = "desktop")]
 pub fn setup_binaries(app_handle: tauri::AppHandle) {
+  let _ = APP_HANDLE.set(app_handle.clone());
+
   std::thread::spawn(move || {
-    let rt = Runtime::new().unwrap();
+    let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
       let handle = &app_handle;
 
-      tokio::join!(
-        run_setup_step(handle, "yt-dlp", "yt-dlpバイナリ", get_yt_dlp_path),
-        run_setup_step(handle, "ffmpeg", "FFmpegバイナリ", ensure_ffmpeg),
-        run_setup_step(handle, "deno", "Denoランタイム", ensure_deno),
+      let results = tokio::join!(
+        run_setup_step(handle, BINARY_YT_DLP, "yt-dlpバイナリ", get_yt_dlp_path),
+        run_setup_step(handle, BINARY_FFMPEG, "FFmpegバイナリ", ensure_ffmpeg),
+        run_setup_step(handle, BINARY_DENO, "Denoランタイム", ensure_deno),
       );
 
-      SETUP_COMPLETE.store(true, Ordering::Relaxed);
+      // 失敗したステップがあっても画面は進め、retry_setup / repair_binaries で再実行できるようにする
       let _ = handle.emit("setup-complete", ());
-      log::info!("全バイナリのセットアップが完了しました");
+      match results {
+        (Ok(()), Ok(()), Ok(())) => log::info!("全バイナリのセットアップが完了しました"),
+        _ => log::warn!("一部のバイナリのセットアップに失敗しました"),
+      }
     });
   });
 }
 
+/// 指定したステップのセットアップを再実行する
+#[cfg(feature = "desktop")]
+pub async fn retry_setup(handle: tauri::AppHandle, step: &str) -> Result<(), String> {
+  let _ = APP_HANDLE.set(handle.clone());
+
+  match step {
+    BINARY_YT_DLP => run_setup_step(&handle, BINARY_YT_DLP, "yt-dlpバイナリ", get_yt_dlp_path).await,
+    BINARY_FFMPEG => run_setup_step(&handle, BINARY_FFMPEG, "FFmpegバイナリ", ensure_ffmpeg).await,
+    BINARY_DENO => run_setup_step(&handle, BINARY_DENO, "Denoランタイム", ensure_deno).await,
+    _ => Err(format!("error.unknown_setup_step:{step}")),
+  }
+}
+
+/// 全バイナリを再ダウンロードして修復する
+///
+/// 既存のバイナリが動作していても取得し直す。置き換えは動作確認後に行うため、
+/// 失敗したバイナリは既存のものが残る。外部バイナリを使う設定のものは
+/// ダウンロードせず、パスを解決し直すだけにする。
+#[cfg(feature = "desktop")]
+pub async fn repair_binaries(handle: tauri::AppHandle) -> Result<(), String> {
+  let _ = APP_HANDLE.set(handle.clone());
+  let binaries = binary_settings();
+  clear_path_cache();
+
+  let (yt_dlp, ffmpeg, deno) = tokio::join!(
+    run_setup_step(&handle, BINARY_YT_DLP, "yt-dlpバイナリ", || async {
+      match binaries.yt_dlp_source {
+        BinarySource::Bundled => update_yt_dlp(None).await.map(drop),
+        _ => get_yt_dlp_path().await.map(drop),
+      }
+    }),
+    run_setup_step(&handle, BINARY_FFMPEG, "FFmpegバイナリ", || async {
+      match binaries.ffmpeg_source {
+        BinarySource::Bundled => update_ffmpeg(None).await.map(drop),
+        _ => ensure_ffmpeg().await.map(drop),
+      }
+    }),
+    run_setup_step(&handle, BINARY_DENO, "Denoランタイム", || async {
+      match binaries.deno_source {
+        BinarySource::Bundled => update_deno(None).await.map(drop),
+        _ => ensure_deno().await.map(drop),
+      }
+    }),
+  );
+  yt_dlp.and(ffmpeg).and(deno)
+}
+
+/// バイナリのダウンロード進捗（バイト単位）を setup-progress で通知する
+///
+/// FFmpeg と FFprobe のように複数ファイルを1ステップとして並行ダウンロードする
+/// 場合も、同じインスタンスを共有して合算した値を通知する。
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+struct ByteProgress {
+  step: &'static str,
+  downloaded: AtomicU64,
+  total: AtomicU64,
+  started: Instant,
+  last_emit: Mutex<Option<Instant>>,
+}
+
+impl ByteProgress {
+  /// 通知の最小間隔
+  #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+  const EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+  fn new(step: &'static str) -> Self {
+    Self {
+      step,
+      downloaded: AtomicU64::new(0),
+      total: AtomicU64::new(0),
+      started: Instant::now(),
+      last_emit: Mutex::new(None),
+    }
+  }
+
+  fn add_total(&self, bytes: u64) {
+    self.total.fetch_add(bytes, Ordering::Relaxed);
+  }
+
+  fn add_downloaded(&self, bytes: u64) {
+    self.downloaded.fetch_add(bytes, Ordering::Relaxed);
+    self.emit();
+  }
+
+  /// 再試行時に、失敗した試行のぶんを取り消す
+  fn rewind(&self, downloaded: u64, total: u64) {
+    self.downloaded.fetch_sub(downloaded, Ordering::Relaxed);
+    self.total.fetch_sub(total, Ordering::Relaxed);
+  }
+
+  #[cfg(feature = "desktop")]
+  fn emit(&self) {
+    let Some(handle) = APP_HANDLE.get() else {
+      return;
+    };
+
+    {
+      let mut last_emit = self.last_emit.lock().unwrap();
+      if last_emit.is_some_and(|t| t.elapsed() < Self::EMIT_INTERVAL) {
+        return;
+      }
+      *last_emit = Some(Instant::now());
+    }
+
+    let downloaded = self.downloaded.load(Ordering::Relaxed);
+    let total = self.total.load(Ordering::Relaxed);
+    let elapsed = self.started.elapsed().as_secs_f64();
+    #[allow(clippy::cast_precision_loss)]
+    let speed = (elapsed > 0.0).then(|| downloaded as f64 / elapsed);
+
+    let _ = handle.emit(
+      "setup-progress",
+      SetupProgress {
+        step: self.step.into(),
+        status: "in_progress".into(),
+        downloaded: Some(downloaded),
+        total: (total > 0).then_some(total),
+        speed,
+      },
+    );
+  }
+
+  /// CLI のみのビルドでは通知先のアプリがないため何もしない
+  #[cfg(not(feature = "desktop"))]
+  fn emit(&self) {}
+}
+
 // ─── Windows コンソール非表示ヘルパー ─────────────────
 
 /// Windows の CREATE_NO_WINDOW フラグ
@@ -95,28 +308,33 @@ pub(crate) fn silent_command(program: &std::path::Path) -> std::process::Command
 /// yt-dlpバイナリのパスを取得する関数
 /// 初回はバージョンチェックを行い、以降はキャッシュから返す
 pub async fn get_yt_dlp_path() -> Result<PathBuf, String> {
-  if let Some(cached) = YT_DLP_PATH_CACHE.get() {
-    return Ok(cached.clone());
+  if let Some(cached) = YT_DLP_PATH_CACHE.lock().unwrap().clone() {
+    return Ok(cached);
   }
 
   let path = get_yt_dlp_path_uncached().await?;
-  let _ = YT_DLP_PATH_CACHE.set(path.clone());
+  *YT_DLP_PATH_CACHE.lock().unwrap() = Some(path.clone());
   Ok(path)
 }
 
+fn yt_dlp_binary_name() -> &'static str {
+  if cfg!(windows) { "yt-dlp.exe" } else { "yt-dlp" }
+}
+
 /// yt-dlpバイナリのパスを取得する（キャッシュなし）
 async fn get_yt_dlp_path_uncached() -> Result<PathBuf, String> {
-  let app_data_dir = ensure_app_data_dir()?;
+  let binaries = binary_settings();
+  if let Some(path) = resolve_external_binary(&binaries.yt_dlp_source, yt_dlp_binary_name(), "--version")? {
+    log::info!("外部のyt-dlpを使用します: {}", path.display());
+    return Ok(path);
+  }
 
-  let yt_dlp_path = app_data_dir.join(if cfg!(windows) {
-    "yt-dlp.exe"
-  } else {
-    "yt-dlp"
-  });
+  let app_data_dir = ensure_app_data_dir()?;
+  let yt_dlp_path = app_data_dir.join(yt_dlp_binary_name());
 
   if !yt_dlp_path.exists() {
     log::info!("yt-dlpバイナリをダウンロードしています...");
-    return download_and_setup_yt_dlp(&app_data_dir).await;
+    return install_yt_dlp(&app_data_dir, &binaries, None).await;
   }
 
   log::info!("既存のyt-dlpバイナリを使用します: {}", yt_dlp_path.display());
@@ -129,6 +347,19 @@ async fn get_yt_dlp_path_uncached() -> Result<PathBuf, String> {
     Ok(output) if output.status.success() => {
       let version = String::from_utf8_lossy(&output.stdout);
       log::info!("yt-dlpバージョン: {}", version.trim());
+
+      if needs_pinned_install(BINARY_YT_DLP, binaries.yt_dlp_version.as_deref())
+        || needs_channel_install(binaries.yt_dlp_channel)
+      {
+        log::info!(
+          "設定のバージョン・チャンネル ({}) のyt-dlpに切り替えます",
+          binaries.yt_dlp_channel.as_str()
+        );
+        match install_yt_dlp(&app_data_dir, &binaries, None).await {
+          Ok(path) => return Ok(path),
+          Err(e) => log::error!("設定のバージョンのyt-dlpの取得に失敗しました。既存のバイナリを使用します: {e}"),
+        }
+      }
       Ok(yt_dlp_path)
     }
     _ => {
@@ -138,7 +369,7 @@ async fn get_yt_dlp_path_uncached() -> Result<PathBuf, String> {
         log::error!("古いyt-dlpバイナリの削除に失敗しました: {e}");
       }
 
-      match download_and_setup_yt_dlp(&app_data_dir).await {
+      match install_yt_dlp(&app_data_dir, &binaries, None).await {
         Ok(path) => Ok(path),
         Err(e) => {
           log::error!("yt-dlpバイナリの再ダウンロードに失敗しました: {e}");
@@ -150,63 +381,47 @@ async fn get_yt_dlp_path_uncached() -> Result<PathBuf, String> {
   }
 }
 
-/// yt-dlpを最新版に更新する（既存バイナリを削除して再ダウンロード）
-pub async fn update_yt_dlp() -> Result<String, String> {
+/// yt-dlpを更新する
+///
+/// `target_version` を省略した場合は固定バージョン、それもなければ
+/// 設定されたチャンネルの最新版を取得する。新しいバイナリの動作確認後に
+/// 置き換えるため、失敗しても既存のバイナリは残る。
+/// 外部バイナリを使う設定の場合は `error.binary_source_external` を返す。
+pub async fn update_yt_dlp(target_version: Option<String>) -> Result<String, String> {
+  let binaries = binary_settings();
+  ensure_bundled_source(&binaries.yt_dlp_source, BINARY_YT_DLP)?;
   let app_data_dir = ensure_app_data_dir()?;
 
-  let yt_dlp_path = app_data_dir.join(if cfg!(windows) {
-    "yt-dlp.exe"
-  } else {
-    "yt-dlp"
-  });
-
-  if yt_dlp_path.exists() {
-    std::fs::remove_file(&yt_dlp_path)
-      .map_err(|e| format!("error.ytdlp_delete_failed:{e}"))?;
-    log::info!("古いyt-dlpバイナリを削除しました");
-  }
+  let path = install_yt_dlp(&app_data_dir, &binaries, target_version.as_deref()).await?;
 
-  let path = download_and_setup_yt_dlp(&app_data_dir).await?;
-
-  let version = silent_command(&path)
-    .arg("--version")
-    .output()
-    .ok()
-    .filter(|o| o.status.success())
-    .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-    .unwrap_or_else(|| "unknown".to_string());
+  let version = binary_version(&path, "--version").unwrap_or_else(|| "unknown".to_string());
 
   log::info!("yt-dlpを更新しました: {version}");
   Ok(version)
 }
 
-/// yt-dlpのバージョンを取得する
-pub async fn get_yt_dlp_version() -> Result<String, String> {
-  let path = get_yt_dlp_path().await?;
-
-  let output = silent_command(&path)
-    .arg("--version")
-    .output()
-    .map_err(|e| format!("error.ytdlp_version_failed:{e}"))?;
-
-  if output.status.success() {
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-  } else {
-    Err("error.ytdlp_version_not_found".to_string())
-  }
-}
-
-/// yt-dlpをダウンロードし、Unix環境では実行権限を付与する
-async fn download_and_setup_yt_dlp(app_data_dir: &std::path::Path) -> Result<PathBuf, String> {
-  let path = download_yt_dlp(app_data_dir)
+/// yt-dlpをダウンロードしてインストールする
+///
+/// 優先順: 引数のバージョン > 固定バージョン > チャンネルの最新版
+async fn install_yt_dlp(
+  app_data_dir: &Path,
+  binaries: &BinarySettings,
+  target_version: Option<&str>,
+) -> Result<PathBuf, String> {
+  let release = ReleaseSource::new(yt_dlp_repo(binaries.yt_dlp_channel), binaries.yt_dlp_mirror.as_deref());
+  let tag = release
+    .resolve_tag(target_version.or(binaries.yt_dlp_version.as_deref()))
+    .await;
+  let url = release.asset_url(tag.as_deref(), yt_dlp_binary_name());
+  let path = app_data_dir.join(yt_dlp_binary_name());
+
+  download_binary(&url, &path, "--version", BINARY_YT_DLP)
     .await
     .map_err(|e| format!("error.ytdlp_download_failed:{e}"))?;
+  record_installed_tag(BINARY_YT_DLP, tag.as_deref());
+  record_installed_tag(MANIFEST_YT_DLP_CHANNEL, Some(binaries.yt_dlp_channel.as_str()));
 
   log::info!("yt-dlpバイナリをダウンロードしました: {}", path.display());
-
-  #[cfg(unix)]
-  set_executable(&path)?;
-
   Ok(path)
 }
 
@@ -224,27 +439,42 @@ pub fn get_ffmpeg_dir() -> Result<PathBuf, String> {
 /// FFmpegバイナリの準備（存在しなければダウンロード）
 /// 初回はバージョンチェックを行い、以降はキャッシュから返す
 pub async fn ensure_ffmpeg() -> Result<PathBuf, String> {
-  if let Some(cached) = FFMPEG_DIR_CACHE.get() {
-    return Ok(cached.clone());
+  if let Some(cached) = FFMPEG_DIR_CACHE.lock().unwrap().clone() {
+    return Ok(cached);
   }
 
   let result = ensure_ffmpeg_uncached().await?;
-  let _ = FFMPEG_DIR_CACHE.set(result.clone());
+  *FFMPEG_DIR_CACHE.lock().unwrap() = Some(result.clone());
   Ok(result)
 }
 
 /// FFmpegバイナリの準備（キャッシュなし）
 async fn ensure_ffmpeg_uncached() -> Result<PathBuf, String> {
+  let binaries = binary_settings();
+  if let Some(dir) = resolve_external_ffmpeg_dir(&binaries.ffmpeg_source)? {
+    log::info!("外部のFFmpegを使用します: {}", dir.display());
+    return Ok(dir);
+  }
+
   let ffmpeg_dir = get_ffmpeg_dir()?;
   let ffmpeg_path = ffmpeg_dir.join(ffmpeg_binary_name());
   let ffprobe_path = ffmpeg_dir.join(ffprobe_binary_name());
+  let pinned = binaries.ffmpeg_version;
 
   if ffmpeg_path.exists() && ffprobe_path.exists() {
     match silent_command(&ffmpeg_path).arg("-version").output() {
       Ok(output) if output.status.success() => {
         let version = String::from_utf8_lossy(&output.stdout);
         let first_line = version.lines().next().unwrap_or("unknown");
-        log::info!("既存のFFmpegを使用: {first_line}");
+        if !needs_pinned_install(BINARY_FFMPEG, pinned.as_deref()) {
+          log::info!("既存のFFmpegを使用: {first_line}");
+          return Ok(ffmpeg_dir);
+        }
+        log::info!("固定バージョンのFFmpegに切り替えます: {first_line}");
+        let mirror = binaries.ffmpeg_mirror.as_deref();
+        if let Err(e) = download_ffmpeg_binaries(&ffmpeg_dir, pinned.as_deref(), mirror).await {
+          log::error!("固定バージョンのFFmpegの取得に失敗しました。既存のバイナリを使用します: {e}");
+        }
         return Ok(ffmpeg_dir);
       }
       _ => {
@@ -256,7 +486,7 @@ async fn ensure_ffmpeg_uncached() -> Result<PathBuf, String> {
   }
 
   log::info!("FFmpegをダウンロードしています...");
-  download_ffmpeg_binaries(&ffmpeg_dir).await?;
+  download_ffmpeg_binaries(&ffmpeg_dir, pinned.as_deref(), binaries.ffmpeg_mirror.as_deref()).await?;
   Ok(ffmpeg_dir)
 }
 
@@ -286,73 +516,83 @@ fn ffmpeg_platform_suffix() -> Result<&'static str, String> {
 }
 
 /// eugeneware/ffmpeg-static からFFmpegとFFprobeをダウンロードして展開する
-async fn download_ffmpeg_binaries(dest_dir: &std::path::Path) -> Result<(), String> {
+///
+/// `pinned` が指定されていればそのリリースタグを、なければ最新版を取得する。
+async fn download_ffmpeg_binaries(
+  dest_dir: &Path,
+  pinned: Option<&str>,
+  mirror: Option<&str>,
+) -> Result<(), String> {
   let suffix = ffmpeg_platform_suffix()?;
-  let base_url = "https://github.com/eugeneware/ffmpeg-static/releases/latest/download";
+  let release = ReleaseSource::new(FFMPEG_REPO, mirror);
+  let tag = release.resolve_tag(pinned).await;
 
-  let ffmpeg_url = format!("{base_url}/ffmpeg-{suffix}.gz");
-  let ffprobe_url = format!("{base_url}/ffprobe-{suffix}.gz");
+  let ffmpeg_url = release.asset_url(tag.as_deref(), &format!("ffmpeg-{suffix}.gz"));
+  let ffprobe_url = release.asset_url(tag.as_deref(), &format!("ffprobe-{suffix}.gz"));
 
   let ffmpeg_path = dest_dir.join(ffmpeg_binary_name());
   let ffprobe_path = dest_dir.join(ffprobe_binary_name());
 
+  let progress = ByteProgress::new(BINARY_FFMPEG);
   let (ffmpeg_result, ffprobe_result) = tokio::join!(
-    download_and_decompress_gz(&ffmpeg_url, &ffmpeg_path),
-    download_and_decompress_gz(&ffprobe_url, &ffprobe_path),
+    download_and_decompress_gz(&ffmpeg_url, &ffmpeg_path, &progress),
+    download_and_decompress_gz(&ffprobe_url, &ffprobe_path, &progress),
   );
 
-  ffmpeg_result?;
-  ffprobe_result?;
+  // 両方揃ってから置き換え、FFmpeg と FFprobe のバージョンがずれないようにする
+  let staged = match (ffmpeg_result, ffprobe_result) {
+    (Ok(ffmpeg), Ok(ffprobe)) => [ffmpeg, ffprobe],
+    (Err(e), other) | (other, Err(e)) => {
+      if let Ok(tmp) = other {
+        let _ = std::fs::remove_file(tmp);
+      }
+      return Err(e);
+    }
+  };
+  swap_staged_binaries(
+    &[(&staged[0], &ffmpeg_path), (&staged[1], &ffprobe_path)],
+    "-version",
+  )
+  .map_err(|e| format!("error.ffmpeg_file_write_failed:{e}"))?;
+  record_installed_tag(BINARY_FFMPEG, tag.as_deref());
 
   log::info!("FFmpegとFFprobeをインストールしました: {}", dest_dir.display());
   Ok(())
 }
 
-/// .gz ファイルをダウンロードしてストリーミング展開する
+/// .gz ファイルをダウンロードして一時ファイルへ展開する
 ///
-/// 一時ファイルに書き出してからリネームすることで、
-/// 途中失敗時に不完全なバイナリが残ることを防ぐ。
+/// 展開先の一時ファイルのパスを返す。置き換えは呼び出し側で
+/// `swap_staged_binaries` を使って行い、途中失敗時に不完全なバイナリが残ることを防ぐ。
 async fn download_and_decompress_gz(
   url: &str,
-  dest_path: &std::path::Path,
-) -> Result<(), String> {
-  use flate2::write::GzDecoder;
-  use futures_util::StreamExt;
-  use std::io::Write;
+  dest_path: &Path,
+  progress: &ByteProgress,
+) -> Result<PathBuf, String> {
+  use flate2::read::GzDecoder;
 
-  log::info!("ダウンロード中: {url}");
-  let response = reqwest::get(url)
+  let gz_path = dest_path.with_extension("gz.download");
+  download_to_file(url, &gz_path, progress)
     .await
     .map_err(|e| format!("error.ffmpeg_download_failed:{e}"))?;
 
-  if !response.status().is_success() {
-    return Err(format!("error.ffmpeg_download_http_failed:HTTP {}", response.status()));
-  }
-
-  let tmp_path = dest_path.with_extension("tmp");
-  let file = std::fs::File::create(&tmp_path)
-    .map_err(|e| format!("error.ffmpeg_file_create_failed:{e}"))?;
-  let mut decoder = GzDecoder::new(file);
-
-  let mut stream = response.bytes_stream();
-  while let Some(chunk) = stream.next().await {
-    let chunk = chunk.map_err(|e| format!("error.ffmpeg_response_failed:{e}"))?;
-    decoder
-      .write_all(&chunk)
+  let tmp_path = staging_path(dest_path);
+  let result = (|| {
+    let gz_file = std::fs::File::open(&gz_path)
+      .map_err(|e| format!("error.ffmpeg_file_create_failed:{e}"))?;
+    let mut out = std::fs::File::create(&tmp_path)
+      .map_err(|e| format!("error.ffmpeg_file_create_failed:{e}"))?;
+    std::io::copy(&mut GzDecoder::new(gz_file), &mut out)
       .map_err(|e| format!("error.ffmpeg_decompress_failed:{e}"))?;
-  }
-
-  decoder
-    .finish()
-    .map_err(|e| format!("error.ffmpeg_decompress_failed:{e}"))?;
-
-  std::fs::rename(&tmp_path, dest_path)
-    .map_err(|e| format!("error.ffmpeg_file_write_failed:{e}"))?;
+    Ok::<(), String>(())
+  })();
+  let _ = std::fs::remove_file(&gz_path);
+  result?;
 
   #[cfg(unix)]
-  set_executable(dest_path)?;
+  set_executable(&tmp_path)?;
 
-  Ok(())
+  Ok(tmp_path)
 }
 
 // ─── Deno (JSランタイム) ───────────────────────────
@@ -369,27 +609,44 @@ pub fn get_deno_dir() -> Result<PathBuf, String> {
 /// Denoバイナリの準備（存在しなければダウンロード）
 /// 初回はバージョンチェックを行い、以降はキャッシュから返す
 pub async fn ensure_deno() -> Result<PathBuf, String> {
-  if let Some(cached) = DENO_PATH_CACHE.get() {
-    return Ok(cached.clone());
+  if let Some(cached) = DENO_PATH_CACHE.lock().unwrap().clone() {
+    return Ok(cached);
   }
 
   let result = ensure_deno_uncached().await?;
-  let _ = DENO_PATH_CACHE.set(result.clone());
+  *DENO_PATH_CACHE.lock().unwrap() = Some(result.clone());
   Ok(result)
 }
 
 /// Denoバイナリの準備（キャッシュなし）
 async fn ensure_deno_uncached() -> Result<PathBuf, String> {
+  let binaries = binary_settings();
+  if let Some(path) = resolve_external_binary(&binaries.deno_source, deno_binary_name(), "--version")? {
+    log::info!("外部のDenoを使用します: {}", path.display());
+    return Ok(path);
+  }
+
   let deno_dir = get_deno_dir()?;
   let deno_path = deno_dir.join(deno_binary_name());
+  let pinned = binaries.deno_version;
 
   if deno_path.exists() {
     match silent_command(&deno_path).arg("--version").output() {
       Ok(output) if output.status.success() => {
         let version = String::from_utf8_lossy(&output.stdout);
         let first_line = version.lines().next().unwrap_or("unknown");
-        log::info!("既存のDenoを使用: {first_line}");
-        return Ok(deno_path);
+        if !needs_pinned_install(BINARY_DENO, pinned.as_deref()) {
+          log::info!("既存のDenoを使用: {first_line}");
+          return Ok(deno_path);
+        }
+        log::info!("固定バージョンのDenoに切り替えます: {first_line}");
+        match download_deno(&deno_dir, pinned.as_deref(), binaries.deno_mirror.as_deref()).await {
+          Ok(path) => return Ok(path),
+          Err(e) => {
+            log::error!("固定バージョンのDenoの取得に失敗しました。既存のバイナリを使用します: {e}");
+            return Ok(deno_path);
+          }
+        }
       }
       _ => {
         log::warn!("既存のDenoバイナリが壊れています。再ダウンロードします");
@@ -399,7 +656,7 @@ async fn ensure_deno_uncached() -> Result<PathBuf, String> {
   }
 
   log::info!("Denoをダウンロードしています...");
-  download_deno(&deno_dir).await
+  download_deno(&deno_dir, pinned.as_deref(), binaries.deno_mirror.as_deref()).await
 }
 
 /// プラットフォームに応じたDenoバイナリ名
@@ -407,8 +664,8 @@ fn deno_binary_name() -> &'static str {
   if cfg!(windows) { "deno.exe" } else { "deno" }
 }
 
-/// プラットフォームに応じたDenoダウンロードURL
-fn deno_download_url() -> Result<String, String> {
+/// プラットフォームに応じたDenoのアーカイブ名
+fn deno_asset_name() -> Result<&'static str, String> {
   let target = if cfg!(target_os = "macos") && cfg!(target_arch = "aarch64") {
     "deno-aarch64-apple-darwin.zip"
   } else if cfg!(target_os = "macos") && cfg!(target_arch = "x86_64") {
@@ -423,59 +680,692 @@ fn deno_download_url() -> Result<String, String> {
     return Err("error.deno_not_supported".to_string());
   };
 
-  Ok(format!(
-    "https://github.com/denoland/deno/releases/latest/download/{target}"
-  ))
+  Ok(target)
 }
 
 /// DenoをGitHub Releasesからダウンロードして展開する
-async fn download_deno(dest_dir: &std::path::Path) -> Result<PathBuf, String> {
-  let url = deno_download_url()?;
+///
+/// `pinned` が指定されていればそのリリースタグを、なければ最新版を取得する。
+async fn download_deno(dest_dir: &Path, pinned: Option<&str>, mirror: Option<&str>) -> Result<PathBuf, String> {
+  let release = ReleaseSource::new(DENO_REPO, mirror);
+  let tag = release.resolve_tag(pinned).await;
+  let url = release.asset_url(tag.as_deref(), deno_asset_name()?);
   log::info!("Denoダウンロード元: {url}");
 
-  let response = reqwest::get(&url)
+  let binary_name = deno_binary_name();
+  let deno_path = dest_dir.join(binary_name);
+
+  // アーカイブはメモリに載せず一時ファイルへストリーミングで保存する
+  let zip_path = deno_path.with_extension("zip.download");
+  download_to_file(&url, &zip_path, &ByteProgress::new(BINARY_DENO))
     .await
     .map_err(|e| format!("error.deno_download_failed:{e}"))?;
 
-  if !response.status().is_success() {
-    return Err(format!("error.deno_download_http_failed:HTTP {}", response.status()));
+  let result = extract_deno_zip(&zip_path, &deno_path);
+  let _ = std::fs::remove_file(&zip_path);
+  if result.is_ok() {
+    record_installed_tag(BINARY_DENO, tag.as_deref());
+    log::info!("Denoをインストールしました: {}", deno_path.display());
   }
+  result
+}
 
-  let bytes = response
-    .bytes()
-    .await
-    .map_err(|e| format!("error.deno_response_failed:{e}"))?;
-
-  let cursor = std::io::Cursor::new(bytes);
-  let mut archive = zip::ZipArchive::new(cursor)
+/// ダウンロードしたDenoのZIPからバイナリを展開し、動作確認後に置き換える
+fn extract_deno_zip(zip_path: &Path, deno_path: &Path) -> Result<PathBuf, String> {
+  let file = std::fs::File::open(zip_path)
+    .map_err(|e| format!("error.deno_zip_failed:{e}"))?;
+  let mut archive = zip::ZipArchive::new(file)
     .map_err(|e| format!("error.deno_zip_failed:{e}"))?;
 
   let binary_name = deno_binary_name();
-  let deno_path = dest_dir.join(binary_name);
+  let tmp_path = staging_path(deno_path);
 
-  // ZIP内からDenoバイナリを探して展開
+  // ZIP内からDenoバイナリを探して一時ファイルに展開し、動作確認後に置き換える
   for i in 0..archive.len() {
     let mut file = archive
       .by_index(i)
       .map_err(|e| format!("error.deno_zip_entry_failed:{e}"))?;
 
     if file.name().ends_with(binary_name) {
-      let mut out = std::fs::File::create(&deno_path)
+      let mut out = std::fs::File::create(&tmp_path)
         .map_err(|e| format!("error.deno_file_create_failed:{e}"))?;
       std::io::copy(&mut file, &mut out)
         .map_err(|e| format!("error.deno_file_write_failed:{e}"))?;
+      drop(out);
 
       #[cfg(unix)]
-      set_executable(&deno_path)?;
+      set_executable(&tmp_path)?;
+
+      swap_staged_binaries(&[(&tmp_path, deno_path)], "--version")
+        .map_err(|e| format!("error.deno_file_write_failed:{e}"))?;
 
-      log::info!("Denoをインストールしました: {}", deno_path.display());
-      return Ok(deno_path);
+      return Ok(deno_path.to_path_buf());
     }
   }
 
   Err("error.deno_not_found_in_zip".to_string())
 }
 
+// ─── リリース取得 ──────────────────────────────────
+
+const GITHUB_URL: &str = "https://github.com";
+const GITHUB_API_URL: &str = "https://api.github.com";
+const FFMPEG_REPO: &str = "eugeneware/ffmpeg-static";
+const DENO_REPO: &str = "denoland/deno";
+
+/// チャンネルに対応する yt-dlp のリポジトリ
+fn yt_dlp_repo(channel: YtDlpChannel) -> &'static str {
+  match channel {
+    YtDlpChannel::Stable => "yt-dlp/yt-dlp",
+    YtDlpChannel::Nightly => "yt-dlp/yt-dlp-nightly-builds",
+    YtDlpChannel::Master => "yt-dlp/yt-dlp-master-builds",
+  }
+}
+
+/// リリースの取得元（GitHub またはミラー）
+///
+/// ミラーのベースURLは `https://github.com/{owner}/{repo}/releases` に相当し、
+/// `{mirror}/download/{tag}/{asset}` と `{mirror}/latest/download/{asset}` で
+/// アセットを取得できることを前提とする。
+struct ReleaseSource<'a> {
+  repo: &'a str,
+  mirror: Option<&'a str>,
+}
+
+impl<'a> ReleaseSource<'a> {
+  fn new(repo: &'a str, mirror: Option<&'a str>) -> Self {
+    let mirror = mirror.map(|m| m.trim().trim_end_matches('/')).filter(|m| !m.is_empty());
+    Self { repo, mirror }
+  }
+
+  /// アセットのURLを組み立てる（タグ未指定なら最新版）
+  fn asset_url(&self, tag: Option<&str>, asset: &str) -> String {
+    let base = match self.mirror {
+      Some(mirror) => mirror.to_string(),
+      None => format!("{GITHUB_URL}/{}/releases", self.repo),
+    };
+    match tag {
+      Some(tag) => format!("{base}/download/{tag}/{asset}"),
+      None => format!("{base}/latest/download/{asset}"),
+    }
+  }
+
+  /// インストールするリリースタグを決定する
+  ///
+  /// 固定バージョンがあればそれを使い、なければ GitHub API に最新タグを問い合わせる。
+  /// ミラー使用時（github.com に届かない環境を想定）や問い合わせ失敗時は
+  /// `None`（latest URL からダウンロード）を返す。
+  async fn resolve_tag(&self, pinned: Option<&str>) -> Option<String> {
+    if let Some(tag) = pinned {
+      return Some(tag.to_string());
+    }
+    if self.mirror.is_some() {
+      return None;
+    }
+
+    match fetch_latest_tag(self.repo).await {
+      Ok(tag) => Some(tag),
+      Err(e) => {
+        log::warn!("最新リリースの取得に失敗しました ({}): {e}", self.repo);
+        None
+      }
+    }
+  }
+}
+
+/// GitHub API からリポジトリの最新リリースタグを取得する
+async fn fetch_latest_tag(repo: &str) -> Result<String, String> {
+  #[derive(Deserialize)]
+  struct Release {
+    tag_name: String,
+  }
+
+  let url = format!("{GITHUB_API_URL}/repos/{repo}/releases/latest");
+  let release: Release = http_client()?
+    .get(&url)
+    .send()
+    .await
+    .and_then(reqwest::Response::error_for_status)
+    .map_err(|e| format!("error.release_fetch_failed:{e}"))?
+    .json()
+    .await
+    .map_err(|e| format!("error.release_fetch_failed:{e}"))?;
+
+  Ok(release.tag_name)
+}
+
+// ─── インストール済みバージョンの記録 ─────────────────
+
+/// 記録ファイルの読み書きを直列化するロック（セットアップは並行実行されるため）
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// バイナリ識別子 → インストール済みリリースタグ（と yt-dlp のチャンネル）の記録ファイル
+fn manifest_path() -> Result<PathBuf, String> {
+  Ok(ensure_app_data_dir()?.join("binaries.json"))
+}
+
+fn load_manifest() -> HashMap<String, String> {
+  manifest_path()
+    .ok()
+    .and_then(|path| std::fs::read_to_string(path).ok())
+    .and_then(|content| serde_json::from_str(&content).ok())
+    .unwrap_or_default()
+}
+
+/// インストールしたリリースタグを記録する（タグ不明の場合は記録を消す）
+fn record_installed_tag(name: &str, tag: Option<&str>) {
+  let _guard = MANIFEST_LOCK.lock().unwrap();
+  let mut manifest = load_manifest();
+  match tag {
+    Some(tag) => manifest.insert(name.to_string(), tag.to_string()),
+    None => manifest.remove(name),
+  };
+
+  let result = manifest_path().and_then(|path| {
+    let content = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())
+  });
+  if let Err(e) = result {
+    log::warn!("バイナリのバージョン記録に失敗しました: {e}");
+  }
+}
+
+/// 固定バージョンが指定されており、インストール済みのタグと異なるか
+fn needs_pinned_install(name: &str, pinned: Option<&str>) -> bool {
+  pinned.is_some_and(|pin| load_manifest().get(name).map(String::as_str) != Some(pin))
+}
+
+/// インストールした yt-dlp のチャンネルを記録するキー
+const MANIFEST_YT_DLP_CHANNEL: &str = "yt-dlp.channel";
+
+impl YtDlpChannel {
+  fn as_str(self) -> &'static str {
+    match self {
+      YtDlpChannel::Stable => "stable",
+      YtDlpChannel::Nightly => "nightly",
+      YtDlpChannel::Master => "master",
+    }
+  }
+}
+
+/// インストール済みの yt-dlp が設定と異なるチャンネルのものか
+///
+/// チャンネルの記録がない場合（記録を始める前のインストール）は stable とみなす。
+fn needs_channel_install(channel: YtDlpChannel) -> bool {
+  let manifest = load_manifest();
+  let installed = manifest
+    .get(MANIFEST_YT_DLP_CHANNEL)
+    .map_or(YtDlpChannel::Stable.as_str(), String::as_str);
+  installed != channel.as_str()
+}
+
+/// 現在のバイナリ設定を読み込む
+fn binary_settings() -> BinarySettings {
+  settings::load_settings().unwrap_or_default().binaries
+}
+
+// ─── 外部バイナリ ──────────────────────────────────
+
+/// 設定された外部バイナリ（system / custom）のパス（同梱版を使う設定なら `None`）
+///
+/// 実行できるかは確認しない。system 設定で PATH に見つからない場合はバイナリ名だけを返す。
+fn external_binary_path(source: &BinarySource, binary_name: &str) -> Option<PathBuf> {
+  match source {
+    BinarySource::Bundled => None,
+    BinarySource::System => Some(
+      ensure_app_data_dir()
+        .ok()
+        .and_then(|app_data_dir| find_in_path(binary_name, &app_data_dir))
+        .unwrap_or_else(|| PathBuf::from(binary_name)),
+    ),
+    BinarySource::Custom { path } => {
+      let path = PathBuf::from(path);
+      Some(if path.is_dir() { path.join(binary_name) } else { path })
+    }
+  }
+}
+
+/// 設定された外部バイナリ（system / custom）のパスを解決し、実行できるか確認する
+///
+/// 同梱版を使う設定の場合は `None` を返す。外部バイナリが見つからない・実行できない
+/// 場合は、別のバイナリが黙って使われないよう同梱版には切り替えず
+/// `error.binary_invalid` を返す。
+fn resolve_external_binary(
+  source: &BinarySource,
+  binary_name: &str,
+  version_arg: &str,
+) -> Result<Option<PathBuf>, String> {
+  let Some(path) = external_binary_path(source, binary_name) else {
+    return Ok(None);
+  };
+  if binary_version(&path, version_arg).is_none() {
+    log::error!("設定された {binary_name} が見つからないか、実行できません: {}", path.display());
+    return Err(format!("error.binary_invalid:{}", path.display()));
+  }
+  Ok(Some(path))
+}
+
+/// 外部の FFmpeg を使う場合、ffmpeg と ffprobe を含むディレクトリを返す
+fn resolve_external_ffmpeg_dir(source: &BinarySource) -> Result<Option<PathBuf>, String> {
+  let Some(ffmpeg) = resolve_external_binary(source, ffmpeg_binary_name(), "-version")? else {
+    return Ok(None);
+  };
+  let dir = ffmpeg.parent().map(Path::to_path_buf).unwrap_or_default();
+
+  let ffprobe = dir.join(ffprobe_binary_name());
+  if binary_version(&ffprobe, "-version").is_none() {
+    log::error!("ffmpeg と同じ場所に ffprobe が見つかりません: {}", dir.display());
+    return Err(format!("error.binary_invalid:{}", ffprobe.display()));
+  }
+  Ok(Some(dir))
+}
+
+/// 実際に使用される yt-dlp のパス（ダウンロード・実行確認は行わない）
+fn active_yt_dlp_path(binaries: &BinarySettings) -> Result<PathBuf, String> {
+  match external_binary_path(&binaries.yt_dlp_source, yt_dlp_binary_name()) {
+    Some(path) => Ok(path),
+    None => Ok(ensure_app_data_dir()?.join(yt_dlp_binary_name())),
+  }
+}
+
+/// 実際に使用される FFmpeg のディレクトリ（ダウンロード・実行確認は行わない）
+fn active_ffmpeg_dir(binaries: &BinarySettings) -> Result<PathBuf, String> {
+  match external_binary_path(&binaries.ffmpeg_source, ffmpeg_binary_name()) {
+    Some(path) => Ok(path.parent().map(Path::to_path_buf).unwrap_or_default()),
+    None => get_ffmpeg_dir(),
+  }
+}
+
+/// 実際に使用される Deno のパス（ダウンロード・実行確認は行わない）
+fn active_deno_path(binaries: &BinarySettings) -> Result<PathBuf, String> {
+  match external_binary_path(&binaries.deno_source, deno_binary_name()) {
+    Some(path) => Ok(path),
+    None => Ok(get_deno_dir()?.join(deno_binary_name())),
+  }
+}
+
+/// yt-dlp の `--ffmpeg-location` に渡す FFmpeg バイナリのパス
+pub fn ffmpeg_location() -> Option<PathBuf> {
+  let cached = FFMPEG_DIR_CACHE.lock().unwrap().clone();
+  let dir = match cached {
+    Some(dir) => dir,
+    None => active_ffmpeg_dir(&binary_settings()).ok()?,
+  };
+  let ffmpeg = dir.join(ffmpeg_binary_name());
+  ffmpeg.exists().then_some(ffmpeg)
+}
+
+/// yt-dlp の `--js-runtimes deno:<path>` に渡す Deno のパス
+///
+/// PATH は起動時にしか設定されないため、Deno の取得元を変更した場合もここで
+/// 解決し直したパスを明示的に渡す。
+pub fn deno_location() -> Option<PathBuf> {
+  let cached = DENO_PATH_CACHE.lock().unwrap().clone();
+  let deno = match cached {
+    Some(path) => path,
+    None => active_deno_path(&binary_settings()).ok()?,
+  };
+  deno.exists().then_some(deno)
+}
+
+/// PATH の先頭に追加する FFmpeg / Deno のディレクトリ
+///
+/// 起動時に設定に従って並べる（yt-dlp には `deno_location` のパスも明示的に渡す）。
+/// system 設定のものは既存の PATH に任せるので含めない。起動時に呼ばれるため
+/// バイナリの実行確認は行わない。
+pub fn binary_search_dirs() -> Vec<PathBuf> {
+  let binaries = binary_settings();
+  let bundled = [(binaries.ffmpeg_source, get_ffmpeg_dir()), (binaries.deno_source, get_deno_dir())];
+
+  bundled
+    .into_iter()
+    .filter_map(|(source, bundled_dir)| match source {
+      BinarySource::Bundled => bundled_dir.ok(),
+      BinarySource::System => None,
+      BinarySource::Custom { path } => {
+        let path = PathBuf::from(path);
+        if path.is_dir() { Some(path) } else { path.parent().map(Path::to_path_buf) }
+      }
+    })
+    .collect()
+}
+
+// ─── バージョン情報 ────────────────────────────────
+
+/// 1つのバイナリのバージョン情報
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryVersionInfo {
+  /// バイナリ自身が報告するバージョン（未インストールなら None）
+  pub installed: Option<String>,
+  /// 設定で固定されたリリースタグ
+  pub pinned: Option<String>,
+  /// 取得可能な最新リリースタグ（問い合わせ失敗時は None）
+  pub latest: Option<String>,
+}
+
+/// 全バイナリのバージョン情報
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryVersionReport {
+  pub yt_dlp_channel: YtDlpChannel,
+  pub yt_dlp: BinaryVersionInfo,
+  pub ffmpeg: BinaryVersionInfo,
+  pub deno: BinaryVersionInfo,
+}
+
+/// インストール済み・固定・最新のバージョンを全バイナリについて取得する
+///
+/// 未インストールのバイナリはダウンロードせず `installed: None` として返す。
+pub async fn get_binary_versions() -> Result<BinaryVersionReport, String> {
+  let (yt_dlp, ffmpeg, deno) =
+    tokio::join!(get_yt_dlp_version_info(), get_ffmpeg_version(), get_deno_version());
+
+  Ok(BinaryVersionReport {
+    yt_dlp_channel: binary_settings().yt_dlp_channel,
+    yt_dlp: yt_dlp?,
+    ffmpeg: ffmpeg?,
+    deno: deno?,
+  })
+}
+
+/// yt-dlpのバージョン情報（最新版は設定チャンネルのもの）
+async fn get_yt_dlp_version_info() -> Result<BinaryVersionInfo, String> {
+  let binaries = binary_settings();
+  let installed = binary_version(&active_yt_dlp_path(&binaries)?, "--version");
+
+  Ok(BinaryVersionInfo {
+    installed,
+    pinned: binaries.yt_dlp_version,
+    latest: fetch_latest_tag(yt_dlp_repo(binaries.yt_dlp_channel)).await.ok(),
+  })
+}
+
+/// FFmpegのバージョン情報
+pub async fn get_ffmpeg_version() -> Result<BinaryVersionInfo, String> {
+  let binaries = binary_settings();
+  let installed = ffmpeg_version_of(&active_ffmpeg_dir(&binaries)?.join(ffmpeg_binary_name()));
+
+  Ok(BinaryVersionInfo {
+    installed,
+    pinned: binaries.ffmpeg_version,
+    latest: fetch_latest_tag(FFMPEG_REPO).await.ok(),
+  })
+}
+
+/// Denoのバージョン情報
+pub async fn get_deno_version() -> Result<BinaryVersionInfo, String> {
+  let binaries = binary_settings();
+  let installed = deno_version_of(&active_deno_path(&binaries)?);
+
+  Ok(BinaryVersionInfo {
+    installed,
+    pinned: binaries.deno_version,
+    latest: fetch_latest_tag(DENO_REPO).await.ok(),
+  })
+}
+
+/// "ffmpeg version 6.0-static https://..." の形式からバージョン部分を取り出す
+fn ffmpeg_version_of(path: &Path) -> Option<String> {
+  binary_version(path, "-version").and_then(|line| line.split_whitespace().nth(2).map(String::from))
+}
+
+/// "deno 2.1.4 (stable, release, ...)" の形式からバージョン部分を取り出す
+fn deno_version_of(path: &Path) -> Option<String> {
+  binary_version(path, "--version").and_then(|line| line.split_whitespace().nth(1).map(String::from))
+}
+
+// ─── 更新 ─────────────────────────────────────────
+
+/// FFmpegとFFprobeを更新する
+///
+/// `target_version` を省略した場合は固定バージョン、それもなければ最新版を取得する。
+/// 両方の動作確認後にまとめて置き換えるため、失敗しても既存のバイナリは残る。
+/// 外部バイナリを使う設定の場合は `error.binary_source_external` を返す。
+pub async fn update_ffmpeg(target_version: Option<String>) -> Result<String, String> {
+  let binaries = binary_settings();
+  ensure_bundled_source(&binaries.ffmpeg_source, BINARY_FFMPEG)?;
+  let ffmpeg_dir = get_ffmpeg_dir()?;
+  let target = target_version.or(binaries.ffmpeg_version);
+
+  download_ffmpeg_binaries(&ffmpeg_dir, target.as_deref(), binaries.ffmpeg_mirror.as_deref()).await?;
+
+  let version = ffmpeg_version_of(&ffmpeg_dir.join(ffmpeg_binary_name()))
+    .unwrap_or_else(|| "unknown".to_string());
+  log::info!("FFmpegを更新しました: {version}");
+  Ok(version)
+}
+
+/// Denoを更新する
+///
+/// `target_version` を省略した場合は固定バージョン、それもなければ最新版を取得する。
+/// 外部バイナリを使う設定の場合は `error.binary_source_external` を返す。
+pub async fn update_deno(target_version: Option<String>) -> Result<String, String> {
+  let binaries = binary_settings();
+  ensure_bundled_source(&binaries.deno_source, BINARY_DENO)?;
+  let deno_dir = get_deno_dir()?;
+  let target = target_version.or(binaries.deno_version);
+
+  let path = download_deno(&deno_dir, target.as_deref(), binaries.deno_mirror.as_deref()).await?;
+
+  let version = deno_version_of(&path).unwrap_or_else(|| "unknown".to_string());
+  log::info!("Denoを更新しました: {version}");
+  Ok(version)
+}
+
+// ─── オフラインインストール ─────────────────────────
+
+/// ローカルのアーカイブ（.zip / .tar / .tar.gz / .tgz）から同梱バイナリをインストールする
+///
+/// アーカイブ内のファイル名（yt-dlp, ffmpeg, ffprobe, deno。Windows では .exe 付き）で
+/// 対象を判定する。すべてのバイナリの実行確認が済んでからまとめて置き換えるため、
+/// 1つでも確認に失敗した場合は何もインストールしない。インストールしたバイナリ識別子を返す。
+pub fn install_binaries_from_archive(archive_path: &Path) -> Result<Vec<String>, String> {
+  if !archive_path.is_file() {
+    return Err(format!("error.archive_not_found:{}", archive_path.display()));
+  }
+
+  let ffmpeg_dir = get_ffmpeg_dir()?;
+  let targets = [
+    ensure_app_data_dir()?.join(yt_dlp_binary_name()),
+    ffmpeg_dir.join(ffmpeg_binary_name()),
+    ffmpeg_dir.join(ffprobe_binary_name()),
+    get_deno_dir()?.join(deno_binary_name()),
+  ];
+  let staged = extract_archive_binaries(archive_path, &targets)?;
+
+  let cleanup = || {
+    for tmp in staged.iter().flatten() {
+      let _ = std::fs::remove_file(tmp);
+    }
+  };
+
+  // FFmpeg は ffprobe と揃っていないと yt-dlp の後処理が動かないため、片方だけは受け付けない
+  if staged[1].is_some() != staged[2].is_some() {
+    cleanup();
+    return Err("error.archive_ffmpeg_incomplete".to_string());
+  }
+
+  let groups: [(&str, &[usize], &str); 3] = [
+    (BINARY_YT_DLP, &[0], "--version"),
+    (BINARY_FFMPEG, &[1, 2], "-version"),
+    (BINARY_DENO, &[3], "--version"),
+  ];
+
+  // 一部だけ置き換わった状態で失敗を返さないよう、先に全グループを確認する
+  let mut installed = Vec::new();
+  let mut pairs: Vec<(&Path, &Path)> = Vec::new();
+  for (name, indices, version_arg) in groups {
+    let group: Vec<(&Path, &Path)> = indices
+      .iter()
+      .filter_map(|&i| staged[i].as_deref().map(|tmp| (tmp, targets[i].as_path())))
+      .collect();
+    if group.is_empty() {
+      continue;
+    }
+
+    if let Err(e) = verify_staged_binaries(&group, version_arg) {
+      cleanup();
+      return Err(format!("error.archive_binary_invalid:{name} - {e}"));
+    }
+    installed.push(name.to_string());
+    pairs.extend(group);
+  }
+
+  if installed.is_empty() {
+    return Err("error.archive_no_binaries".to_string());
+  }
+  replace_staged_binaries(&pairs).map_err(|e| format!("error.archive_extract_failed:{e}"))?;
+
+  for name in &installed {
+    // アーカイブからはリリースタグが分からないため記録を消す
+    record_installed_tag(name, None);
+    if name == BINARY_YT_DLP {
+      // チャンネルも分からないため、設定のチャンネルとみなして入れ替えないようにする
+      let channel = binary_settings().yt_dlp_channel;
+      record_installed_tag(MANIFEST_YT_DLP_CHANNEL, Some(channel.as_str()));
+    }
+    log::info!("アーカイブから{name}をインストールしました");
+  }
+  Ok(installed)
+}
+
+/// アーカイブから対象のバイナリを一時ファイルへ展開する
+///
+/// 戻り値は `targets` と同じ順序で、見つかったものは一時ファイルのパスを持つ。
+fn extract_archive_binaries(
+  archive_path: &Path,
+  targets: &[PathBuf],
+) -> Result<Vec<Option<PathBuf>>, String> {
+  use std::io::Read;
+
+  let mut staged: Vec<Option<PathBuf>> = vec![None; targets.len()];
+  let mut stage = |entry_path: &Path, reader: &mut dyn Read| -> Result<(), String> {
+    let Some(index) = targets
+      .iter()
+      .position(|target| entry_path.file_name().is_some() && target.file_name() == entry_path.file_name())
+    else {
+      return Ok(());
+    };
+
+    let tmp_path = staging_path(&targets[index]);
+    let mut out = std::fs::File::create(&tmp_path)
+      .map_err(|e| format!("error.archive_extract_failed:{e}"))?;
+    std::io::copy(reader, &mut out).map_err(|e| format!("error.archive_extract_failed:{e}"))?;
+
+    #[cfg(unix)]
+    set_executable(&tmp_path)?;
+
+    staged[index] = Some(tmp_path);
+    Ok(())
+  };
+
+  let file_name = archive_path.to_string_lossy().to_lowercase();
+  let file = std::fs::File::open(archive_path).map_err(|e| format!("error.archive_open_failed:{e}"))?;
+
+  if file_name.ends_with(".zip") {
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("error.archive_open_failed:{e}"))?;
+    for i in 0..archive.len() {
+      let mut entry = archive
+        .by_index(i)
+        .map_err(|e| format!("error.archive_extract_failed:{e}"))?;
+      if !entry.is_file() {
+        continue;
+      }
+      let entry_path = PathBuf::from(entry.name());
+      stage(&entry_path, &mut entry)?;
+    }
+  } else if [".tar", ".tar.gz", ".tgz"].iter().any(|ext| file_name.ends_with(ext)) {
+    let reader: Box<dyn Read> = if file_name.ends_with(".tar") {
+      Box::new(file)
+    } else {
+      Box::new(flate2::read::GzDecoder::new(file))
+    };
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive.entries().map_err(|e| format!("error.archive_open_failed:{e}"))?;
+    for entry in entries {
+      let mut entry = entry.map_err(|e| format!("error.archive_extract_failed:{e}"))?;
+      if !entry.header().entry_type().is_file() {
+        continue;
+      }
+      let entry_path = entry
+        .path()
+        .map_err(|e| format!("error.archive_extract_failed:{e}"))?
+        .into_owned();
+      stage(&entry_path, &mut entry)?;
+    }
+  } else {
+    return Err("error.archive_unsupported_format".to_string());
+  }
+
+  Ok(staged)
+}
+
+// ─── インストール状態 ──────────────────────────────
+
+/// 1つのバイナリのインストール状態
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryStatus {
+  pub name: String,
+  pub installed: bool,
+  /// 同梱版ではなく system / custom のバイナリを使用しているか
+  pub external: bool,
+  pub version: Option<String>,
+  pub path: String,
+  pub size: Option<u64>,
+  /// ファイルの更新日時（インストール・更新時に書き換わる）
+  pub installed_at: Option<String>,
+  /// 設定された外部バイナリが見つからない・実行できない場合のエラー
+  pub error: Option<String>,
+}
+
+/// yt-dlp / FFmpeg / FFprobe / Deno のインストール状態を返す
+///
+/// 外部バイナリを使う設定の場合は、設定されたバイナリの状態を返す（同梱版には切り替えない）。
+pub fn get_binaries_status() -> Result<Vec<BinaryStatus>, String> {
+  let binaries = binary_settings();
+  let app_data_dir = ensure_app_data_dir()?;
+  let yt_dlp_path = active_yt_dlp_path(&binaries)?;
+  let ffmpeg_dir = active_ffmpeg_dir(&binaries)?;
+  let deno_path = active_deno_path(&binaries)?;
+
+  let targets = [
+    (BINARY_YT_DLP, binary_version(&yt_dlp_path, "--version"), yt_dlp_path),
+    (BINARY_FFMPEG, ffmpeg_version_of(&ffmpeg_dir.join(ffmpeg_binary_name())), ffmpeg_dir.join(ffmpeg_binary_name())),
+    ("ffprobe", ffmpeg_version_of(&ffmpeg_dir.join(ffprobe_binary_name())), ffmpeg_dir.join(ffprobe_binary_name())),
+    (BINARY_DENO, deno_version_of(&deno_path), deno_path),
+  ];
+
+  Ok(
+    targets
+      .into_iter()
+      .map(|(name, version, path)| {
+        let metadata = std::fs::metadata(&path).ok().filter(std::fs::Metadata::is_file);
+        let installed_at = metadata.as_ref().and_then(|m| m.modified().ok()).map(|t| {
+          let datetime: chrono::DateTime<chrono::Utc> = t.into();
+          datetime.to_rfc3339()
+        });
+
+        let external = !path.starts_with(&app_data_dir);
+        let error = (external && version.is_none())
+          .then(|| format!("error.binary_invalid:{}", path.display()));
+
+        BinaryStatus {
+          name: name.to_string(),
+          installed: metadata.is_some(),
+          external,
+          error,
+          version,
+          path: path.to_string_lossy().to_string(),
+          size: metadata.as_ref().map(std::fs::Metadata::len),
+          installed_at,
+        }
+      })
+      .collect(),
+  )
+}
+
 // ─── ユーティリティ ────────────────────────────────
 
 /// Unix環境でファイルに実行権限を付与する
@@ -490,3 +1380,209 @@ fn set_executable(path: &std::path::Path) -> Result<(), String> {
     .map_err(|e| format!("error.permission_failed:{e}"))?;
   Ok(())
 }
+
+/// バイナリを実行してバージョン出力の1行目を取得する
+fn binary_version(path: &Path, version_arg: &str) -> Option<String> {
+  if !path.exists() {
+    return None;
+  }
+
+  silent_command(path)
+    .arg(version_arg)
+    .output()
+    .ok()
+    .filter(|o| o.status.success())
+    .and_then(|o| {
+      String::from_utf8_lossy(&o.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+    })
+}
+
+/// 単体の実行バイナリをダウンロードしてインストールする
+///
+/// 一時ファイルに書き出して実行できることを確認してから置き換えるため、
+/// 途中で失敗しても既存のバイナリは残る。
+async fn download_binary(
+  url: &str,
+  dest_path: &Path,
+  version_arg: &str,
+  step: &'static str,
+) -> Result<(), String> {
+  let tmp_path = staging_path(dest_path);
+  download_to_file(url, &tmp_path, &ByteProgress::new(step)).await?;
+
+  #[cfg(unix)]
+  set_executable(&tmp_path)?;
+
+  swap_staged_binaries(&[(&tmp_path, dest_path)], version_arg)
+}
+
+/// ダウンロードの最大試行回数
+const DOWNLOAD_MAX_ATTEMPTS: u32 = 4;
+
+/// ダウンロード失敗の内容と、再試行する価値があるか
+struct DownloadFailure {
+  message: String,
+  retryable: bool,
+}
+
+/// URL の内容をファイルへストリーミングで保存する
+///
+/// 通信エラーやサーバー側のエラー（5xx / 429）は指数バックオフ（1, 2, 4秒）で
+/// 再試行する。最終的に失敗した場合は書きかけのファイルを削除する。
+async fn download_to_file(url: &str, dest_path: &Path, progress: &ByteProgress) -> Result<(), String> {
+  log::info!("ダウンロード中: {url}");
+
+  let mut attempt = 1;
+  loop {
+    match try_download_to_file(url, dest_path, progress).await {
+      Ok(()) => return Ok(()),
+      Err(failure) if failure.retryable && attempt < DOWNLOAD_MAX_ATTEMPTS => {
+        let delay = Duration::from_secs(1 << (attempt - 1));
+        log::warn!(
+          "ダウンロードに失敗しました。{}秒後に再試行します ({attempt}/{DOWNLOAD_MAX_ATTEMPTS}): {}",
+          delay.as_secs(),
+          failure.message
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+      }
+      Err(failure) => {
+        let _ = std::fs::remove_file(dest_path);
+        return Err(failure.message);
+      }
+    }
+  }
+}
+
+/// `download_to_file` の1回分の試行
+async fn try_download_to_file(
+  url: &str,
+  dest_path: &Path,
+  progress: &ByteProgress,
+) -> Result<(), DownloadFailure> {
+  use futures_util::StreamExt;
+  use std::io::Write;
+
+  let fatal = |message: String| DownloadFailure { message, retryable: false };
+  let transient = |message: String| DownloadFailure { message, retryable: true };
+
+  let response = http_client()
+    .map_err(fatal)?
+    .get(url)
+    .send()
+    .await
+    .map_err(|e| transient(e.to_string()))?;
+
+  let status = response.status();
+  if !status.is_success() {
+    let retryable = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+    return Err(DownloadFailure { message: format!("HTTP {status}"), retryable });
+  }
+
+  let total = response.content_length().unwrap_or(0);
+  progress.add_total(total);
+  let mut received: u64 = 0;
+
+  let result = async {
+    let mut file = std::fs::File::create(dest_path).map_err(|e| fatal(e.to_string()))?;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+      let chunk = chunk.map_err(|e| transient(e.to_string()))?;
+      file.write_all(&chunk).map_err(|e| fatal(e.to_string()))?;
+      received += chunk.len() as u64;
+      progress.add_downloaded(chunk.len() as u64);
+    }
+    Ok(())
+  }
+  .await;
+
+  if result.is_err() {
+    progress.rewind(received, total);
+  }
+  result
+}
+
+/// インストール前にバイナリを書き出す一時ファイルのパス
+///
+/// Windows では拡張子が .exe でないと実行確認できないため `.tmp.exe` とする。
+fn staging_path(dest_path: &Path) -> PathBuf {
+  dest_path.with_extension(if cfg!(windows) { "tmp.exe" } else { "tmp" })
+}
+
+/// 一時ファイルのバイナリがすべて実行できることを確認してから置き換える
+///
+/// 1つでも実行できなければ一時ファイルを削除し、既存のバイナリには触れない。
+fn swap_staged_binaries(staged: &[(&Path, &Path)], version_arg: &str) -> Result<(), String> {
+  if let Err(e) = verify_staged_binaries(staged, version_arg) {
+    for (tmp, _) in staged {
+      let _ = std::fs::remove_file(tmp);
+    }
+    return Err(e);
+  }
+  replace_staged_binaries(staged)
+}
+
+/// 一時ファイルのバイナリがすべて実行できるかを確認する
+fn verify_staged_binaries(staged: &[(&Path, &Path)], version_arg: &str) -> Result<(), String> {
+  match staged.iter().find(|(tmp, _)| binary_version(tmp, version_arg).is_none()) {
+    Some((broken, _)) => Err(format!("downloaded binary is not executable: {}", broken.display())),
+    None => Ok(()),
+  }
+}
+
+/// 確認済みの一時ファイルで既存のバイナリを置き換える
+///
+/// 途中で失敗した場合は置き換え済みのバイナリを元に戻し、残りの一時ファイルを削除する。
+fn replace_staged_binaries(staged: &[(&Path, &Path)]) -> Result<(), String> {
+  // 途中で失敗した場合に戻せるよう、既存のバイナリは退避してから置き換える
+  let mut swapped: Vec<(&Path, Option<PathBuf>)> = Vec::new();
+  for (index, (tmp, dest)) in staged.iter().enumerate() {
+    let backup = dest.with_extension("old");
+    let result = (|| {
+      let backup = if dest.exists() {
+        std::fs::rename(dest, &backup).map_err(|e| e.to_string())?;
+        Some(backup)
+      } else {
+        None
+      };
+      if let Err(e) = std::fs::rename(tmp, dest) {
+        if let Some(backup) = &backup {
+          let _ = std::fs::rename(backup, dest);
+        }
+        return Err(e.to_string());
+      }
+      Ok(backup)
+    })();
+
+    match result {
+      Ok(backup) => swapped.push((dest, backup)),
+      Err(e) => {
+        // 置き換え済みのバイナリを元に戻し、残りの一時ファイルを削除する
+        for (dest, backup) in swapped.iter().rev() {
+          match backup {
+            Some(backup) => {
+              let _ = std::fs::rename(backup, dest);
+            }
+            None => {
+              let _ = std::fs::remove_file(dest);
+            }
+          }
+        }
+        for (tmp, _) in &staged[index..] {
+          let _ = std::fs::remove_file(tmp);
+        }
+        return Err(e);
+      }
+    }
+  }
+
+  for (_, backup) in swapped {
+    if let Some(backup) = backup {
+      let _ = std::fs::remove_file(backup);
+    }
+  }
+  Ok(())
+}
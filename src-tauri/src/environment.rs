@@ -0,0 +1,102 @@
+use std::env;
+
+use crate::downloader::{binary_search_dirs, get_deno_dir, get_ffmpeg_dir};
+
+/// 起動時に削除するプロキシの環境変数
+///
+/// yt-dlp と reqwest はどちらも環境変数のプロキシを読むため、残すと OS によって
+/// 設定のプロキシと異なる経路を使ってしまう。大文字・小文字の両方を削除する。
+const PROXY_ENV_VARS: &[&str] = &[
+  "HTTP_PROXY",
+  "http_proxy",
+  "HTTPS_PROXY",
+  "https_proxy",
+  "ALL_PROXY",
+  "all_proxy",
+  "NO_PROXY",
+  "no_proxy",
+];
+
+/// アプリ・CLI の起動時にプロセスの環境変数を整える
+///
+/// PATH に FFmpeg / Deno などのディレクトリを追加し、プロキシの環境変数を削除する
+/// （プロキシは設定の値だけを yt-dlp と HTTP クライアントに明示的に渡す）。環境変数を書き換えるため、
+/// スレッドを起動する前に呼ぶこと。CLI の標準出力を汚さないよう、診断情報は stdout に出さない。
+pub fn prepare_environment() {
+  for name in PROXY_ENV_VARS {
+    if env::var_os(name).is_some() {
+      log::debug!("プロキシの環境変数を無視します: {name}");
+      env::remove_var(name);
+    }
+  }
+
+  // 環境に応じたPATH設定
+  // Tauri アプリはシェルプロファイルを読まないため、
+  // Homebrew, Volta, nvm, fnm 等の一般的なパスを明示的に追加する
+  #[cfg(not(windows))]
+  {
+    let current_path = env::var("PATH").unwrap_or_default();
+    let home = env::var("HOME").unwrap_or_default();
+
+    // Volta の shim が動作するには VOLTA_HOME が必要
+    let volta_home = format!("{home}/.volta");
+    if std::path::Path::new(&volta_home).exists() && env::var("VOLTA_HOME").is_err() {
+      env::set_var("VOLTA_HOME", &volta_home);
+    }
+
+    // 設定されたバイナリディレクトリ（通常はアプリ同梱）を最優先でPATHに追加し、
+    // system 設定のバイナリが見つからない場合に備えて同梱ディレクトリを末尾に追加
+    let binary_dirs = binary_search_dirs();
+    let deno_dir = get_deno_dir().unwrap_or_default();
+    let ffmpeg_dir = get_ffmpeg_dir().unwrap_or_default();
+
+    let mut paths: Vec<String> = binary_dirs
+      .iter()
+      .map(|dir| dir.to_string_lossy().to_string())
+      .collect();
+    paths.extend([
+      "/opt/homebrew/bin".to_string(),
+      "/usr/local/bin".to_string(),
+      format!("{home}/.volta/bin"),
+      format!("{home}/.nvm/current/bin"),
+      format!("{home}/.fnm/current/bin"),
+      format!("{home}/.local/bin"),
+      current_path,
+      ffmpeg_dir.to_string_lossy().to_string(),
+      deno_dir.to_string_lossy().to_string(),
+    ]);
+    paths.retain(|p| !p.is_empty());
+    let new_path = paths.join(":");
+    env::set_var("PATH", new_path);
+  }
+
+  #[cfg(windows)]
+  {
+    if let Ok(current_path) = env::var("PATH") {
+      log::debug!("現在のPATH: {current_path}");
+    }
+
+    env::set_var("NO_COLOR", "1");
+
+    let temp_dirs = vec![
+      dirs::data_dir().map(|p| p.join("my-video-downloader").join("temp")),
+      Some(std::env::temp_dir().join("my-video-downloader")),
+    ];
+
+    for dir in temp_dirs.iter().flatten() {
+      if !dir.exists() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+          eprintln!("一時ディレクトリの作成に失敗: {:?} - {}", dir, e);
+        } else {
+          log::debug!("一時ディレクトリを作成: {}", dir.display());
+          env::set_var("TEMP", dir.to_string_lossy().to_string());
+          break;
+        }
+      } else {
+        log::debug!("既存の一時ディレクトリを使用: {}", dir.display());
+        env::set_var("TEMP", dir.to_string_lossy().to_string());
+        break;
+      }
+    }
+  }
+}
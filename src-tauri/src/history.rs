@@ -1,8 +1,14 @@
-use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
 
+use crate::download::DownloadRequest;
+use crate::redact::redact;
 use crate::utils::ensure_app_data_dir;
 
 /// ダウンロード履歴の1エントリ
@@ -19,6 +25,12 @@ pub struct HistoryEntry {
   pub status: HistoryStatus,
   pub error_message: Option<String>,
   pub timestamp: DateTime<Utc>,
+  /// 再ダウンロードに使うダウンロード要求（パスワードは保存しない。古い履歴にはない）
+  #[serde(default)]
+  pub request: Option<DownloadRequest>,
+  /// 再試行元の履歴エントリID
+  #[serde(default)]
+  pub retry_of: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -28,6 +40,18 @@ pub enum HistoryStatus {
   Failed,
 }
 
+/// まとめて再試行する失敗履歴の条件（すべて省略可）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryFilter {
+  /// URL・タイトル・エラーメッセージの部分一致（大文字小文字を区別しない）
+  pub query: Option<String>,
+  /// この日時以降の失敗のみ
+  pub since: Option<DateTime<Utc>>,
+  /// 再試行する最大件数
+  pub limit: Option<usize>,
+}
+
 /// 日付ごとにグループ化された履歴
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,39 +74,254 @@ pub struct DownloadStats {
   pub month_total: u32,
 }
 
-/// 履歴ファイルのパスを取得する
-fn history_path() -> Result<PathBuf, String> {
+// ─── データベース ─────────────────────────────────
+
+/// スキーマのマイグレーション（`PRAGMA user_version` が適用済みの数）
+///
+/// 適用済みのマイグレーションは変更せず、変更は末尾に追加する。
+const MIGRATIONS: &[&str] = &[
+  // 1: 履歴テーブル（timestamp は UNIX ミリ秒）
+  "CREATE TABLE history (
+     id TEXT PRIMARY KEY,
+     url TEXT NOT NULL,
+     title TEXT NOT NULL,
+     thumbnail TEXT,
+     file_path TEXT,
+     format TEXT NOT NULL,
+     size INTEGER,
+     status TEXT NOT NULL,
+     error_message TEXT,
+     timestamp INTEGER NOT NULL,
+     request TEXT,
+     retry_of TEXT
+   );
+   CREATE INDEX idx_history_timestamp ON history (timestamp);
+   CREATE INDEX idx_history_status_timestamp ON history (status, timestamp);
+   CREATE INDEX idx_history_url_timestamp ON history (url, timestamp);
+   CREATE INDEX idx_history_file_path ON history (file_path);",
+];
+
+/// 履歴画面に表示する件数（データベースには全件残る）
+const GROUPED_HISTORY_LIMIT: usize = 1000;
+/// 他のプロセス（CLI など）が書き込み中の場合に待つ時間
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SELECT_COLUMNS: &str = "SELECT id, url, title, thumbnail, file_path, format, size, status,
+  error_message, timestamp, request, retry_of FROM history";
+
+/// 開いたデータベース（最初の利用時にマイグレーションと history.json の取り込みを行う）
+static DB: LazyLock<Mutex<Option<Connection>>> = LazyLock::new(|| Mutex::new(None));
+
+fn db_error(e: impl std::fmt::Display) -> String {
+  format!("error.history_db_failed:{e}")
+}
+
+/// 履歴データベースのパスを取得する
+fn db_path() -> Result<PathBuf, String> {
+  Ok(ensure_app_data_dir()?.join("history.db"))
+}
+
+/// 以前の履歴ファイル（初回起動時にデータベースへ取り込む）
+fn legacy_json_path() -> Result<PathBuf, String> {
   Ok(ensure_app_data_dir()?.join("history.json"))
 }
 
-/// 履歴を全件読み込む
-pub fn load_all_entries() -> Result<Vec<HistoryEntry>, String> {
-  let path = history_path()?;
+fn open() -> Result<Connection, String> {
+  open_at(&db_path()?, &legacy_json_path()?)
+}
+
+/// データベースを開き、未適用のマイグレーションを適用する
+///
+/// 新規作成時は `legacy_json` の履歴も同じトランザクションで取り込み、
+/// コミットに成功してから元のファイルを history.json.bak に移動する。
+fn open_at(path: &Path, legacy_json: &Path) -> Result<Connection, String> {
+  let mut conn = Connection::open(path).map_err(db_error)?;
+  conn.busy_timeout(BUSY_TIMEOUT).map_err(db_error)?;
+  conn.pragma_update(None, "journal_mode", "WAL").map_err(db_error)?;
+
+  let version: usize = conn
+    .pragma_query_value(None, "user_version", |row| row.get(0))
+    .map_err(db_error)?;
+  if version < MIGRATIONS.len() {
+    let tx = conn.transaction().map_err(db_error)?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+      tx.execute_batch(migration).map_err(db_error)?;
+      log::info!("履歴データベースのマイグレーション {} を適用しました", index + 1);
+    }
+    let imported = if version == 0 { import_legacy_json(&tx, legacy_json)? } else { None };
+    tx.pragma_update(None, "user_version", MIGRATIONS.len()).map_err(db_error)?;
+    tx.commit().map_err(db_error)?;
+
+    // コミットに失敗した場合は次回起動時に取り込み直せるよう、元のファイルは最後に移動する
+    if let Some(count) = imported {
+      if let Err(e) = fs::rename(legacy_json, legacy_json.with_extension("json.bak")) {
+        log::warn!("取り込み済みの履歴ファイルを移動できません: {e}");
+      }
+      log::info!("history.json から {count} 件の履歴を取り込みました");
+    }
+  }
+  Ok(conn)
+}
+
+/// データベースの接続を使って処理する
+fn with_db<T>(f: impl FnOnce(&mut Connection) -> Result<T, String>) -> Result<T, String> {
+  let mut db = DB.lock().unwrap();
+  if db.is_none() {
+    *db = Some(open()?);
+  }
+  f(db.as_mut().unwrap())
+}
 
+/// history.json の履歴をデータベースに取り込み、取り込んだ件数を返す
+///
+/// ファイルがない・パースできない場合は None（元のファイルは移動しない）。
+fn import_legacy_json(conn: &Connection, path: &Path) -> Result<Option<usize>, String> {
   if !path.exists() {
-    return Ok(Vec::new());
+    return Ok(None);
   }
 
-  let content =
-    fs::read_to_string(&path).map_err(|e| format!("履歴ファイルの読み込みに失敗: {e}"))?;
+  let content = fs::read_to_string(path).map_err(|e| format!("履歴ファイルの読み込みに失敗: {e}"))?;
+  let entries: Vec<HistoryEntry> = match serde_json::from_str(&content) {
+    Ok(entries) => entries,
+    Err(e) => {
+      // 壊れたファイルで起動できなくならないよう、取り込まずに残しておく
+      log::warn!("履歴ファイルのパースに失敗したため取り込みません: {e}");
+      return Ok(None);
+    }
+  };
 
-  serde_json::from_str(&content).map_err(|e| {
-    log::warn!("履歴ファイルのパースに失敗: {e}");
-    format!("履歴ファイルのパースに失敗: {e}")
-  })
+  for entry in &entries {
+    insert_entry(conn, entry)?;
+  }
+  Ok(Some(entries.len()))
 }
 
-/// 履歴を保存する（内部用）
-fn save_all_entries(entries: &[HistoryEntry]) -> Result<(), String> {
-  let path = history_path()?;
+fn status_str(status: &HistoryStatus) -> &'static str {
+  match status {
+    HistoryStatus::Success => "success",
+    HistoryStatus::Failed => "failed",
+  }
+}
 
-  let content = serde_json::to_string_pretty(entries)
+fn insert_entry(conn: &Connection, entry: &HistoryEntry) -> Result<(), String> {
+  let request = entry
+    .request
+    .as_ref()
+    .map(serde_json::to_string)
+    .transpose()
     .map_err(|e| format!("履歴のシリアライズに失敗: {e}"))?;
-
-  fs::write(&path, content).map_err(|e| format!("履歴ファイルの書き込みに失敗: {e}"))?;
+  // SQLite の INTEGER は i64 のため、i64::MAX を超えるサイズは扱わない
+  #[allow(clippy::cast_possible_wrap)]
+  let size = entry.size.map(|size| size as i64);
+
+  conn
+    .execute(
+      "INSERT OR REPLACE INTO history (id, url, title, thumbnail, file_path, format, size, status,
+         error_message, timestamp, request, retry_of)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+      params![
+        entry.id,
+        entry.url,
+        entry.title,
+        entry.thumbnail,
+        entry.file_path,
+        entry.format,
+        size,
+        status_str(&entry.status),
+        entry.error_message,
+        entry.timestamp.timestamp_millis(),
+        request,
+        entry.retry_of,
+      ],
+    )
+    .map_err(db_error)?;
   Ok(())
 }
 
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+  let status: String = row.get("status")?;
+  let timestamp: i64 = row.get("timestamp")?;
+  let request: Option<String> = row.get("request")?;
+  #[allow(clippy::cast_sign_loss)]
+  let size = row.get::<_, Option<i64>>("size")?.map(|size| size as u64);
+
+  Ok(HistoryEntry {
+    id: row.get("id")?,
+    url: row.get("url")?,
+    title: row.get("title")?,
+    thumbnail: row.get("thumbnail")?,
+    file_path: row.get("file_path")?,
+    format: row.get("format")?,
+    size,
+    status: if status == "success" { HistoryStatus::Success } else { HistoryStatus::Failed },
+    error_message: row.get("error_message")?,
+    timestamp: Utc.timestamp_millis_opt(timestamp).single().unwrap_or_default(),
+    // 形式が変わって読めない要求は再試行時に URL から復元する
+    request: request.and_then(|json| serde_json::from_str(&json).ok()),
+    retry_of: row.get("retry_of")?,
+  })
+}
+
+fn query_entries(
+  conn: &Connection,
+  sql: &str,
+  params: impl rusqlite::Params,
+) -> Result<Vec<HistoryEntry>, String> {
+  let mut stmt = conn.prepare(sql).map_err(db_error)?;
+  let rows = stmt.query_map(params, entry_from_row).map_err(db_error)?;
+  rows.collect::<rusqlite::Result<Vec<_>>>().map_err(db_error)
+}
+
+// ─── 読み込み ─────────────────────────────────────
+
+/// 履歴を新しい順に読み込む（status を指定するとその状態のみ、limit 省略時は全件）
+pub fn load_entries(
+  status: Option<HistoryStatus>,
+  limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+  #[allow(clippy::cast_possible_wrap)]
+  let limit = limit.map_or(-1, |limit| limit as i64);
+  with_db(|conn| {
+    query_entries(
+      conn,
+      &format!("{SELECT_COLUMNS} WHERE ?1 IS NULL OR status = ?1 ORDER BY timestamp DESC LIMIT ?2"),
+      params![status.as_ref().map(status_str), limit],
+    )
+  })
+}
+
+/// 履歴を全件読み込む
+pub fn load_all_entries() -> Result<Vec<HistoryEntry>, String> {
+  load_entries(None, None)
+}
+
+/// 履歴の件数
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn count_entries() -> Result<usize, String> {
+  with_db(|conn| {
+    conn
+      .query_row("SELECT COUNT(*) FROM history", [], |row| row.get::<_, i64>(0))
+      .map(|count| count as usize)
+      .map_err(db_error)
+  })
+}
+
+/// 保存先ファイルのパス → サムネイル URL（ダウンロード済みファイルの一覧用）
+pub fn thumbnails_by_file_path() -> Result<HashMap<String, String>, String> {
+  with_db(|conn| {
+    let mut stmt = conn
+      .prepare(
+        "SELECT file_path, thumbnail FROM history
+         WHERE file_path IS NOT NULL AND thumbnail IS NOT NULL",
+      )
+      .map_err(db_error)?;
+    let rows = stmt
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+      .map_err(db_error)?;
+    rows.collect::<rusqlite::Result<HashMap<_, _>>>().map_err(db_error)
+  })
+}
+
 /// 日付ラベルを生成する
 fn format_date_label(date: NaiveDate, today: NaiveDate, yesterday: NaiveDate) -> (String, String) {
   let formatted = date.format("%B %-d, %Y").to_string().to_uppercase();
@@ -97,21 +336,65 @@ fn format_date_label(date: NaiveDate, today: NaiveDate, yesterday: NaiveDate) ->
 }
 
 /// 履歴エントリを追加する
-pub fn add_entry(entry: HistoryEntry) -> Result<(), String> {
-  let mut entries = load_all_entries()?;
-  entries.insert(0, entry);
+///
+/// エラーメッセージは yt-dlp の stderr をそのまま含むため、保存前に秘密情報を伏せる。
+pub fn add_entry(mut entry: HistoryEntry) -> Result<(), String> {
+  entry.error_message = entry.error_message.map(|message| redact(&message));
+  with_db(|conn| insert_entry(conn, &entry))?;
+  log::info!("履歴エントリを追加しました: {}", entry.id);
+  Ok(())
+}
 
-  // 最大1000件に制限
-  entries.truncate(1000);
+/// ID の履歴エントリを取得する
+pub fn find_entry(id: &str) -> Result<HistoryEntry, String> {
+  with_db(|conn| {
+    conn
+      .query_row(&format!("{SELECT_COLUMNS} WHERE id = ?1"), [id], entry_from_row)
+      .optional()
+      .map_err(db_error)
+  })?
+  .ok_or_else(|| format!("error.history_not_found:{id}"))
+}
 
-  save_all_entries(&entries)?;
-  log::info!("履歴エントリを追加しました (総数: {})", entries.len());
-  Ok(())
+/// LIKE 用に `%`・`_`・`\` をエスケープする
+fn escape_like(text: &str) -> String {
+  text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// 条件に合う、再試行すべき失敗履歴を新しい順に取得する
+///
+/// 同じ URL は最新の1件だけを対象とし、最新が成功している URL は除く。
+pub fn failed_entries(filter: &RetryFilter) -> Result<Vec<HistoryEntry>, String> {
+  let pattern = filter
+    .query
+    .as_deref()
+    .map(str::trim)
+    .filter(|query| !query.is_empty())
+    .map(|query| format!("%{}%", escape_like(&query.to_lowercase())));
+  let since = filter.since.map(|since| since.timestamp_millis());
+  #[allow(clippy::cast_possible_wrap)]
+  let limit = filter.limit.map_or(-1, |limit| limit as i64);
+
+  with_db(|conn| {
+    query_entries(
+      conn,
+      &format!(
+        "{SELECT_COLUMNS} AS h
+         WHERE status = 'failed'
+           AND timestamp = (SELECT MAX(timestamp) FROM history WHERE url = h.url)
+           AND (?1 IS NULL OR timestamp >= ?1)
+           AND (?2 IS NULL OR lower(url) LIKE ?2 ESCAPE '\\' OR lower(title) LIKE ?2 ESCAPE '\\'
+             OR lower(error_message) LIKE ?2 ESCAPE '\\')
+         ORDER BY timestamp DESC LIMIT ?3"
+      ),
+      params![since, pattern, limit],
+    )
+  })
 }
 
-/// 日付ごとにグループ化した履歴を取得する
+/// 日付ごとにグループ化した履歴を取得する（新しい順に最大 GROUPED_HISTORY_LIMIT 件）
 pub fn get_grouped_history() -> Result<Vec<HistoryGroup>, String> {
-  let entries = load_all_entries()?;
+  let entries = load_entries(None, Some(GROUPED_HISTORY_LIMIT))?;
   let now = Local::now();
   let today = now.date_naive();
   let yesterday = today - chrono::Duration::days(1);
@@ -123,80 +406,191 @@ pub fn get_grouped_history() -> Result<Vec<HistoryGroup>, String> {
     let entry_date = entry_local.date_naive();
     let (label, date_str) = format_date_label(entry_date, today, yesterday);
 
-    if let Some(group) = groups.iter_mut().find(|g| g.date == date_str) {
-      group.items.push(entry);
-    } else {
-      groups.push(HistoryGroup {
+    // 新しい順に並んでいるため、同じ日付は常に最後のグループになる
+    match groups.last_mut() {
+      Some(group) if group.date == date_str => group.items.push(entry),
+      _ => groups.push(HistoryGroup {
         label,
         date: date_str,
         items: vec![entry],
-      });
+      }),
     }
   }
 
   Ok(groups)
 }
 
+/// ローカル日付の 0 時を UNIX ミリ秒にする
+fn start_of_day_millis(date: NaiveDate) -> i64 {
+  let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+  Local
+    .from_local_datetime(&midnight)
+    .earliest()
+    .map_or_else(|| midnight.and_utc().timestamp_millis(), |dt| dt.timestamp_millis())
+}
+
 /// ダウンロード統計を計算する
+#[allow(clippy::cast_sign_loss)]
 pub fn get_stats() -> Result<DownloadStats, String> {
-  let entries = load_all_entries()?;
-  let now = Local::now();
-  let today = now.date_naive();
-  let week_ago = today - chrono::Duration::days(7);
-  let month_ago = today - chrono::Duration::days(30);
-
-  let mut stats = DownloadStats {
-    today_count: 0,
-    today_size: 0,
-    week_count: 0,
-    week_size: 0,
-    month_count: 0,
-    month_size: 0,
-    month_total: 0,
-  };
-
-  for entry in &entries {
-    let entry_local: DateTime<Local> = entry.timestamp.into();
-    let entry_date = entry_local.date_naive();
+  let today = Local::now().date_naive();
+  let today_start = start_of_day_millis(today);
+  let week_start = start_of_day_millis(today - chrono::Duration::days(7));
+  let month_start = start_of_day_millis(today - chrono::Duration::days(30));
+
+  // 過去30日の全エントリ（成功+失敗）を month_total、成功のみを期間ごとに集計する
+  with_db(|conn| {
+    conn
+      .query_row(
+        "SELECT
+           COUNT(*),
+           COALESCE(SUM(status = 'success' AND timestamp >= ?1), 0),
+           COALESCE(SUM(CASE WHEN status = 'success' AND timestamp >= ?1 THEN size END), 0),
+           COALESCE(SUM(status = 'success' AND timestamp >= ?2), 0),
+           COALESCE(SUM(CASE WHEN status = 'success' AND timestamp >= ?2 THEN size END), 0),
+           COALESCE(SUM(status = 'success'), 0),
+           COALESCE(SUM(CASE WHEN status = 'success' THEN size END), 0)
+         FROM history WHERE timestamp >= ?3",
+        params![today_start, week_start, month_start],
+        |row| {
+          Ok(DownloadStats {
+            month_total: row.get(0)?,
+            today_count: row.get(1)?,
+            today_size: row.get::<_, i64>(2)? as u64,
+            week_count: row.get(3)?,
+            week_size: row.get::<_, i64>(4)? as u64,
+            month_count: row.get(5)?,
+            month_size: row.get::<_, i64>(6)? as u64,
+          })
+        },
+      )
+      .map_err(db_error)
+  })
+}
 
-    // 過去30日の全エントリ（成功+失敗）をカウント
-    if entry_date >= month_ago {
-      stats.month_total += 1;
-    }
+/// 全履歴を消去する
+pub fn clear_all() -> Result<(), String> {
+  with_db(|conn| conn.execute("DELETE FROM history", []).map_err(db_error))?;
+  log::info!("全履歴を消去しました");
+  Ok(())
+}
 
-    if entry.status != HistoryStatus::Success {
-      continue;
-    }
+#[cfg(test)]
+mod tests {
+  use super::*;
 
-    let size = entry.size.unwrap_or(0);
+  /// テストごとの一時ディレクトリ（終了時に削除する）
+  struct TempDir(PathBuf);
 
-    if entry_date == today {
-      stats.today_count += 1;
-      stats.today_size += size;
+  impl TempDir {
+    fn new() -> Self {
+      let path = std::env::temp_dir().join(format!("lucentia-history-{}", uuid::Uuid::new_v4()));
+      fs::create_dir_all(&path).unwrap();
+      Self(path)
     }
+  }
 
-    if entry_date >= week_ago {
-      stats.week_count += 1;
-      stats.week_size += size;
+  impl Drop for TempDir {
+    fn drop(&mut self) {
+      let _ = fs::remove_dir_all(&self.0);
     }
+  }
 
-    if entry_date >= month_ago {
-      stats.month_count += 1;
-      stats.month_size += size;
+  fn entry(id: &str, status: HistoryStatus, size: Option<u64>) -> HistoryEntry {
+    HistoryEntry {
+      id: id.to_string(),
+      url: format!("https://example.com/{id}"),
+      title: format!("Title {id}"),
+      thumbnail: None,
+      file_path: None,
+      format: "MP4 best".to_string(),
+      size,
+      status,
+      error_message: None,
+      timestamp: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+      request: None,
+      retry_of: None,
     }
   }
 
-  Ok(stats)
-}
+  fn user_version(conn: &Connection) -> usize {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+  }
 
-/// 全履歴を消去する
-pub fn clear_all() -> Result<(), String> {
-  let path = history_path()?;
+  fn all_entries(conn: &Connection) -> Vec<HistoryEntry> {
+    query_entries(conn, &format!("{SELECT_COLUMNS} ORDER BY id"), []).unwrap()
+  }
 
-  if path.exists() {
-    fs::write(&path, "[]").map_err(|e| format!("履歴のクリアに失敗: {e}"))?;
-    log::info!("全履歴を消去しました");
+  #[test]
+  fn migrations_create_schema_once() {
+    let dir = TempDir::new();
+    let db = dir.0.join("history.db");
+    let legacy = dir.0.join("history.json");
+
+    let conn = open_at(&db, &legacy).unwrap();
+    assert_eq!(user_version(&conn), MIGRATIONS.len());
+    insert_entry(&conn, &entry("a", HistoryStatus::Success, Some(1024))).unwrap();
+    drop(conn);
+
+    // 再度開いてもマイグレーションは再適用されず、既存の行は残る
+    let conn = open_at(&db, &legacy).unwrap();
+    assert_eq!(user_version(&conn), MIGRATIONS.len());
+    let entries = all_entries(&conn);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].size, Some(1024));
+    assert_eq!(entries[0].status, HistoryStatus::Success);
+    assert_eq!(entries[0].timestamp, entry("a", HistoryStatus::Success, None).timestamp);
   }
 
-  Ok(())
+  #[test]
+  fn imports_legacy_json_and_moves_it_after_commit() {
+    let dir = TempDir::new();
+    let db = dir.0.join("history.db");
+    let legacy = dir.0.join("history.json");
+    let entries = vec![
+      entry("a", HistoryStatus::Success, Some(10)),
+      entry("b", HistoryStatus::Failed, None),
+    ];
+    fs::write(&legacy, serde_json::to_string(&entries).unwrap()).unwrap();
+
+    let conn = open_at(&db, &legacy).unwrap();
+    let imported = all_entries(&conn);
+    assert_eq!(
+      imported.iter().map(|e| (e.id.as_str(), e.status.clone(), e.size)).collect::<Vec<_>>(),
+      vec![("a", HistoryStatus::Success, Some(10)), ("b", HistoryStatus::Failed, None)]
+    );
+    assert!(!legacy.exists());
+    assert!(legacy.with_extension("json.bak").exists());
+  }
+
+  #[test]
+  fn keeps_unparsable_legacy_json() {
+    let dir = TempDir::new();
+    let db = dir.0.join("history.db");
+    let legacy = dir.0.join("history.json");
+    fs::write(&legacy, "{ not json").unwrap();
+
+    let conn = open_at(&db, &legacy).unwrap();
+    assert!(all_entries(&conn).is_empty());
+    assert_eq!(user_version(&conn), MIGRATIONS.len());
+    assert!(legacy.exists());
+    assert!(!legacy.with_extension("json.bak").exists());
+  }
+
+  #[test]
+  fn failed_import_keeps_legacy_json() {
+    let dir = TempDir::new();
+    let db = dir.0.join("history.db");
+    let legacy = dir.0.join("history.json");
+    fs::write(&legacy, serde_json::to_string(&[entry("a", HistoryStatus::Success, None)]).unwrap())
+      .unwrap();
+
+    // 同名のテーブルがあるとマイグレーションが失敗し、元のファイルは残る
+    let conn = Connection::open(&db).unwrap();
+    conn.execute_batch("CREATE TABLE history (id TEXT PRIMARY KEY);").unwrap();
+    drop(conn);
+
+    assert!(open_at(&db, &legacy).is_err());
+    assert!(legacy.exists());
+    assert!(!legacy.with_extension("json.bak").exists());
+  }
 }
@@ -0,0 +1,392 @@
+use fs4::fs_std::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::{
+  AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+
+use crate::commands;
+use crate::download::DownloadRequest;
+use crate::cookies::{self, BrowserCookie};
+use crate::deep_link;
+
+/// 1メッセージの最大サイズ（Cookie を含むため余裕を持たせる）
+///
+/// ネイティブメッセージングホストがブラウザから受け取るメッセージの上限も同じ値にする。
+pub(crate) const MAX_MESSAGE_SIZE: usize = 8 * 1024 * 1024;
+/// 1行として読み書きする上限（受け取ったメッセージを包み直した分の余裕を持たせる）
+const MAX_LINE_SIZE: usize = MAX_MESSAGE_SIZE + 64 * 1024;
+/// 起動処理中のインスタンスが待ち受けを始めるまで引数の受け渡しを再試行する時間
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(15);
+const FORWARD_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// このプロセスが唯一のインスタンスであることを示すロック（終了まで保持する）
+static INSTANCE_LOCK: OnceLock<File> = OnceLock::new();
+
+/// 起動中のインスタンスに送るメッセージ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum InstanceMessage {
+  /// 疎通確認
+  Ping,
+  /// 2つ目に起動されたインスタンスのコマンドライン引数（ディープリンク・URL）
+  Args { args: Vec<String> },
+  /// ブラウザ拡張機能からのダウンロード要求（Cookie はそのサイトの Cookie セットとして保存する）
+  Download {
+    request: Box<DownloadRequest>,
+    #[serde(default)]
+    cookies: Vec<BrowserCookie>,
+  },
+}
+
+/// 起動中のインスタンスからの応答
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceResponse {
+  pub ok: bool,
+  /// 受け付けたダウンロードのID
+  pub id: Option<String>,
+  pub error: Option<String>,
+}
+
+impl InstanceResponse {
+  fn ok(id: Option<String>) -> Self {
+    Self { ok: true, id, error: None }
+  }
+
+  fn error(error: impl Into<String>) -> Self {
+    Self { ok: false, id: None, error: Some(error.into()) }
+  }
+}
+
+// ─── 接続先 ───────────────────────────────────────
+
+fn lock_path() -> Result<std::path::PathBuf, String> {
+  Ok(crate::utils::ensure_app_data_dir()?.join("lucentia.lock"))
+}
+
+#[cfg(unix)]
+fn socket_path() -> Result<std::path::PathBuf, String> {
+  Ok(crate::utils::ensure_app_data_dir()?.join("lucentia.sock"))
+}
+
+/// ユーザーごとに分けた名前付きパイプ
+#[cfg(windows)]
+fn pipe_name() -> String {
+  let user = std::env::var("USERNAME").unwrap_or_default();
+  let user: String = user.chars().filter(char::is_ascii_alphanumeric).collect();
+  format!(r"\\.\pipe\lucentia-{user}")
+}
+
+/// 現在のユーザーだけが接続できるパイプのセキュリティ記述子
+///
+/// 既定の DACL では同じマシンの他のユーザー（Everyone の読み取りなど）も接続できるため、
+/// プロセストークンのユーザー SID のみにフルアクセスを許可する。
+#[cfg(windows)]
+struct PipeSecurity {
+  descriptor: windows_sys::Win32::Security::PSECURITY_DESCRIPTOR,
+}
+
+// SAFETY: 記述子は作成後に変更しないヒープ上のデータで、どのスレッドから参照・解放してもよい
+#[cfg(windows)]
+unsafe impl Send for PipeSecurity {}
+
+#[cfg(windows)]
+impl PipeSecurity {
+  fn current_user() -> Result<Self, String> {
+    use windows_sys::Win32::Foundation::{CloseHandle, LocalFree, HANDLE};
+    use windows_sys::Win32::Security::Authorization::{
+      ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW,
+      SDDL_REVISION_1,
+    };
+    use windows_sys::Win32::Security::{GetTokenInformation, TokenUser, TOKEN_QUERY, TOKEN_USER};
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    let last_error = || format!("error.instance_ipc_failed:{}", std::io::Error::last_os_error());
+
+    // SAFETY: 各ハンドル・バッファは取得した関数の仕様どおりに解放する
+    unsafe {
+      let mut token: HANDLE = std::ptr::null_mut();
+      if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+        return Err(last_error());
+      }
+      let mut length = 0u32;
+      GetTokenInformation(token, TokenUser, std::ptr::null_mut(), 0, &mut length);
+      // TOKEN_USER のアラインメントを満たすよう u64 単位で確保する
+      let mut buffer = vec![0u64; (length as usize).div_ceil(8)];
+      let ok =
+        GetTokenInformation(token, TokenUser, buffer.as_mut_ptr().cast(), length, &mut length);
+      CloseHandle(token);
+      if ok == 0 {
+        return Err(last_error());
+      }
+      let user = &*buffer.as_ptr().cast::<TOKEN_USER>();
+
+      let mut sid_string = std::ptr::null_mut();
+      if ConvertSidToStringSidW(user.User.Sid, &mut sid_string) == 0 {
+        return Err(last_error());
+      }
+      let sid_len = (0..).take_while(|&i| *sid_string.add(i) != 0).count();
+      let sid = String::from_utf16_lossy(std::slice::from_raw_parts(sid_string, sid_len));
+      LocalFree(sid_string.cast());
+
+      // D:P = 継承しない DACL、GA = GENERIC_ALL
+      let sddl: Vec<u16> = format!("D:P(A;;GA;;;{sid})").encode_utf16().chain([0]).collect();
+      let mut descriptor = std::ptr::null_mut();
+      if ConvertStringSecurityDescriptorToSecurityDescriptorW(
+        sddl.as_ptr(),
+        SDDL_REVISION_1,
+        &mut descriptor,
+        std::ptr::null_mut(),
+      ) == 0
+      {
+        return Err(last_error());
+      }
+      Ok(Self { descriptor })
+    }
+  }
+
+  /// このセキュリティ記述子を使ってパイプのインスタンスを作る
+  fn create(
+    &self,
+    options: &tokio::net::windows::named_pipe::ServerOptions,
+    name: &str,
+  ) -> Result<tokio::net::windows::named_pipe::NamedPipeServer, String> {
+    use windows_sys::Win32::Security::SECURITY_ATTRIBUTES;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut attributes = SECURITY_ATTRIBUTES {
+      nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+      lpSecurityDescriptor: self.descriptor,
+      bInheritHandle: 0,
+    };
+    let attributes = std::ptr::addr_of_mut!(attributes).cast();
+    // SAFETY: attributes と記述子は CreateNamedPipeW の呼び出し中に有効
+    unsafe { options.create_with_security_attributes_raw(name, attributes) }
+      .map_err(|e| format!("error.instance_ipc_failed:{e}"))
+  }
+}
+
+#[cfg(windows)]
+impl Drop for PipeSecurity {
+  fn drop(&mut self) {
+    // SAFETY: ConvertStringSecurityDescriptorToSecurityDescriptorW が LocalAlloc で確保したもの
+    unsafe {
+      windows_sys::Win32::Foundation::LocalFree(self.descriptor);
+    }
+  }
+}
+
+// ─── 送受信 ───────────────────────────────────────
+
+/// 改行区切りの JSON を1つ読み込む
+async fn read_json<T, R>(reader: &mut BufReader<R>) -> Result<T, String>
+where
+  T: for<'de> Deserialize<'de>,
+  R: AsyncRead + Unpin,
+{
+  let mut line = String::new();
+  let mut limited = (&mut *reader).take(MAX_LINE_SIZE as u64);
+  limited
+    .read_line(&mut line)
+    .await
+    .map_err(|e| format!("error.instance_ipc_failed:{e}"))?;
+  serde_json::from_str(&line).map_err(|e| format!("error.instance_ipc_failed:{e}"))
+}
+
+async fn write_json<T, W>(writer: &mut W, value: &T) -> Result<(), String>
+where
+  T: Serialize,
+  W: AsyncWrite + Unpin,
+{
+  let mut line = serde_json::to_string(value).map_err(|e| format!("error.instance_ipc_failed:{e}"))?;
+  if line.len() >= MAX_LINE_SIZE {
+    return Err(format!("error.instance_ipc_failed:message too large: {} bytes", line.len()));
+  }
+  line.push('\n');
+  writer
+    .write_all(line.as_bytes())
+    .await
+    .map_err(|e| format!("error.instance_ipc_failed:{e}"))?;
+  writer.flush().await.map_err(|e| format!("error.instance_ipc_failed:{e}"))
+}
+
+async fn exchange<S>(stream: S, message: &InstanceMessage) -> Result<InstanceResponse, String>
+where
+  S: AsyncRead + AsyncWrite + Unpin,
+{
+  let mut stream = BufReader::new(stream);
+  write_json(stream.get_mut(), message).await?;
+  read_json(&mut stream).await
+}
+
+/// 起動中のインスタンスにメッセージを送り、応答を待つ
+///
+/// インスタンスが起動していない場合は "error.instance_not_running" を返す。
+pub async fn send(message: &InstanceMessage) -> Result<InstanceResponse, String> {
+  #[cfg(unix)]
+  let stream = tokio::net::UnixStream::connect(socket_path()?).await;
+  #[cfg(windows)]
+  let stream = tokio::net::windows::named_pipe::ClientOptions::new().open(pipe_name());
+
+  match stream {
+    Ok(stream) => exchange(stream, message).await,
+    Err(_) => Err("error.instance_not_running".to_string()),
+  }
+}
+
+/// インスタンスのロックを取得する
+///
+/// アプリの初期化前に同期的に呼び、false（他のインスタンスがロックを保持している）の場合は
+/// `forward_to_running` で引数を渡して終了する。同時に起動された場合もロックを取れるのは1つだけ。
+/// ロックファイルを作れない場合は起動を妨げないよう true を返す。
+pub fn acquire_lock() -> bool {
+  let file = lock_path().and_then(|path| {
+    OpenOptions::new()
+      .create(true)
+      .truncate(false)
+      .write(true)
+      .open(path)
+      .map_err(|e| e.to_string())
+  });
+  let file = match file {
+    Ok(file) => file,
+    Err(e) => {
+      eprintln!("インスタンスのロックファイルを開けません: {e}");
+      return true;
+    }
+  };
+  match file.try_lock_exclusive() {
+    Ok(true) => {
+      let _ = INSTANCE_LOCK.set(file);
+      true
+    }
+    Ok(false) => false,
+    Err(e) => {
+      eprintln!("インスタンスのロックを取得できません: {e}");
+      true
+    }
+  }
+}
+
+/// 起動中のインスタンスに引数を渡す
+///
+/// ロックを持つインスタンスが起動処理中の場合は、待ち受けを始めるまで再試行する。
+pub fn forward_to_running(args: Vec<String>) -> bool {
+  let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() else {
+    return false;
+  };
+  let message = InstanceMessage::Args { args };
+  runtime.block_on(async {
+    let deadline = tokio::time::Instant::now() + FORWARD_TIMEOUT;
+    loop {
+      match send(&message).await {
+        Err(e) if e == "error.instance_not_running" && tokio::time::Instant::now() < deadline => {
+          tokio::time::sleep(FORWARD_POLL_INTERVAL).await;
+        }
+        result => return result.is_ok_and(|response| response.ok),
+      }
+    }
+  })
+}
+
+/// 受信したメッセージを処理する
+fn handle_message(app_handle: &tauri::AppHandle, message: InstanceMessage) -> InstanceResponse {
+  match message {
+    InstanceMessage::Ping => InstanceResponse::ok(None),
+    InstanceMessage::Args { args } => {
+      deep_link::handle_args(app_handle, &args);
+      deep_link::focus_main_window(app_handle);
+      InstanceResponse::ok(None)
+    }
+    InstanceMessage::Download { request, cookies } => {
+      if !cookies.is_empty() {
+        if let Err(e) = cookies::store_browser_cookies(&request.url, &cookies) {
+          log::warn!("拡張機能から受け取った Cookie を保存できません: {e}");
+        }
+      }
+      match commands::enqueue_download(app_handle, *request) {
+        Ok(id) => InstanceResponse::ok(Some(id)),
+        Err(e) => InstanceResponse::error(e),
+      }
+    }
+  }
+}
+
+async fn serve_connection<S>(app_handle: tauri::AppHandle, stream: S)
+where
+  S: AsyncRead + AsyncWrite + Unpin,
+{
+  let mut stream = BufReader::new(stream);
+  let response = match read_json::<InstanceMessage, _>(&mut stream).await {
+    Ok(message) => handle_message(&app_handle, message),
+    Err(e) => InstanceResponse::error(e),
+  };
+  if let Err(e) = write_json(stream.get_mut(), &response).await {
+    log::warn!("インスタンス間通信の応答に失敗: {e}");
+  }
+}
+
+// ─── 待ち受け ─────────────────────────────────────
+
+/// 他のプロセス（ネイティブメッセージングホストなど）からの要求を待ち受ける
+///
+/// `acquire_lock` でロックを取得したインスタンスから呼ぶ。ロックがない場合
+/// （ロックファイルを作れなかった場合）は、別のインスタンスが待ち受けていれば何もしない。
+pub fn listen(app_handle: tauri::AppHandle) {
+  tauri::async_runtime::spawn(async move {
+    if INSTANCE_LOCK.get().is_none() && send(&InstanceMessage::Ping).await.is_ok() {
+      log::warn!("別のインスタンスが起動中のため、インスタンス間通信を待ち受けません");
+      return;
+    }
+    if let Err(e) = accept_loop(app_handle).await {
+      log::error!("インスタンス間通信を開始できません: {e}");
+    }
+  });
+}
+
+#[cfg(unix)]
+async fn accept_loop(app_handle: tauri::AppHandle) -> Result<(), String> {
+  let path = socket_path()?;
+  // ロックを持っていれば、残っているソケットファイルは前回異常終了したときのもの
+  if INSTANCE_LOCK.get().is_some() {
+    let _ = std::fs::remove_file(&path);
+  }
+
+  let listener =
+    tokio::net::UnixListener::bind(&path).map_err(|e| format!("error.instance_ipc_failed:{e}"))?;
+  crate::utils::restrict_permissions(&path, 0o600)?;
+  log::info!("インスタンス間通信を待ち受けます: {}", path.display());
+
+  loop {
+    match listener.accept().await {
+      Ok((stream, _)) => {
+        tauri::async_runtime::spawn(serve_connection(app_handle.clone(), stream));
+      }
+      Err(e) => log::warn!("インスタンス間通信の接続を受け付けられません: {e}"),
+    }
+  }
+}
+
+#[cfg(windows)]
+async fn accept_loop(app_handle: tauri::AppHandle) -> Result<(), String> {
+  use tokio::net::windows::named_pipe::ServerOptions;
+
+  let name = pipe_name();
+  let security = PipeSecurity::current_user()?;
+  let mut server = security.create(ServerOptions::new().first_pipe_instance(true), &name)?;
+  log::info!("インスタンス間通信を待ち受けます: {name}");
+
+  loop {
+    if let Err(e) = server.connect().await {
+      log::warn!("インスタンス間通信の接続を受け付けられません: {e}");
+      continue;
+    }
+    // 次のクライアント用のインスタンスを先に作ってから現在の接続を処理する
+    let connected = server;
+    server = security.create(&ServerOptions::new(), &name)?;
+    tauri::async_runtime::spawn(serve_connection(app_handle.clone(), connected));
+  }
+}
@@ -1,30 +1,74 @@
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
+#[cfg(feature = "desktop")]
+pub mod api_server;
+#[cfg(feature = "desktop")]
+pub mod clipboard_watcher;
+#[cfg(feature = "desktop")]
 pub mod commands;
+pub mod cookies;
+pub mod credentials;
+#[cfg(feature = "desktop")]
+pub mod deep_link;
+pub mod diagnostics;
+pub mod doctor;
+pub mod download;
 pub mod downloader;
+pub mod environment;
 pub mod history;
+#[cfg(feature = "desktop")]
+pub mod instance;
+pub mod logging;
+#[cfg(feature = "desktop")]
+pub mod native_messaging;
+pub mod network;
+pub mod presets;
+pub mod redact;
 pub mod settings;
+pub mod url_import;
 pub mod utils;
+#[cfg(feature = "desktop")]
+pub mod watch_folder;
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
+/// コマンド・プラグイン・環境変数・バイナリのセットアップを含むアプリのビルダー
+///
+/// デスクトップ・モバイルのどちらのエントリポイントもこれを使う。
+#[cfg(feature = "desktop")]
+pub fn app_builder() -> tauri::Builder<tauri::Wry> {
+  use tauri_plugin_deep_link::DeepLinkExt;
   use crate::commands::{
     clear_cache, clear_history, delete_downloaded_files, download_metadata, download_video,
-    get_download_stats, get_download_statuses, get_history, change_save_path, get_settings,
-    get_yt_dlp_version, initialize_app, is_initialized, is_setup_complete,
-    list_downloaded_files, open_file, open_file_in_folder, reset_settings, save_settings,
-    update_yt_dlp, validate_save_path,
+    get_binaries_status, get_deno_version, get_download_stats, get_download_statuses,
+    get_ffmpeg_version, get_history, get_setup_status, change_save_path, get_settings,
+    get_yt_dlp_version, initialize_app, install_binaries_from_archive, is_initialized,
+    is_setup_complete, list_downloaded_files, open_file, open_file_in_folder, repair_binaries,
+    reset_settings, retry_setup, save_settings, test_proxy, update_deno, update_ffmpeg,
+    update_yt_dlp, validate_save_path, import_cookies, list_cookie_sets, replace_cookie_set,
+    delete_cookie_set, list_credentials, save_credential, delete_credential, get_logs,
+    get_download_log, open_log_folder, export_diagnostics, run_doctor, get_api_status,
+    regenerate_api_token, cancel_download, install_native_host, uninstall_native_host,
+    get_native_host_status, take_pending_downloads, import_urls, enqueue_imported_urls,
+    list_presets, save_preset, delete_preset, export_presets, import_presets, retry_history_entry,
+    retry_all_failed,
   };
 
+  environment::prepare_environment();
+  let app_settings = settings::load_settings().unwrap_or_default();
+
   tauri::Builder::default()
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_notification::init())
+    .plugin(tauri_plugin_deep_link::init())
+    .plugin(tauri_plugin_clipboard_manager::init())
     .invoke_handler(tauri::generate_handler![
       download_video,
       download_metadata,
       get_download_statuses,
       is_initialized,
       is_setup_complete,
+      get_setup_status,
+      retry_setup,
+      repair_binaries,
       initialize_app,
       validate_save_path,
       change_save_path,
@@ -35,23 +79,99 @@ pub fn run() {
       clear_history,
       update_yt_dlp,
       get_yt_dlp_version,
+      update_ffmpeg,
+      get_ffmpeg_version,
+      update_deno,
+      get_deno_version,
+      get_binaries_status,
+      install_binaries_from_archive,
       list_downloaded_files,
       delete_downloaded_files,
       open_file,
       open_file_in_folder,
       reset_settings,
       clear_cache,
+      test_proxy,
+      import_cookies,
+      list_cookie_sets,
+      replace_cookie_set,
+      delete_cookie_set,
+      list_credentials,
+      save_credential,
+      delete_credential,
+      get_logs,
+      get_download_log,
+      open_log_folder,
+      export_diagnostics,
+      run_doctor,
+      get_api_status,
+      regenerate_api_token,
+      cancel_download,
+      install_native_host,
+      uninstall_native_host,
+      get_native_host_status,
+      take_pending_downloads,
+      import_urls,
+      enqueue_imported_urls,
+      list_presets,
+      save_preset,
+      delete_preset,
+      export_presets,
+      import_presets,
+      retry_history_entry,
+      retry_all_failed,
     ])
-    .setup(|app| {
-      if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
+    .setup(move |app| {
+      // リリースビルドでも原因を追えるよう、ログは常にファイルへ出力する
+      app.handle().plugin(logging::plugin())?;
+      logging::apply_level(app_settings.log_level);
+
+      // yt-dlp, Deno, FFmpeg のダウンロードを非同期で並行実行（進捗イベント付き）
+      downloader::setup_binaries(app.handle().clone());
+
+      // 外部ツール向けのローカル API（既定では無効）
+      api_server::apply(app.handle(), &app_settings.api);
+
+      // クリップボードの URL 検出（既定では無効）
+      clipboard_watcher::apply(app.handle(), &app_settings.clipboard);
+
+      // 監視フォルダに置かれた URL ファイルの取り込み（既定では無効）
+      watch_folder::apply(app.handle(), &app_settings.watch_folder);
+
+      // ネイティブメッセージングホストや2つ目のインスタンスからの要求を受け付ける
+      instance::listen(app.handle().clone());
+
+      // lucentia:// リンク（macOS は起動中のアプリにイベントで届く）と起動時の引数
+      #[cfg(any(target_os = "linux", windows))]
+      if let Err(e) = app.deep_link().register_all() {
+        log::warn!("URL スキームを登録できません: {e}");
       }
+      let handle = app.handle().clone();
+      app.deep_link().on_open_url(move |event| {
+        let links: Vec<String> = event.urls().iter().map(ToString::to_string).collect();
+        deep_link::handle_args(&handle, &links);
+        deep_link::focus_main_window(&handle);
+      });
+      let args: Vec<String> = std::env::args().skip(1).collect();
+      deep_link::handle_args(app.handle(), &args);
       Ok(())
     })
+}
+
+#[cfg(feature = "desktop")]
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+  // 2つ目の起動は引数（ディープリンク・URL）を起動中のインスタンスに渡して終了する。
+  // ロックはアプリの初期化前に取得し、同時に起動されても1つだけが残るようにする
+  #[cfg(desktop)]
+  if !instance::acquire_lock() {
+    if !instance::forward_to_running(std::env::args().skip(1).collect()) {
+      eprintln!("起動中のインスタンスに引数を渡せませんでした");
+    }
+    return;
+  }
+
+  app_builder()
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
@@ -0,0 +1,242 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "desktop")]
+use tauri::plugin::TauriPlugin;
+#[cfg(feature = "desktop")]
+use tauri::Runtime;
+#[cfg(feature = "desktop")]
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+use crate::redact::redact;
+use crate::settings::LogLevel;
+use crate::utils::ensure_app_data_dir;
+
+/// アプリログのファイル名（拡張子なし。ローテーション時は日時が付与される）
+const LOG_FILE_NAME: &str = "lucentia";
+/// 1ファイルあたりの最大サイズ（5MB）
+#[cfg(feature = "desktop")]
+const MAX_LOG_FILE_SIZE: u128 = 5 * 1024 * 1024;
+/// ローテーション後に残すファイル数
+#[cfg(feature = "desktop")]
+const KEEP_LOG_FILES: usize = 5;
+/// 残すダウンロードごとのログ数
+const KEEP_DOWNLOAD_LOGS: usize = 200;
+/// get_logs で返す行数の既定値と上限
+const DEFAULT_LOG_LIMIT: usize = 500;
+const MAX_LOG_LIMIT: usize = 5000;
+
+/// 詳細ログを出すと量が多すぎる依存クレート
+#[cfg(feature = "desktop")]
+const NOISY_TARGETS: &[&str] = &["hyper", "hyper_util", "reqwest", "rustls", "tao", "wry", "zbus"];
+
+impl From<LogLevel> for log::LevelFilter {
+  fn from(level: LogLevel) -> Self {
+    match level {
+      LogLevel::Error => log::LevelFilter::Error,
+      LogLevel::Warn => log::LevelFilter::Warn,
+      LogLevel::Info => log::LevelFilter::Info,
+      LogLevel::Debug => log::LevelFilter::Debug,
+      LogLevel::Trace => log::LevelFilter::Trace,
+    }
+  }
+}
+
+/// ログファイルのディレクトリを取得する
+pub fn log_dir() -> Result<PathBuf, String> {
+  let dir = ensure_app_data_dir()?.join("logs");
+  fs::create_dir_all(&dir).map_err(|e| format!("error.dir_create_failed:{e}"))?;
+  Ok(dir)
+}
+
+fn download_log_dir() -> Result<PathBuf, String> {
+  let dir = log_dir()?.join("downloads");
+  fs::create_dir_all(&dir).map_err(|e| format!("error.dir_create_failed:{e}"))?;
+  Ok(dir)
+}
+
+/// ログプラグインを生成する（標準出力とサイズローテーション付きのファイルに出力）
+///
+/// 出力レベルは [`apply_level`] で実行中に切り替えるため、プラグイン側では絞らない。
+#[cfg(feature = "desktop")]
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+  let mut targets = vec![Target::new(TargetKind::Stdout)];
+  match log_dir() {
+    Ok(path) => targets.push(Target::new(TargetKind::Folder {
+      path,
+      file_name: Some(LOG_FILE_NAME.to_string()),
+    })),
+    Err(e) => eprintln!("ログディレクトリを作成できません: {e}"),
+  }
+
+  let mut builder = tauri_plugin_log::Builder::new()
+    .clear_targets()
+    .targets(targets)
+    .rotation_strategy(RotationStrategy::KeepSome(KEEP_LOG_FILES))
+    .max_file_size(MAX_LOG_FILE_SIZE)
+    .level(log::LevelFilter::Trace)
+    // すべてのログ出力から秘密情報とホームディレクトリのパスを伏せる
+    .format(|out, message, record| {
+      out.finish(format_args!(
+        "{}[{}][{}] {}",
+        chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+        record.level(),
+        record.target(),
+        redact(&message.to_string())
+      ));
+    });
+
+  for target in NOISY_TARGETS {
+    builder = builder.level_for(*target, log::LevelFilter::Warn);
+  }
+
+  builder.build()
+}
+
+/// 設定のログレベルを反映する
+pub fn apply_level(level: LogLevel) {
+  log::set_max_level(level.into());
+}
+
+/// アプリログを新しい順に読み、条件に合う行を古い順で返す
+///
+/// `filter` は大文字小文字を区別しない部分一致（"ERROR" などレベル指定にも使える）。
+pub fn read_logs(filter: Option<&str>, limit: Option<usize>) -> Result<Vec<String>, String> {
+  let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT).clamp(1, MAX_LOG_LIMIT);
+  let filter = filter.map(str::to_lowercase).filter(|f| !f.is_empty());
+
+  let mut files: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(log_dir()?)
+    .map_err(|e| format!("error.log_read_failed:{e}"))?
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .filter(|path| {
+      path.is_file()
+        && path.extension().is_some_and(|ext| ext == "log")
+        && path
+          .file_name()
+          .is_some_and(|name| name.to_string_lossy().starts_with(LOG_FILE_NAME))
+    })
+    .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+    .collect();
+  files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+  let mut lines = Vec::new();
+  for (_, path) in files {
+    let content = fs::read(&path).map_err(|e| format!("error.log_read_failed:{e}"))?;
+    let content = String::from_utf8_lossy(&content);
+
+    for line in content.lines().rev() {
+      if filter
+        .as_ref()
+        .map_or(true, |f| line.to_lowercase().contains(f))
+      {
+        lines.push(line.to_string());
+        if lines.len() >= limit {
+          lines.reverse();
+          return Ok(lines);
+        }
+      }
+    }
+  }
+
+  lines.reverse();
+  Ok(lines)
+}
+
+// ─── ダウンロードごとのログ ───────────────────────────
+
+/// ダウンロードIDをファイル名に使えるか（フロントエンドから渡される値のため検証する）
+pub(crate) fn is_valid_download_id(download_id: &str) -> bool {
+  !download_id.is_empty()
+    && download_id.len() <= 128
+    && download_id
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn download_log_path(download_id: &str) -> Result<PathBuf, String> {
+  if !is_valid_download_id(download_id) {
+    return Err("error.invalid_download_id".to_string());
+  }
+  Ok(download_log_dir()?.join(format!("{download_id}.log")))
+}
+
+/// 古いダウンロードログを削除する
+fn prune_download_logs() {
+  let Ok(dir) = download_log_dir() else {
+    return;
+  };
+  let Ok(entries) = fs::read_dir(&dir) else {
+    return;
+  };
+
+  let mut logs: Vec<(std::time::SystemTime, PathBuf)> = entries
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+    .collect();
+  if logs.len() <= KEEP_DOWNLOAD_LOGS {
+    return;
+  }
+
+  logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+  for (_, path) in logs.into_iter().skip(KEEP_DOWNLOAD_LOGS) {
+    let _ = fs::remove_file(path);
+  }
+}
+
+/// yt-dlp の stdout/stderr を1件のダウンロードごとに保存するログ
+///
+/// 作成に失敗した場合は何も書き込まない（ダウンロード自体は継続する）。
+#[derive(Clone)]
+pub struct DownloadLog {
+  file: Option<Arc<Mutex<File>>>,
+}
+
+impl DownloadLog {
+  pub fn create(download_id: &str) -> Self {
+    prune_download_logs();
+
+    let file = download_log_path(download_id).and_then(|path| {
+      OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("error.log_write_failed:{e}"))
+    });
+
+    match file {
+      Ok(file) => Self { file: Some(Arc::new(Mutex::new(file))) },
+      Err(e) => {
+        log::warn!("ダウンロードログを作成できません ({download_id}): {e}");
+        Self { file: None }
+      }
+    }
+  }
+
+  /// 1行書き込む（秘密情報は伏せる）
+  pub fn write(&self, stream: &str, line: &str) {
+    let Some(file) = &self.file else {
+      return;
+    };
+    let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = writeln!(
+      file,
+      "{} [{stream}] {}",
+      chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+      redact(line)
+    );
+  }
+}
+
+/// ダウンロードIDに対応する yt-dlp のログを読み込む
+pub fn read_download_log(download_id: &str) -> Result<String, String> {
+  let path = download_log_path(download_id)?;
+  if !path.exists() {
+    return Err(format!("error.log_not_found:{download_id}"));
+  }
+
+  let content = fs::read(&path).map_err(|e| format!("error.log_read_failed:{e}"))?;
+  Ok(String::from_utf8_lossy(&content).into_owned())
+}
@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::download::DownloadRequest;
+use crate::cookies::BrowserCookie;
+use crate::instance::{self, InstanceMessage, InstanceResponse};
+use crate::network::HeaderOverrides;
+use crate::utils::ensure_app_data_dir;
+
+/// ネイティブメッセージングホストとして起動する引数
+pub const HOST_ARG: &str = "--native-messaging-host";
+/// ホスト名（マニフェストのファイル名・レジストリのキー名）
+const HOST_NAME: &str = "com.lucentia.native_host";
+/// アプリを起動してから IPC の待ち受けが始まるまで待つ時間
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(15);
+const LAUNCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// ネイティブメッセージングに対応するブラウザ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NativeHostBrowser {
+  Chrome,
+  Firefox,
+}
+
+/// ホストマニフェストのインストール状況
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeHostStatus {
+  pub browser: NativeHostBrowser,
+  pub installed: bool,
+  pub manifest_path: Option<String>,
+}
+
+/// 拡張機能から受け取るメッセージ
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+enum HostRequest {
+  /// 疎通確認（アプリが起動していなくても応答する）
+  Ping,
+  /// 現在のタブの URL をダウンロードする
+  Download {
+    #[serde(flatten)]
+    request: Box<DownloadRequest>,
+    /// ページのリファラー（headers.referer が未指定の場合に使う）
+    referer: Option<String>,
+    #[serde(default)]
+    cookies: Vec<BrowserCookie>,
+  },
+}
+
+/// 拡張機能に返すメッセージ
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HostResponse {
+  ok: bool,
+  id: Option<String>,
+  error: Option<String>,
+  version: &'static str,
+  /// アプリが起動しているか（ping のみ）
+  #[serde(skip_serializing_if = "Option::is_none")]
+  running: Option<bool>,
+}
+
+impl HostResponse {
+  fn from_instance(response: InstanceResponse) -> Self {
+    Self {
+      ok: response.ok,
+      id: response.id,
+      error: response.error,
+      version: env!("CARGO_PKG_VERSION"),
+      running: None,
+    }
+  }
+
+  fn error(error: impl Into<String>) -> Self {
+    Self {
+      ok: false,
+      id: None,
+      error: Some(error.into()),
+      version: env!("CARGO_PKG_VERSION"),
+      running: None,
+    }
+  }
+}
+
+// ─── ホストモード ───────────────────────────────────
+
+/// ネイティブメッセージングホストとして起動されたか
+pub fn is_host_invocation() -> bool {
+  std::env::args().skip(1).any(|arg| arg == HOST_ARG)
+}
+
+/// 長さ（ネイティブエンディアンの u32）+ JSON のメッセージを1つ読み込む
+///
+/// 標準入力が閉じられた（ブラウザ側が切断した）場合は None を返す。
+fn read_message(input: &mut impl Read) -> Result<Option<Vec<u8>>, String> {
+  let mut length = [0u8; 4];
+  match input.read_exact(&mut length) {
+    Ok(()) => {}
+    Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+    Err(e) => return Err(e.to_string()),
+  }
+
+  let length = u32::from_ne_bytes(length) as usize;
+  // Chrome の仕様では拡張機能→ホストは 64MiB まで送れるが、アプリに渡せる大きさに揃える
+  if length > instance::MAX_MESSAGE_SIZE {
+    return Err(format!("message too large: {length} bytes"));
+  }
+
+  let mut message = vec![0u8; length];
+  input.read_exact(&mut message).map_err(|e| e.to_string())?;
+  Ok(Some(message))
+}
+
+fn write_message(output: &mut impl Write, response: &HostResponse) -> Result<(), String> {
+  let body = serde_json::to_vec(response).map_err(|e| e.to_string())?;
+  let length = u32::try_from(body.len()).map_err(|e| e.to_string())?;
+  output.write_all(&length.to_ne_bytes()).map_err(|e| e.to_string())?;
+  output.write_all(&body).map_err(|e| e.to_string())?;
+  output.flush().map_err(|e| e.to_string())
+}
+
+/// 起動中のインスタンスに転送する（起動していなければアプリを起動して待つ）
+async fn forward(message: &InstanceMessage) -> Result<InstanceResponse, String> {
+  match instance::send(message).await {
+    Err(e) if e == "error.instance_not_running" => {}
+    result => return result,
+  }
+
+  let exe = std::env::current_exe().map_err(|e| format!("error.instance_launch_failed:{e}"))?;
+  crate::downloader::silent_command(&exe)
+    .stdin(std::process::Stdio::null())
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .spawn()
+    .map_err(|e| format!("error.instance_launch_failed:{e}"))?;
+
+  let deadline = tokio::time::Instant::now() + LAUNCH_TIMEOUT;
+  loop {
+    tokio::time::sleep(LAUNCH_POLL_INTERVAL).await;
+    match instance::send(message).await {
+      Err(e) if e == "error.instance_not_running" && tokio::time::Instant::now() < deadline => {}
+      result => return result,
+    }
+  }
+}
+
+async fn handle_request(body: &[u8]) -> HostResponse {
+  let request: HostRequest = match serde_json::from_slice(body) {
+    Ok(request) => request,
+    Err(e) => return HostResponse::error(format!("error.native_host_invalid_message:{e}")),
+  };
+
+  match request {
+    HostRequest::Ping => {
+      let running = instance::send(&InstanceMessage::Ping).await.is_ok();
+      let pong = InstanceResponse { ok: true, ..InstanceResponse::default() };
+      HostResponse { running: Some(running), ..HostResponse::from_instance(pong) }
+    }
+    HostRequest::Download { mut request, referer, cookies } => {
+      if let Some(referer) = referer.filter(|r| !r.is_empty()) {
+        let headers = request.headers.get_or_insert_with(HeaderOverrides::default);
+        headers.referer.get_or_insert(referer);
+      }
+      match forward(&InstanceMessage::Download { request, cookies }).await {
+        Ok(response) => HostResponse::from_instance(response),
+        Err(e) => HostResponse::error(e),
+      }
+    }
+  }
+}
+
+/// ネイティブメッセージングホストとして標準入出力でやり取りし、終了コードを返す
+///
+/// ブラウザが標準入力を閉じるまでメッセージを処理し続ける。
+/// 標準出力はプロトコルに使うため、診断メッセージは標準エラー出力に書く。
+pub fn run_host() -> i32 {
+  let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+    Ok(runtime) => runtime,
+    Err(e) => {
+      eprintln!("lucentia native host: {e}");
+      return 1;
+    }
+  };
+
+  let mut stdin = std::io::stdin().lock();
+  let mut stdout = std::io::stdout().lock();
+
+  loop {
+    let body = match read_message(&mut stdin) {
+      Ok(Some(body)) => body,
+      Ok(None) => return 0,
+      Err(e) => {
+        eprintln!("lucentia native host: {e}");
+        return 1;
+      }
+    };
+
+    let response = runtime.block_on(handle_request(&body));
+    if let Err(e) = write_message(&mut stdout, &response) {
+      eprintln!("lucentia native host: {e}");
+      return 1;
+    }
+  }
+}
+
+// ─── マニフェストのインストール ────────────────────────
+
+/// ブラウザから起動されるラッパースクリプト
+///
+/// マニフェストの `path` には引数を書けないため、ホストモードの引数を付けて本体を起動する。
+fn write_host_launcher() -> Result<PathBuf, String> {
+  let dir = ensure_app_data_dir()?.join("native-host");
+  fs::create_dir_all(&dir).map_err(|e| format!("error.dir_create_failed:{e}"))?;
+  let exe = std::env::current_exe().map_err(|e| format!("error.native_host_install_failed:{e}"))?;
+
+  #[cfg(windows)]
+  let (path, content) = (
+    dir.join("lucentia-native-host.bat"),
+    format!("@echo off\r\n\"{}\" {HOST_ARG} %*\r\n", exe.display()),
+  );
+  #[cfg(not(windows))]
+  let (path, content) = (
+    dir.join("lucentia-native-host"),
+    format!("#!/bin/sh\nexec \"{}\" {HOST_ARG} \"$@\"\n", exe.display()),
+  );
+
+  fs::write(&path, content).map_err(|e| format!("error.native_host_install_failed:{e}"))?;
+  crate::utils::restrict_permissions(&path, 0o700)?;
+  Ok(path)
+}
+
+/// マニフェストの配置先（Windows ではアプリデータに置き、レジストリから参照する）
+fn manifest_path(browser: NativeHostBrowser) -> Result<PathBuf, String> {
+  let file_name = format!("{HOST_NAME}.json");
+
+  if cfg!(windows) {
+    let name = match browser {
+      NativeHostBrowser::Chrome => "chrome",
+      NativeHostBrowser::Firefox => "firefox",
+    };
+    return Ok(ensure_app_data_dir()?.join("native-host").join(name).join(file_name));
+  }
+
+  // macOS の config_dir は ~/Library/Application Support
+  let macos = cfg!(target_os = "macos");
+  let (base, rel) = match browser {
+    NativeHostBrowser::Chrome if macos => (dirs::config_dir(), "Google/Chrome/NativeMessagingHosts"),
+    NativeHostBrowser::Chrome => (dirs::config_dir(), "google-chrome/NativeMessagingHosts"),
+    NativeHostBrowser::Firefox if macos => (dirs::config_dir(), "Mozilla/NativeMessagingHosts"),
+    NativeHostBrowser::Firefox => (dirs::home_dir(), ".mozilla/native-messaging-hosts"),
+  };
+  let base =
+    base.ok_or_else(|| "error.native_host_install_failed:home directory not found".to_string())?;
+  Ok(base.join(rel).join(file_name))
+}
+
+/// Windows でマニフェストを登録するレジストリキー
+#[cfg(windows)]
+fn registry_key(browser: NativeHostBrowser) -> String {
+  match browser {
+    NativeHostBrowser::Chrome => {
+      format!(r"HKCU\Software\Google\Chrome\NativeMessagingHosts\{HOST_NAME}")
+    }
+    NativeHostBrowser::Firefox => {
+      format!(r"HKCU\Software\Mozilla\NativeMessagingHosts\{HOST_NAME}")
+    }
+  }
+}
+
+#[cfg(windows)]
+fn run_reg(args: &[&str]) -> Result<(), String> {
+  let status = crate::downloader::silent_command(std::path::Path::new("reg"))
+    .args(args)
+    .status()
+    .map_err(|e| format!("error.native_host_install_failed:{e}"))?;
+  if status.success() {
+    Ok(())
+  } else {
+    Err(format!("error.native_host_install_failed:reg exited with {status}"))
+  }
+}
+
+/// 拡張機能IDを検証する（マニフェストに埋め込むため）
+///
+/// Chrome は 32 文字の a-p、Firefox はメールアドレス形式か {UUID} 形式。
+fn validate_extension_id(browser: NativeHostBrowser, extension_id: &str) -> Result<(), String> {
+  let valid = match browser {
+    NativeHostBrowser::Chrome => {
+      extension_id.len() == 32 && extension_id.chars().all(|c| ('a'..='p').contains(&c))
+    }
+    NativeHostBrowser::Firefox => {
+      !extension_id.is_empty()
+        && extension_id.len() <= 255
+        && extension_id
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || "@._-{}".contains(c))
+    }
+  };
+  if valid {
+    Ok(())
+  } else {
+    Err(format!("error.native_host_invalid_extension_id:{extension_id}"))
+  }
+}
+
+/// ホストマニフェストをインストールし、その配置先を返す
+pub fn install_host(browser: NativeHostBrowser, extension_id: &str) -> Result<PathBuf, String> {
+  let extension_id = extension_id.trim();
+  validate_extension_id(browser, extension_id)?;
+
+  let launcher = write_host_launcher()?;
+  let mut manifest = serde_json::json!({
+    "name": HOST_NAME,
+    "description": "Lucentia browser extension bridge",
+    "path": launcher,
+    "type": "stdio",
+  });
+  match browser {
+    NativeHostBrowser::Chrome => {
+      manifest["allowed_origins"] = serde_json::json!([format!("chrome-extension://{extension_id}/")]);
+    }
+    NativeHostBrowser::Firefox => {
+      manifest["allowed_extensions"] = serde_json::json!([extension_id]);
+    }
+  }
+
+  let path = manifest_path(browser)?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).map_err(|e| format!("error.dir_create_failed:{e}"))?;
+  }
+  let content = serde_json::to_string_pretty(&manifest)
+    .map_err(|e| format!("error.native_host_install_failed:{e}"))?;
+  fs::write(&path, content).map_err(|e| format!("error.native_host_install_failed:{e}"))?;
+
+  #[cfg(windows)]
+  {
+    let key = registry_key(browser);
+    let manifest = path.to_string_lossy();
+    run_reg(&["add", key.as_str(), "/ve", "/t", "REG_SZ", "/d", manifest.as_ref(), "/f"])?;
+  }
+
+  log::info!("ネイティブメッセージングホストを登録しました ({browser:?}): {}", path.display());
+  Ok(path)
+}
+
+/// ホストマニフェストを削除する
+pub fn uninstall_host(browser: NativeHostBrowser) -> Result<(), String> {
+  let path = manifest_path(browser)?;
+  match fs::remove_file(&path) {
+    Ok(()) => {}
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+    Err(e) => return Err(format!("error.native_host_uninstall_failed:{e}")),
+  }
+
+  #[cfg(windows)]
+  {
+    // 未登録の場合も失敗扱いにしない
+    let _ = run_reg(&["delete", registry_key(browser).as_str(), "/f"]);
+  }
+
+  log::info!("ネイティブメッセージングホストの登録を解除しました ({browser:?})");
+  Ok(())
+}
+
+/// 各ブラウザへのインストール状況を取得する
+pub fn host_status() -> Vec<NativeHostStatus> {
+  [NativeHostBrowser::Chrome, NativeHostBrowser::Firefox]
+    .into_iter()
+    .map(|browser| {
+      let path = manifest_path(browser).ok();
+      NativeHostStatus {
+        browser,
+        installed: path.as_ref().is_some_and(|p| p.is_file()),
+        manifest_path: path.map(|p| p.to_string_lossy().into_owned()),
+      }
+    })
+    .collect()
+}
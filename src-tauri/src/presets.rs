@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::settings::{self, DownloadOptions, DownloadPreset};
+
+/// yt-dlp でダウンロード可能な音声フォーマット
+pub(crate) const SUPPORTED_AUDIO_FORMATS: &[&str] = &["mp3", "m4a"];
+/// 映像の出力コンテナ（--merge-output-format）
+pub(crate) const SUPPORTED_VIDEO_FORMATS: &[&str] = &["mp4", "mkv", "webm"];
+/// プリセット名の最大文字数
+const MAX_NAME_LENGTH: usize = 64;
+/// エクスポートするファイルの形式バージョン
+const EXPORT_VERSION: u32 = 1;
+
+/// プリセットの値を項目ごとに上書きする指定（None の項目はプリセットの値を使う）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DownloadOptionsOverride {
+  pub audio_only: Option<bool>,
+  pub folder_path: Option<String>,
+  pub best_quality: Option<bool>,
+  pub download_subtitles: Option<bool>,
+  pub preferred_format: Option<String>,
+}
+
+impl DownloadOptionsOverride {
+  /// `base` に上書きを適用する
+  pub fn apply(&self, base: DownloadOptions) -> DownloadOptions {
+    DownloadOptions {
+      audio_only: self.audio_only.unwrap_or(base.audio_only),
+      folder_path: self.folder_path.clone().or(base.folder_path),
+      best_quality: self.best_quality.unwrap_or(base.best_quality),
+      download_subtitles: self.download_subtitles.unwrap_or(base.download_subtitles),
+      preferred_format: self.preferred_format.clone().or(base.preferred_format),
+    }
+  }
+}
+
+impl From<DownloadOptions> for DownloadOptionsOverride {
+  fn from(options: DownloadOptions) -> Self {
+    Self {
+      audio_only: Some(options.audio_only),
+      folder_path: options.folder_path,
+      best_quality: Some(options.best_quality),
+      download_subtitles: Some(options.download_subtitles),
+      preferred_format: options.preferred_format,
+    }
+  }
+}
+
+/// エクスポートファイルの内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PresetExport {
+  version: u32,
+  presets: Vec<DownloadPreset>,
+}
+
+/// インポートするファイル（エクスポート形式と、プリセットの配列のみのどちらも受け付ける）
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PresetImport {
+  Export(PresetExport),
+  List(Vec<DownloadPreset>),
+}
+
+/// 名前のプリセットを探す（大文字小文字は区別しない）
+fn position(presets: &[DownloadPreset], name: &str) -> Option<usize> {
+  let name = name.trim();
+  presets.iter().position(|preset| preset.name.eq_ignore_ascii_case(name))
+}
+
+/// ダウンロードのオプションを検証・補正する
+///
+/// 音声のみの場合は画質・字幕の指定を無視し、フォーマットは種類に合うものだけを受け付ける。
+pub fn normalize_options(options: &mut DownloadOptions) -> Result<(), String> {
+  if options.audio_only {
+    options.best_quality = false;
+    options.download_subtitles = false;
+  }
+
+  if let Some(format) = options.preferred_format.as_deref() {
+    let supported =
+      if options.audio_only { SUPPORTED_AUDIO_FORMATS } else { SUPPORTED_VIDEO_FORMATS };
+    if !supported.contains(&format) {
+      return Err(format!("error.unsupported_format:{format}"));
+    }
+  }
+  Ok(())
+}
+
+/// プリセットを検証・補正する
+fn validate_preset(preset: &mut DownloadPreset) -> Result<(), String> {
+  preset.name = preset.name.trim().to_string();
+  if preset.name.is_empty() || preset.name.chars().count() > MAX_NAME_LENGTH {
+    return Err("error.preset_invalid_name".to_string());
+  }
+  normalize_options(&mut preset.options)
+}
+
+/// プリセットとその上書きから実際のオプションを求め、検証・補正する
+///
+/// プリセット名が None の場合は既定のオプションに上書きを適用する。`download_video`・
+/// API・ディープリンクなど経路によらず、不正なフォーマットはエラーになる。
+pub fn resolve(
+  preset: Option<&str>,
+  overrides: &DownloadOptionsOverride,
+) -> Result<DownloadOptions, String> {
+  let base = match preset.map(str::trim).filter(|name| !name.is_empty()) {
+    Some(name) => get_preset(name)?.options,
+    None => DownloadOptions::default(),
+  };
+  let mut options = overrides.apply(base);
+  normalize_options(&mut options)?;
+  Ok(options)
+}
+
+// ─── CRUD ───────────────────────────────────────
+
+pub fn list_presets() -> Result<Vec<DownloadPreset>, String> {
+  Ok(settings::load_settings()?.presets)
+}
+
+pub fn get_preset(name: &str) -> Result<DownloadPreset, String> {
+  let presets = list_presets()?;
+  position(&presets, name)
+    .map(|index| presets[index].clone())
+    .ok_or_else(|| format!("error.preset_not_found:{name}"))
+}
+
+/// プリセットを保存する（同じ名前があれば置き換える）
+///
+/// `previous_name` を指定した場合はそのプリセットの名前を変更する。
+pub fn save_preset(
+  mut preset: DownloadPreset,
+  previous_name: Option<&str>,
+) -> Result<Vec<DownloadPreset>, String> {
+  validate_preset(&mut preset)?;
+  let mut app_settings = settings::load_settings()?;
+  let presets = &mut app_settings.presets;
+
+  let existing = position(presets, &preset.name);
+  match previous_name.and_then(|name| position(presets, name)) {
+    Some(index) => {
+      // 名前の変更先が別のプリセットと重なる場合は上書きしない
+      if existing.is_some_and(|other| other != index) {
+        return Err(format!("error.preset_exists:{}", preset.name));
+      }
+      presets[index] = preset;
+    }
+    None => match existing {
+      Some(index) => presets[index] = preset,
+      None => presets.push(preset),
+    },
+  }
+
+  settings::save_settings(&app_settings)?;
+  Ok(app_settings.presets)
+}
+
+pub fn delete_preset(name: &str) -> Result<Vec<DownloadPreset>, String> {
+  let mut app_settings = settings::load_settings()?;
+  let index =
+    position(&app_settings.presets, name).ok_or_else(|| format!("error.preset_not_found:{name}"))?;
+  app_settings.presets.remove(index);
+  settings::save_settings(&app_settings)?;
+  Ok(app_settings.presets)
+}
+
+// ─── インポート・エクスポート ───────────────────────────
+
+/// プリセットを JSON ファイルに書き出す（names が None の場合はすべて）
+pub fn export_presets(path: &Path, names: Option<&[String]>) -> Result<usize, String> {
+  let presets: Vec<DownloadPreset> = list_presets()?
+    .into_iter()
+    .filter(|preset| {
+      names.map_or(true, |names| names.iter().any(|name| name.trim().eq_ignore_ascii_case(&preset.name)))
+    })
+    .collect();
+  let count = presets.len();
+
+  let json = serde_json::to_string_pretty(&PresetExport { version: EXPORT_VERSION, presets })
+    .map_err(|e| format!("error.preset_export_failed:{e}"))?;
+  fs::write(path, json).map_err(|e| format!("error.preset_export_failed:{e}"))?;
+  Ok(count)
+}
+
+/// JSON ファイルのプリセットを取り込む（同じ名前のプリセットは置き換える）
+///
+/// 1つでも不正なプリセットがあれば何も取り込まない。取り込んだプリセットを返す。
+pub fn import_presets(path: &Path) -> Result<Vec<DownloadPreset>, String> {
+  let content = fs::read_to_string(path).map_err(|e| format!("error.preset_import_failed:{e}"))?;
+  let mut imported = match serde_json::from_str(&content)
+    .map_err(|e| format!("error.preset_import_failed:{e}"))?
+  {
+    PresetImport::Export(export) => export.presets,
+    PresetImport::List(presets) => presets,
+  };
+  for preset in &mut imported {
+    validate_preset(preset)?;
+  }
+
+  let mut app_settings = settings::load_settings()?;
+  for preset in &imported {
+    match position(&app_settings.presets, &preset.name) {
+      Some(index) => app_settings.presets[index] = preset.clone(),
+      None => app_settings.presets.push(preset.clone()),
+    }
+  }
+  settings::save_settings(&app_settings)?;
+  log::info!("プリセットを {} 件取り込みました", imported.len());
+  Ok(imported)
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::credentials;
 use crate::utils::{ensure_app_data_dir, get_download_dir};
 
 /// サブディレクトリ名
@@ -25,6 +27,225 @@ pub struct AppSettings {
   pub notif_sound: bool,
   #[serde(default = "default_language")]
   pub language: String,
+  /// 同梱バイナリのバージョン設定
+  #[serde(default)]
+  pub binaries: BinarySettings,
+  /// yt-dlp とバイナリのダウンロードに使うプロキシ
+  #[serde(default)]
+  pub proxy: ProxySettings,
+  /// TLS 検証・IP バージョンなどの接続設定
+  #[serde(default)]
+  pub network: NetworkSettings,
+  /// サイトごとの HTTP ヘッダー設定
+  #[serde(default)]
+  pub site_profiles: Vec<SiteProfile>,
+  /// ログファイルに出力するレベル
+  #[serde(default)]
+  pub log_level: LogLevel,
+  /// 外部ツール向けのローカル HTTP API
+  #[serde(default)]
+  pub api: ApiSettings,
+  /// クリップボードの URL 検出
+  #[serde(default)]
+  pub clipboard: ClipboardSettings,
+  /// URL を書いたファイルを置くとダウンロードする監視フォルダ
+  #[serde(default)]
+  pub watch_folder: WatchFolderSettings,
+  /// 名前付きのダウンロードオプション
+  #[serde(default)]
+  pub presets: Vec<DownloadPreset>,
+}
+
+/// ログレベル
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+  Error,
+  Warn,
+  #[default]
+  Info,
+  Debug,
+  Trace,
+}
+
+/// サイトごとに yt-dlp へ渡す HTTP ヘッダーの設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SiteProfile {
+  pub name: String,
+  /// 適用するドメイン（サブドメインにも一致する）
+  pub domains: Vec<String>,
+  pub user_agent: Option<String>,
+  pub referer: Option<String>,
+  /// 追加のヘッダー（名前 → 値）
+  pub headers: BTreeMap<String, String>,
+}
+
+/// プロキシの種類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyScheme {
+  #[default]
+  Http,
+  Https,
+  Socks5,
+}
+
+/// プロキシ設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxySettings {
+  pub enabled: bool,
+  pub scheme: ProxyScheme,
+  pub host: String,
+  pub port: u16,
+  pub username: Option<String>,
+  /// 入力されたパスワード（設定ファイルには書かず、保存時に認証情報ストアへ移す）
+  ///
+  /// `None` なら保存済みの値を維持し、空文字なら削除する。
+  #[serde(serialize_with = "serialize_as_none")]
+  pub password: Option<String>,
+  /// プロキシを経由しないホスト（"localhost", ".example.com" など）
+  pub no_proxy: Vec<String>,
+}
+
+/// 秘密情報を常に null として書き出す（設定ファイルやフロントエンドに出さない）
+fn serialize_as_none<S: serde::Serializer>(_: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+  serializer.serialize_none()
+}
+
+/// 接続設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+  /// TLS 証明書の検証を無効にする（yt-dlp の --no-check-certificate）
+  pub skip_certificate_check: bool,
+  /// IPv4 のみで接続する（yt-dlp の --force-ipv4）
+  pub force_ipv4: bool,
+  /// 追加で信頼する CA 証明書（PEM）のパス
+  pub ca_bundle_path: Option<String>,
+}
+
+/// ローカル HTTP API の設定
+///
+/// 127.0.0.1 のみで待ち受け、すべてのリクエストにトークンを要求する。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiSettings {
+  pub enabled: bool,
+  pub port: u16,
+  /// Bearer トークン（有効化時に空なら自動生成する）
+  pub token: String,
+}
+
+impl Default for ApiSettings {
+  fn default() -> Self {
+    Self { enabled: false, port: 17_385, token: String::new() }
+  }
+}
+
+/// クリップボード監視の設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClipboardSettings {
+  pub enabled: bool,
+  /// 検出した URL を確認なしでキューに追加する
+  pub auto_enqueue: bool,
+  /// 自動で追加するときのプリセット（None は既定のオプション）
+  pub preset: Option<String>,
+}
+
+/// 監視フォルダの設定
+///
+/// 処理したファイルはフォルダ内の `done/`・`failed/` に移動する。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchFolderSettings {
+  pub enabled: bool,
+  pub path: String,
+  /// このフォルダから追加するダウンロードのプリセット（None は既定のオプション）
+  pub preset: Option<String>,
+}
+
+/// ダウンロードのオプション
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DownloadOptions {
+  pub audio_only: bool,
+  /// 保存先（None は設定の保存先）
+  pub folder_path: Option<String>,
+  /// 映像と音声を別々に取得して結合する（false は単一ファイルの最高画質）
+  pub best_quality: bool,
+  pub download_subtitles: bool,
+  /// 映像は mp4 / mkv / webm、音声は mp3 / m4a
+  pub preferred_format: Option<String>,
+}
+
+impl Default for DownloadOptions {
+  fn default() -> Self {
+    Self {
+      audio_only: false,
+      folder_path: None,
+      best_quality: true,
+      download_subtitles: false,
+      preferred_format: None,
+    }
+  }
+}
+
+/// 名前付きのダウンロードオプション（"Podcast m4a" など）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadPreset {
+  pub name: String,
+  #[serde(default)]
+  pub options: DownloadOptions,
+}
+
+/// yt-dlp のリリースチャンネル
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YtDlpChannel {
+  #[default]
+  Stable,
+  Nightly,
+  Master,
+}
+
+/// 使用するバイナリの取得元
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BinarySource {
+  /// アプリデータディレクトリにダウンロードした同梱版
+  #[default]
+  Bundled,
+  /// PATH から見つかったシステムのバイナリ（pipx, Homebrew 等）
+  System,
+  /// 任意のパス（FFmpeg はバイナリまたは ffmpeg/ffprobe を含むディレクトリ）
+  Custom { path: String },
+}
+
+/// yt-dlp / FFmpeg / Deno の取得元とバージョン固定設定
+///
+/// バージョンが `None` の場合は最新版（yt-dlp はチャンネルの最新版）を使用する。
+/// バージョン固定は同梱版にのみ適用される。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BinarySettings {
+  pub yt_dlp_source: BinarySource,
+  pub ffmpeg_source: BinarySource,
+  pub deno_source: BinarySource,
+  pub yt_dlp_channel: YtDlpChannel,
+  /// yt-dlp のリリースタグ（例: "2025.01.15"）
+  pub yt_dlp_version: Option<String>,
+  /// ffmpeg-static のリリースタグ（例: "b6.0"）
+  pub ffmpeg_version: Option<String>,
+  /// Deno のリリースタグ（例: "v2.1.4"）
+  pub deno_version: Option<String>,
+  /// ダウンロード元ミラーのベースURL（`https://github.com/{owner}/{repo}/releases` の代替）
+  pub yt_dlp_mirror: Option<String>,
+  pub ffmpeg_mirror: Option<String>,
+  pub deno_mirror: Option<String>,
 }
 
 fn default_language() -> String {
@@ -48,6 +269,15 @@ impl Default for AppSettings {
       notif_error: true,
       notif_sound: false,
       language: default_language(),
+      binaries: BinarySettings::default(),
+      proxy: ProxySettings::default(),
+      network: NetworkSettings::default(),
+      site_profiles: Vec::new(),
+      log_level: LogLevel::default(),
+      api: ApiSettings::default(),
+      clipboard: ClipboardSettings::default(),
+      watch_folder: WatchFolderSettings::default(),
+      presets: Vec::new(),
     }
   }
 }
@@ -134,16 +364,30 @@ pub fn load_settings() -> Result<AppSettings, String> {
   let content =
     fs::read_to_string(&path).map_err(|e| format!("error.settings_read_failed:{e}"))?;
 
-  serde_json::from_str(&content).map_err(|e| {
+  let mut app_settings: AppSettings = serde_json::from_str(&content).map_err(|e| {
     log::warn!("設定ファイルのパースに失敗: {e}、デフォルト値を使用します");
     format!("error.settings_parse_failed:{e}")
-  })
+  })?;
+
+  // 以前のバージョンが平文で保存したプロキシのパスワードを認証情報ストアに移す
+  if app_settings.proxy.password.is_some() {
+    save_settings(&app_settings)?;
+    app_settings.proxy.password = None;
+    log::info!("プロキシのパスワードを認証情報ストアに移行しました");
+  }
+  Ok(app_settings)
 }
 
 /// 設定を保存する
+///
+/// プロキシのパスワードが入力されていれば、設定ファイルではなく認証情報ストアに保存する。
 pub fn save_settings(settings: &AppSettings) -> Result<(), String> {
   let path = settings_path()?;
 
+  if let Some(password) = &settings.proxy.password {
+    credentials::set_proxy_password(password)?;
+  }
+
   let content = serde_json::to_string_pretty(settings)
     .map_err(|e| format!("error.settings_serialize_failed:{e}"))?;
 
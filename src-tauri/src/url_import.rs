@@ -0,0 +1,437 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+#[cfg(feature = "desktop")]
+use crate::commands;
+use crate::download::clean_timestamp_param;
+#[cfg(feature = "desktop")]
+use crate::download::DownloadRequest;
+use crate::utils::is_valid_url;
+
+/// 取り込むファイルの上限サイズ
+const MAX_IMPORT_FILE_SIZE: u64 = 16 * 1024 * 1024;
+/// CSV で URL 列とみなすヘッダー名（小文字）
+const CSV_URL_HEADERS: &[&str] = &["url", "urls", "link", "links", "href", "video", "video url"];
+/// CSV でタイトル列とみなすヘッダー名（小文字）
+const CSV_TITLE_HEADERS: &[&str] = &["title", "name", "タイトル", "名前"];
+
+/// Netscape ブックマーク形式の `<A HREF="...">タイトル</A>`
+static RE_BOOKMARK_LINK: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r#"(?is)<a\s[^>]*?href\s*=\s*["']([^"']*)["'][^>]*>(.*?)</a>"#).unwrap()
+});
+static RE_HTML_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+/// 取り込んだファイルの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+  /// 1行に1つの URL（`#` で始まる行はコメント）
+  Text,
+  /// ブラウザのブックマークエクスポート（Netscape 形式の HTML）
+  Bookmarks,
+  /// URL 列を含む CSV
+  Csv,
+}
+
+/// 取り込んだ URL の判定結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportedUrlStatus {
+  Accepted,
+  Invalid,
+  /// 同じファイル内で既に出現した URL
+  Duplicate,
+}
+
+/// プレビューの1行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedUrl {
+  /// タイムスタンプを除去した URL（無効な場合は元の文字列）
+  pub url: String,
+  /// ブックマーク名・CSV のタイトル列
+  pub title: Option<String>,
+  /// ファイル内の行番号（1始まり）
+  pub line: usize,
+  pub status: ImportedUrlStatus,
+}
+
+/// `import_urls` の結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlImportPreview {
+  pub format: ImportFormat,
+  pub entries: Vec<ImportedUrl>,
+  pub accepted_count: usize,
+}
+
+/// ファイルから取り出した URL の候補
+struct Candidate {
+  url: String,
+  title: Option<String>,
+  line: usize,
+}
+
+/// 拡張子と内容から形式を判定する
+fn detect_format(path: &Path, content: &str) -> ImportFormat {
+  let extension = path
+    .extension()
+    .map(|ext| ext.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+  match extension.as_str() {
+    "html" | "htm" => ImportFormat::Bookmarks,
+    "csv" => ImportFormat::Csv,
+    _ if content.contains("NETSCAPE-Bookmark-file") => ImportFormat::Bookmarks,
+    _ => ImportFormat::Text,
+  }
+}
+
+// ─── 形式ごとの読み込み ─────────────────────────────
+
+fn parse_text(content: &str) -> Vec<Candidate> {
+  content
+    .lines()
+    .enumerate()
+    .filter_map(|(index, line)| {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        return None;
+      }
+      Some(Candidate { url: line.to_string(), title: None, line: index + 1 })
+    })
+    .collect()
+}
+
+/// ブックマーク HTML でよく使われる文字参照だけを戻す
+fn decode_html_entities(text: &str) -> String {
+  text
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&quot;", "\"")
+    .replace("&#39;", "'")
+    .replace("&#x27;", "'")
+    .replace("&amp;", "&")
+}
+
+fn parse_bookmarks(content: &str) -> Vec<Candidate> {
+  // 行番号は直前のリンクからの改行数を足していく
+  let mut line = 1;
+  let mut offset = 0;
+  RE_BOOKMARK_LINK
+    .captures_iter(content)
+    .map(|caps| {
+      let start = caps.get(0).unwrap().start();
+      line += content[offset..start].matches('\n').count();
+      offset = start;
+
+      let title = decode_html_entities(RE_HTML_TAG.replace_all(&caps[2], "").trim());
+      Candidate {
+        url: decode_html_entities(caps[1].trim()),
+        title: (!title.is_empty()).then_some(title),
+        line,
+      }
+    })
+    .collect()
+}
+
+/// CSV の区切り文字を先頭行（ヘッダー）から判定する
+///
+/// カンマ・セミコロン・タブのうち、クォート外で最も多く現れたものを使う。
+/// いずれも現れない（1列だけの）場合はカンマとみなす。
+fn detect_delimiter(content: &str) -> char {
+  let mut counts = [(',', 0usize), (';', 0), ('\t', 0)];
+  let mut in_quotes = false;
+  for c in content.chars() {
+    match c {
+      '"' => in_quotes = !in_quotes,
+      '\n' if !in_quotes => break,
+      _ if !in_quotes => {
+        if let Some((_, count)) = counts.iter_mut().find(|(delimiter, _)| *delimiter == c) {
+          *count += 1;
+        }
+      }
+      _ => {}
+    }
+  }
+  // 同数のときは配列の順（カンマ優先）にする
+  counts
+    .iter()
+    .rev()
+    .max_by_key(|(_, count)| *count)
+    .filter(|(_, count)| *count > 0)
+    .map_or(',', |(delimiter, _)| *delimiter)
+}
+
+/// CSV を行ごとのフィールドに分割し、各行の開始行番号と組にして返す
+///
+/// 区切り文字はヘッダー行から判定した1種類だけを使い（URL に含まれる `;` などで
+/// 列がずれないようにする）、ダブルクォートで囲まれた区切り文字・改行は
+/// フィールドの一部として扱う。
+fn split_csv(content: &str) -> Vec<(usize, Vec<String>)> {
+  let delimiter = detect_delimiter(content);
+  let mut rows = Vec::new();
+  let mut fields = Vec::new();
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut line = 1;
+  let mut row_line = 1;
+  let mut chars = content.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if in_quotes && chars.peek() == Some(&'"') => {
+        chars.next();
+        field.push('"');
+      }
+      '"' => in_quotes = !in_quotes,
+      _ if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+      '\r' if !in_quotes => {}
+      '\n' if !in_quotes => {
+        fields.push(std::mem::take(&mut field));
+        rows.push((row_line, std::mem::take(&mut fields)));
+        line += 1;
+        row_line = line;
+      }
+      '\n' => {
+        field.push(c);
+        line += 1;
+      }
+      _ => field.push(c),
+    }
+  }
+  if !field.is_empty() || !fields.is_empty() {
+    fields.push(field);
+    rows.push((row_line, fields));
+  }
+  rows
+}
+
+fn find_column(header: &[String], names: &[&str]) -> Option<usize> {
+  header
+    .iter()
+    .position(|cell| names.contains(&cell.trim().to_lowercase().as_str()))
+}
+
+fn parse_csv(content: &str) -> Vec<Candidate> {
+  let rows = split_csv(content);
+  let Some((_, header)) = rows.first() else {
+    return Vec::new();
+  };
+
+  // ヘッダーに URL 列がなければ、最初に URL が入っている列を使う（ヘッダーなしの CSV）
+  let (url_column, title_column, skip) = match find_column(header, CSV_URL_HEADERS) {
+    Some(column) => (column, find_column(header, CSV_TITLE_HEADERS), 1),
+    None => {
+      let column = rows.iter().find_map(|(_, fields)| {
+        fields.iter().position(|cell| is_valid_url(cell.trim()))
+      });
+      match column {
+        Some(column) => (column, None, 0),
+        None => return Vec::new(),
+      }
+    }
+  };
+
+  rows
+    .into_iter()
+    .skip(skip)
+    .filter_map(|(line, fields)| {
+      let url = fields.get(url_column)?.trim().to_string();
+      if url.is_empty() {
+        return None;
+      }
+      let title = title_column
+        .and_then(|column| fields.get(column))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty());
+      Some(Candidate { url, title, line })
+    })
+    .collect()
+}
+
+// ─── 取り込み ─────────────────────────────────────
+
+/// URL 一覧のファイルを読み込み、検証・重複除去した結果を返す
+///
+/// テキスト（1行1URL、`#` はコメント）、Netscape 形式のブックマーク HTML、
+/// URL 列を含む CSV に対応する。キューへの追加は行わない。
+pub fn import_urls(path: &Path) -> Result<UrlImportPreview, String> {
+  let size = fs::metadata(path)
+    .map_err(|e| format!("error.import_read_failed:{e}"))?
+    .len();
+  if size > MAX_IMPORT_FILE_SIZE {
+    return Err("error.import_file_too_large".to_string());
+  }
+  let bytes = fs::read(path).map_err(|e| format!("error.import_read_failed:{e}"))?;
+  let content = String::from_utf8_lossy(&bytes);
+  let content = content.trim_start_matches('\u{feff}');
+
+  let format = detect_format(path, content);
+  let candidates = match format {
+    ImportFormat::Text => parse_text(content),
+    ImportFormat::Bookmarks => parse_bookmarks(content),
+    ImportFormat::Csv => parse_csv(content),
+  };
+
+  let mut seen = HashSet::new();
+  let entries: Vec<ImportedUrl> = candidates
+    .into_iter()
+    .map(|candidate| {
+      let (url, status) = if !is_valid_url(&candidate.url) {
+        (candidate.url, ImportedUrlStatus::Invalid)
+      } else {
+        let url = clean_timestamp_param(&candidate.url);
+        let status = if seen.insert(url.clone()) {
+          ImportedUrlStatus::Accepted
+        } else {
+          ImportedUrlStatus::Duplicate
+        };
+        (url, status)
+      };
+      ImportedUrl { url, title: candidate.title, line: candidate.line, status }
+    })
+    .collect();
+
+  let accepted_count =
+    entries.iter().filter(|entry| entry.status == ImportedUrlStatus::Accepted).count();
+  log::info!(
+    "URL 一覧を読み込みました ({}): {accepted_count}/{} 件",
+    path.display(),
+    entries.len()
+  );
+  Ok(UrlImportPreview { format, entries, accepted_count })
+}
+
+/// プレビューで選んだ URL を共通のオプションでキューに追加し、ダウンロードIDを返す
+///
+/// `options` の url・download_id・metadata_title は URL ごとに置き換える。
+/// オプションが不正な場合は1件も追加せずにエラーを返す。
+#[cfg(feature = "desktop")]
+pub fn enqueue_urls(
+  app_handle: &tauri::AppHandle,
+  entries: Vec<ImportedUrl>,
+  options: DownloadRequest,
+) -> Result<Vec<String>, String> {
+  let mut ids = Vec::with_capacity(entries.len());
+  for entry in entries {
+    if entry.status != ImportedUrlStatus::Accepted {
+      continue;
+    }
+    let request = DownloadRequest {
+      download_id: String::new(),
+      url: entry.url,
+      metadata_title: entry.title,
+      ..options.clone()
+    };
+    match commands::enqueue_download(app_handle, request) {
+      Ok(id) => ids.push(id),
+      // 最初の1件で失敗した場合はオプション自体の誤りとして扱う
+      Err(e) if ids.is_empty() => return Err(e),
+      Err(e) => log::warn!("取り込んだ URL をキューに追加できません: {e}"),
+    }
+  }
+  Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fields(rows: &[(usize, Vec<String>)]) -> Vec<Vec<&str>> {
+    rows
+      .iter()
+      .map(|(_, fields)| fields.iter().map(String::as_str).collect())
+      .collect()
+  }
+
+  #[test]
+  fn split_csv_uses_header_delimiter_only() {
+    let rows = split_csv("url,title\nhttps://example.com/watch?a=1;b=2,Video\n");
+    assert_eq!(
+      fields(&rows),
+      vec![vec!["url", "title"], vec!["https://example.com/watch?a=1;b=2", "Video"]]
+    );
+
+    let rows = split_csv("url;title\nhttps://example.com/a,b;Video\n");
+    assert_eq!(
+      fields(&rows),
+      vec![vec!["url", "title"], vec!["https://example.com/a,b", "Video"]]
+    );
+
+    let rows = split_csv("url\ttitle\nhttps://example.com/\tVideo");
+    assert_eq!(fields(&rows), vec![vec!["url", "title"], vec!["https://example.com/", "Video"]]);
+  }
+
+  #[test]
+  fn split_csv_handles_quotes_and_line_numbers() {
+    let content =
+      "url,title\r\n\"https://example.com/\",\"a \"\"quoted\"\",\nmultiline\"\nhttps://example.org/,x\n";
+    let rows = split_csv(content);
+    assert_eq!(
+      fields(&rows),
+      vec![
+        vec!["url", "title"],
+        vec!["https://example.com/", "a \"quoted\",\nmultiline"],
+        vec!["https://example.org/", "x"],
+      ]
+    );
+    let lines: Vec<usize> = rows.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, vec![1, 2, 4]);
+  }
+
+  #[test]
+  fn split_csv_defaults_to_comma_for_single_column() {
+    assert_eq!(detect_delimiter("url\nhttps://example.com/a;b\n"), ',');
+    let rows = split_csv("url\nhttps://example.com/a;b\n");
+    assert_eq!(fields(&rows), vec![vec!["url"], vec!["https://example.com/a;b"]]);
+  }
+
+  #[test]
+  fn parse_csv_finds_url_and_title_columns() {
+    let candidates =
+      parse_csv("Title;URL\nFirst;https://example.com/1\n;\nSecond;https://example.com/2\n");
+    let parsed: Vec<_> = candidates
+      .iter()
+      .map(|c| (c.url.as_str(), c.title.as_deref(), c.line))
+      .collect();
+    assert_eq!(
+      parsed,
+      vec![
+        ("https://example.com/1", Some("First"), 2),
+        ("https://example.com/2", Some("Second"), 4),
+      ]
+    );
+  }
+
+  #[test]
+  fn parse_bookmarks_extracts_links_titles_and_lines() {
+    let content = "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n<DL><p>\n\
+      <DT><A HREF=\"https://example.com/?a=1&amp;b=2\" ADD_DATE=\"0\">Tom &amp; <b>Jerry</b></A>\n\
+      <DT><A HREF='https://example.org/'></A>\n</DL>\n";
+    let candidates = parse_bookmarks(content);
+    let parsed: Vec<_> = candidates
+      .iter()
+      .map(|c| (c.url.as_str(), c.title.as_deref(), c.line))
+      .collect();
+    assert_eq!(
+      parsed,
+      vec![
+        ("https://example.com/?a=1&b=2", Some("Tom & Jerry"), 3),
+        ("https://example.org/", None, 4),
+      ]
+    );
+  }
+
+  #[test]
+  fn parse_text_skips_blank_lines_and_comments() {
+    let candidates = parse_text(
+      "# list\nhttps://example.com/1\n\n  https://example.com/2  \n#https://example.com/3\n",
+    );
+    let parsed: Vec<_> = candidates.iter().map(|c| (c.url.as_str(), c.line)).collect();
+    assert_eq!(parsed, vec![("https://example.com/1", 2), ("https://example.com/2", 4)]);
+  }
+}
@@ -23,6 +23,21 @@ pub fn ensure_app_data_dir() -> Result<PathBuf, String> {
   Ok(dir)
 }
 
+/// ファイル・ディレクトリを所有者のみアクセス可能にする
+///
+/// Windows ではユーザープロファイル配下の ACL に任せる。
+#[cfg(unix)]
+pub fn restrict_permissions(path: &Path, mode: u32) -> Result<(), String> {
+  use std::os::unix::fs::PermissionsExt;
+  fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    .map_err(|e| format!("error.permission_failed:{e}"))
+}
+
+#[cfg(not(unix))]
+pub fn restrict_permissions(_path: &Path, _mode: u32) -> Result<(), String> {
+  Ok(())
+}
+
 /// ダウンロードディレクトリを取得する
 pub fn get_download_dir() -> Result<PathBuf, String> {
   directories::UserDirs::new()
@@ -30,6 +45,17 @@ pub fn get_download_dir() -> Result<PathBuf, String> {
     .ok_or_else(|| "error.download_dir_failed".to_string())
 }
 
+/// PATH 環境変数から実行ファイルを探す
+///
+/// `exclude` 配下のディレクトリ（アプリ同梱バイナリの格納先）は対象外とする。
+pub fn find_in_path(binary_name: &str, exclude: &Path) -> Option<PathBuf> {
+  let path_var = std::env::var_os("PATH")?;
+  std::env::split_paths(&path_var)
+    .filter(|dir| !dir.starts_with(exclude))
+    .map(|dir| dir.join(binary_name))
+    .find(|candidate| candidate.is_file())
+}
+
 /// URLが有効かどうかを確認する関数
 pub fn is_valid_url(url: &str) -> bool {
   URL_REGEX.is_match(url)
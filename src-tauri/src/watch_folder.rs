@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+
+use crate::download::{clean_timestamp_param, DownloadRequest};
+use crate::settings::WatchFolderSettings;
+use crate::url_import::{self, ImportedUrl, ImportedUrlStatus};
+use crate::utils::is_valid_url;
+
+/// フォルダを確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// 処理済みファイルの移動先
+const DONE_DIR: &str = "done";
+const FAILED_DIR: &str = "failed";
+/// 対象の拡張子（小文字）
+const SUPPORTED_EXTENSIONS: &[&str] = &["txt", "url", "webloc"];
+
+/// 起動中の監視タスク
+static WATCHER: LazyLock<Mutex<Option<RunningWatcher>>> = LazyLock::new(|| Mutex::new(None));
+
+struct RunningWatcher {
+  dir: PathBuf,
+  stop: watch::Sender<bool>,
+}
+
+/// 設定に合わせて監視を開始・停止・再起動する
+pub fn apply(app_handle: &tauri::AppHandle, folder_settings: &WatchFolderSettings) {
+  let mut watcher = WATCHER.lock().unwrap();
+  let path = PathBuf::from(folder_settings.path.trim());
+  let enabled = folder_settings.enabled && !folder_settings.path.trim().is_empty();
+
+  if let Some(running) = watcher.as_ref() {
+    // ダウンロードのオプションは処理のたびに設定を読み直すため、フォルダが同じなら再起動しない
+    if enabled && running.dir == path {
+      return;
+    }
+  }
+  if let Some(running) = watcher.take() {
+    let _ = running.stop.send(true);
+    log::info!("監視フォルダの監視を停止しました: {}", running.dir.display());
+  }
+  if !enabled {
+    return;
+  }
+  if !path.is_dir() {
+    log::warn!("監視フォルダが存在しないため監視しません: {}", path.display());
+    return;
+  }
+
+  let (stop, stop_rx) = watch::channel(false);
+  *watcher = Some(RunningWatcher { dir: path.clone(), stop });
+  log::info!("監視フォルダの監視を開始しました: {}", path.display());
+  tauri::async_runtime::spawn(watch_folder(app_handle.clone(), path, stop_rx));
+}
+
+async fn watch_folder(app_handle: tauri::AppHandle, dir: PathBuf, mut stop: watch::Receiver<bool>) {
+  // 前回の確認時のサイズ・更新日時（コピー中のファイルを処理しないよう、変化がなくなるまで待つ）
+  let mut pending: HashMap<PathBuf, (u64, SystemTime)> = HashMap::new();
+  // 移動できなかったファイル（同じ URL を繰り返し追加しないよう、以後は処理しない）
+  let mut stuck: HashSet<PathBuf> = HashSet::new();
+
+  loop {
+    tokio::select! {
+      () = tokio::time::sleep(POLL_INTERVAL) => {}
+      _ = stop.changed() => return,
+    }
+
+    let mut current = HashMap::new();
+    for path in list_candidates(&dir) {
+      if stuck.contains(&path) {
+        continue;
+      }
+      let Ok(meta) = fs::metadata(&path) else {
+        continue;
+      };
+      let state = (meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+      if pending.get(&path) == Some(&state) {
+        if !process_file(&app_handle, &dir, &path) {
+          stuck.insert(path);
+        }
+      } else {
+        current.insert(path, state);
+      }
+    }
+    pending = current;
+  }
+}
+
+/// フォルダ直下の対象ファイル（サブフォルダ・隠しファイルは除く）
+fn list_candidates(dir: &Path) -> Vec<PathBuf> {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return Vec::new();
+  };
+  entries
+    .flatten()
+    .map(|entry| entry.path())
+    .filter(|path| path.is_file())
+    .filter(|path| {
+      !path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+    })
+    .filter(|path| {
+      path
+        .extension()
+        .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+    })
+    .collect()
+}
+
+// ─── URL の取り出し ─────────────────────────────────
+
+/// 1つの URL を取り込み結果の形にする
+fn single_url(url: &str) -> Vec<ImportedUrl> {
+  let url = url.trim();
+  if !is_valid_url(url) {
+    return Vec::new();
+  }
+  vec![ImportedUrl {
+    url: clean_timestamp_param(url),
+    title: None,
+    line: 1,
+    status: ImportedUrlStatus::Accepted,
+  }]
+}
+
+/// Windows のインターネットショートカット（INI 形式の `URL=`）
+fn parse_url_shortcut(path: &Path) -> Result<Vec<ImportedUrl>, String> {
+  let bytes = fs::read(path).map_err(|e| format!("error.import_read_failed:{e}"))?;
+  let content = String::from_utf8_lossy(&bytes);
+  let url = content
+    .lines()
+    .find_map(|line| {
+      let (key, value) = line.split_once('=')?;
+      key.trim().eq_ignore_ascii_case("URL").then_some(value)
+    })
+    .unwrap_or_default();
+  Ok(single_url(url))
+}
+
+/// macOS の .webloc（XML・バイナリいずれかの plist の `URL` キー）
+fn parse_webloc(path: &Path) -> Result<Vec<ImportedUrl>, String> {
+  let value = plist::Value::from_file(path).map_err(|e| format!("error.import_read_failed:{e}"))?;
+  let url = value
+    .as_dictionary()
+    .and_then(|dict| dict.get("URL"))
+    .and_then(|url| url.as_string())
+    .unwrap_or_default();
+  Ok(single_url(url))
+}
+
+fn extract_urls(path: &Path) -> Result<Vec<ImportedUrl>, String> {
+  let extension = path
+    .extension()
+    .map(|ext| ext.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+  match extension.as_str() {
+    "url" => parse_url_shortcut(path),
+    "webloc" => parse_webloc(path),
+    _ => Ok(url_import::import_urls(path)?.entries),
+  }
+}
+
+// ─── 処理 ────────────────────────────────────────
+
+/// フォルダに設定したプリセットでダウンロード要求のひな形を作る
+fn request_options() -> DownloadRequest {
+  let folder_settings = crate::settings::load_settings().unwrap_or_default().watch_folder;
+  DownloadRequest { preset: folder_settings.preset, ..DownloadRequest::default() }
+}
+
+/// ファイルの URL をキューに追加し、`done/`・`failed/` に移動する（移動できなければ false）
+fn process_file(app_handle: &tauri::AppHandle, dir: &Path, path: &Path) -> bool {
+  let result = extract_urls(path).and_then(|entries| {
+    if !entries.iter().any(|entry| entry.status == ImportedUrlStatus::Accepted) {
+      return Err("error.invalid_url".to_string());
+    }
+    url_import::enqueue_urls(app_handle, entries, request_options())
+  });
+
+  let subdir = match &result {
+    Ok(ids) => {
+      log::info!("監視フォルダから {} 件を追加しました: {}", ids.len(), path.display());
+      DONE_DIR
+    }
+    Err(e) => {
+      log::warn!("監視フォルダのファイルを処理できません ({}): {e}", path.display());
+      FAILED_DIR
+    }
+  };
+  match move_into(dir, subdir, path) {
+    Ok(()) => true,
+    Err(e) => {
+      log::error!("監視フォルダのファイルを移動できません ({}): {e}", path.display());
+      false
+    }
+  }
+}
+
+/// 処理済みのファイルを `done/`・`failed/` に移動する（同名のファイルがあれば日時を付ける）
+fn move_into(dir: &Path, subdir: &str, path: &Path) -> Result<(), String> {
+  let dest_dir = dir.join(subdir);
+  fs::create_dir_all(&dest_dir).map_err(|e| format!("error.dir_create_failed:{e}"))?;
+
+  let file_name = path.file_name().unwrap_or_default();
+  let mut dest = dest_dir.join(file_name);
+  if dest.exists() {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    dest = dest_dir.join(format!("{stamp}-{}", file_name.to_string_lossy()));
+  }
+  fs::rename(path, &dest).map_err(|e| format!("error.watch_folder_move_failed:{e}"))
+}